@@ -0,0 +1,256 @@
+//! Client for a Jupiter-compatible aggregator API (`GET /quote`, `POST /swap`).
+//!
+//! Only the fields the executor compares on are decoded; the full quote
+//! response is kept verbatim because `/swap` expects it back unchanged.
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use std::time::Duration;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A quote as the aggregator returned it.
+#[derive(Debug, Clone)]
+pub struct ProviderQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// The minimum output the aggregator's transaction will enforce.
+    pub other_amount_threshold: u64,
+    pub price_impact_bps: u32,
+    /// AMM labels of each leg, for logging.
+    pub route: Vec<String>,
+    response: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteResponse {
+    in_amount: String,
+    out_amount: String,
+    other_amount_threshold: String,
+    #[serde(default)]
+    price_impact_pct: Option<String>,
+    #[serde(default)]
+    route_plan: Vec<RoutePlanStep>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoutePlanStep {
+    swap_info: SwapInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwapInfo {
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SwapResponse {
+    swap_transaction: String,
+}
+
+pub struct AggregatorClient {
+    client: Client,
+    base_url: String,
+}
+
+impl AggregatorClient {
+    /// `base_url` is the API root, e.g. `https://quote-api.jup.ag/v6`.
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().timeout(timeout).build()?,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Exact-input quote for `amount_in` of `input_mint`.
+    pub async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64, slippage_bps: u32) -> Result<ProviderQuote> {
+        let url = format!("{}/quote", self.base_url);
+        let response = self.client
+            .get(&url)
+            .query(&[
+                ("inputMint", input_mint),
+                ("outputMint", output_mint),
+                ("amount", &amount_in.to_string()),
+                ("slippageBps", &slippage_bps.to_string()),
+                ("swapMode", "ExactIn"),
+            ])
+            .send()
+            .await
+            .with_context(|| format!("GET {} failed", url))?;
+        let response: Value = read_json(response, &url).await?;
+
+        let decoded: QuoteResponse = serde_json::from_value(response.clone())
+            .with_context(|| format!("unexpected quote from {}", url))?;
+        let amount = |field: &str, value: &str| value.parse::<u64>().with_context(|| format!("quote {} is not an amount: {:?}", field, value));
+        let price_impact_bps = match decoded.price_impact_pct.as_deref() {
+            Some(pct) => {
+                let pct: f64 = pct.parse().with_context(|| format!("quote priceImpactPct is not a number: {:?}", pct))?;
+                (pct * 10_000.0).round().max(0.0) as u32
+            }
+            None => 0,
+        };
+        Ok(ProviderQuote {
+            amount_in: amount("inAmount", &decoded.in_amount)?,
+            amount_out: amount("outAmount", &decoded.out_amount)?,
+            other_amount_threshold: amount("otherAmountThreshold", &decoded.other_amount_threshold)?,
+            price_impact_bps,
+            route: decoded.route_plan.into_iter().map(|step| step.swap_info.label.unwrap_or_default()).collect(),
+            response,
+        })
+    }
+
+    /// The aggregator's unsigned transaction for `quote`, re-encoded as
+    /// base58 bincode for `SolanaApi::sign_and_send_serialized`.
+    pub async fn swap_transaction(&self, quote: &ProviderQuote, user: &Pubkey) -> Result<String> {
+        let url = format!("{}/swap", self.base_url);
+        let body = json!({
+            "quoteResponse": quote.response,
+            "userPublicKey": user.to_string(),
+            // SOL stays wrapped on both sides, as on the router, so fills
+            // are measured in the wSOL account either way.
+            "wrapAndUnwrapSol": false,
+        });
+        let response = self.client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("POST {} failed", url))?;
+        let response: SwapResponse = serde_json::from_value(read_json(response, &url).await?)
+            .with_context(|| format!("unexpected swap response from {}", url))?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&response.swap_transaction)
+            .map_err(|e| anyhow!("swap transaction from {} is not base64: {}", url, e))?;
+        Ok(bs58::encode(bytes).into_string())
+    }
+}
+
+async fn read_json(response: reqwest::Response, url: &str) -> Result<Value> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        bail!("{} returned {}: {}", url, status, body);
+    }
+    serde_json::from_str(&body).with_context(|| format!("{} returned invalid JSON", url))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Answers one connection per canned `(status, body)` and hands back the
+    /// raw requests it received.
+    pub(crate) async fn serve(responses: Vec<(u16, String)>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        serve_with_headers(responses.into_iter().map(|(status, body)| (status, "", body)).collect()).await
+    }
+
+    /// [`serve`] with extra header lines, each ending in `\r\n`, per response.
+    pub(crate) async fn serve_with_headers(responses: Vec<(u16, &'static str, String)>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (status, headers, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                requests.push(read_request(&mut socket).await);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+                    status,
+                    body.len(),
+                    headers,
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            requests
+        });
+        (base_url, handle)
+    }
+
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                if data.len() >= end + 4 + length || n == 0 {
+                    return text;
+                }
+            }
+            if n == 0 {
+                return text;
+            }
+        }
+    }
+
+    pub(crate) fn quote_body(in_amount: u64, out_amount: u64, threshold: u64) -> String {
+        json!({
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "inAmount": in_amount.to_string(),
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "outAmount": out_amount.to_string(),
+            "otherAmountThreshold": threshold.to_string(),
+            "swapMode": "ExactIn",
+            "slippageBps": 50,
+            "priceImpactPct": "0.0012",
+            "routePlan": [{ "swapInfo": { "label": "Whirlpool" }, "percent": 100 }],
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn quote_then_swap_round_trips_the_quote() {
+        let transaction = base64::engine::general_purpose::STANDARD.encode([1u8, 2, 3]);
+        let (base_url, server) = serve(vec![
+            (200, quote_body(1_000, 2_000, 1_990)),
+            (200, json!({ "swapTransaction": transaction, "lastValidBlockHeight": 1 }).to_string()),
+        ])
+        .await;
+        let client = AggregatorClient::new(&format!("{}/", base_url), DEFAULT_TIMEOUT).unwrap();
+
+        let quote = client.quote("So11111111111111111111111111111111111111112", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 1_000, 50).await.unwrap();
+        assert_eq!((quote.amount_in, quote.amount_out, quote.other_amount_threshold), (1_000, 2_000, 1_990));
+        assert_eq!(quote.price_impact_bps, 12);
+        assert_eq!(quote.route, ["Whirlpool"]);
+
+        let user = Pubkey::new_unique();
+        let encoded = client.swap_transaction(&quote, &user).await.unwrap();
+        assert_eq!(bs58::decode(encoded).into_vec().unwrap(), [1, 2, 3]);
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].starts_with("GET /quote?inputMint=So111"), "{}", requests[0]);
+        assert!(requests[0].contains("&amount=1000&slippageBps=50&"), "{}", requests[0]);
+        assert!(requests[1].starts_with("POST /swap "), "{}", requests[1]);
+        let body: Value = serde_json::from_str(&requests[1][requests[1].find("\r\n\r\n").unwrap() + 4..]).unwrap();
+        assert_eq!(body["userPublicKey"], user.to_string());
+        assert_eq!(body["quoteResponse"]["outAmount"], "2000");
+    }
+
+    #[tokio::test]
+    async fn error_statuses_surface_the_body() {
+        let (base_url, _server) = serve(vec![(400, r#"{"error":"no route"}"#.to_string())]).await;
+        let client = AggregatorClient::new(&base_url, DEFAULT_TIMEOUT).unwrap();
+        let err = client.quote("A", "B", 1, 50).await.unwrap_err();
+        assert!(err.to_string().contains("400") && err.to_string().contains("no route"), "{}", err);
+    }
+}
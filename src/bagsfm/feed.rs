@@ -0,0 +1,285 @@
+use super::{BagsFmError, BagsFmLaunch};
+use crate::config::Config;
+use crate::venue::LaunchpadApi;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+
+/// How many token addresses are remembered for dedupe. Launches sharing the
+/// cursor timestamp are returned again on the next poll, so this only has to
+/// cover a few pages.
+const SEEN_CAPACITY: usize = 512;
+
+/// Continuous poller over `/v1/launches` that emits every launch exactly once,
+/// oldest first, and resumes from the persisted cursor after a restart.
+///
+/// The cursor a poll advances lives in memory; only launches passed to
+/// [`LaunchFeed::ack`] (or skipped as backlog) reach the persisted copy, so a
+/// launch fetched but never delivered is fetched again after a restart.
+pub struct LaunchFeed {
+    client: Arc<dyn LaunchpadApi>,
+    state_path: PathBuf,
+    poll_interval: Duration,
+    page_size: u32,
+    skip_backlog: bool,
+    state: FeedState,
+    delivered: FeedState,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FeedState {
+    last_launch_time: Option<DateTime<Utc>>,
+    last_token_address: Option<String>,
+    seen: VecDeque<String>,
+}
+
+impl FeedState {
+    /// Moves the cursor to `launch`; false if it was already seen.
+    fn advance(&mut self, launch: &BagsFmLaunch) -> bool {
+        if self.seen.iter().any(|seen| *seen == launch.token_address) {
+            return false;
+        }
+        if self.seen.len() == SEEN_CAPACITY {
+            self.seen.pop_front();
+        }
+        self.seen.push_back(launch.token_address.clone());
+        self.last_launch_time = Some(launch.launch_time);
+        self.last_token_address = Some(launch.token_address.clone());
+        true
+    }
+}
+
+impl LaunchFeed {
+    pub fn new(client: Arc<dyn LaunchpadApi>, state_path: impl Into<PathBuf>, poll_interval: Duration) -> Result<Self> {
+        let state_path = state_path.into();
+        let state = load_state(&state_path)?;
+        if let Some(last) = &state.last_token_address {
+            log::info!("Resuming launch feed after {} ({})",
+                last, state.last_launch_time.map_or_else(|| "unknown time".to_string(), |t| t.to_rfc3339()));
+        }
+
+        Ok(Self {
+            client,
+            state_path,
+            poll_interval,
+            page_size: 50,
+            skip_backlog: false,
+            delivered: state.clone(),
+            state,
+        })
+    }
+
+    pub fn from_config(client: Arc<dyn LaunchpadApi>, cfg: &Config) -> Result<Self> {
+        Self::new(
+            client,
+            &cfg.launch_feed_state_path,
+            Duration::from_millis(cfg.launch_feed_poll_ms),
+        )
+    }
+
+    /// Make the first poll drain everything up to now, however many pages
+    /// that takes, so only launches that happen after startup are emitted.
+    /// Snipers want this; a history view does not.
+    pub fn skip_backlog(mut self, skip_backlog: bool) -> Self {
+        self.skip_backlog = skip_backlog;
+        self
+    }
+
+    /// Fetches one page past the cursor and returns the launches not seen
+    /// before, oldest first. Ack each one once it has been handled.
+    pub async fn poll(&mut self) -> Result<Vec<BagsFmLaunch>, BagsFmError> {
+        if !self.skip_backlog {
+            return Ok(self.fetch_page().await?.0);
+        }
+
+        let mut skipped = 0;
+        loop {
+            let (fresh, full) = self.fetch_page().await?;
+            skipped += fresh.len();
+            for launch in &fresh {
+                self.delivered.advance(launch);
+            }
+            // A full page of launches sharing the cursor time never advances it.
+            if !full || fresh.is_empty() {
+                break;
+            }
+        }
+        self.skip_backlog = false;
+        self.persist();
+        log::debug!("Skipped {} backlog launches", skipped);
+        Ok(Vec::new())
+    }
+
+    /// Records `launch` as delivered and persists the cursor past it.
+    pub fn ack(&mut self, launch: &BagsFmLaunch) {
+        if self.delivered.advance(launch) {
+            self.persist();
+        }
+    }
+
+    /// One page past the in-memory cursor: the unseen launches, and whether
+    /// the page was full.
+    async fn fetch_page(&mut self) -> Result<(Vec<BagsFmLaunch>, bool), BagsFmError> {
+        let mut launches = self
+            .client
+            .get_launches_since(self.state.last_launch_time, self.page_size)
+            .await?;
+        let full = launches.len() >= self.page_size as usize;
+        launches.sort_by_key(|launch| launch.launch_time);
+        let fresh = launches.into_iter().filter(|launch| self.state.advance(launch)).collect();
+        Ok((fresh, full))
+    }
+
+    /// Runs the feed on a background task. The task stops once the receiver
+    /// is dropped.
+    pub fn spawn(mut self, buffer: usize) -> mpsc::Receiver<BagsFmLaunch> {
+        let (tx, rx) = mpsc::channel(buffer);
+        tokio::spawn(async move {
+            loop {
+                let wait = match self.poll().await {
+                    Ok(launches) => {
+                        for launch in launches {
+                            if tx.send(launch.clone()).await.is_err() {
+                                return;
+                            }
+                            self.ack(&launch);
+                        }
+                        self.poll_interval
+                    }
+                    Err(BagsFmError::RateLimited { retry_after }) => {
+                        retry_after.unwrap_or(self.poll_interval).max(self.poll_interval)
+                    }
+                    Err(e) => {
+                        log::warn!("Launch feed poll failed: {}", e);
+                        self.poll_interval * 2
+                    }
+                };
+
+                if tx.is_closed() {
+                    return;
+                }
+                sleep(wait).await;
+            }
+        });
+        rx
+    }
+
+    fn persist(&self) {
+        if let Err(e) = save_state(&self.state_path, &self.delivered) {
+            log::warn!("Failed to persist launch feed cursor: {:#}", e);
+        }
+    }
+}
+
+fn save_state(path: &Path, state: &FeedState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn load_state(path: &Path) -> Result<FeedState> {
+    if !path.exists() {
+        return Ok(FeedState::default());
+    }
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read launch feed state {}", path.display()))?;
+    serde_json::from_str(&raw)
+        .with_context(|| format!("corrupt launch feed state {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockLaunchpad;
+    use crate::numeric::Decimal;
+    use chrono::TimeZone;
+
+    fn launch(token_address: &str, launch_time: i64) -> BagsFmLaunch {
+        BagsFmLaunch {
+            token_address: token_address.to_string(),
+            initial_liquidity_sol: Decimal::new(1, 0),
+            launch_time: Utc.timestamp_opt(launch_time, 0).unwrap(),
+            status: "live".to_string(),
+        }
+    }
+
+    fn addresses(launches: &[BagsFmLaunch]) -> Vec<&str> {
+        launches.iter().map(|launch| launch.token_address.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn emits_each_launch_once_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let launchpad = Arc::new(MockLaunchpad::default().with_launch(launch("B", 200)).with_launch(launch("A", 100)));
+        let mut feed = LaunchFeed::new(launchpad.clone(), dir.path().join("feed.json"), Duration::ZERO).unwrap();
+
+        assert_eq!(addresses(&feed.poll().await.unwrap()), ["A", "B"]);
+        // B shares the cursor timestamp, so the API returns it again.
+        assert!(feed.poll().await.unwrap().is_empty());
+
+        launchpad.launches.lock().unwrap().extend([launch("C", 200), launch("D", 300)]);
+        assert_eq!(addresses(&feed.poll().await.unwrap()), ["C", "D"]);
+    }
+
+    #[tokio::test]
+    async fn restart_resumes_from_the_saved_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state").join("feed.json");
+        let launchpad = Arc::new(MockLaunchpad::default().with_launch(launch("A", 100)).with_launch(launch("B", 200)));
+
+        let mut feed = LaunchFeed::new(launchpad.clone(), &state_path, Duration::ZERO).unwrap();
+        let launches = feed.poll().await.unwrap();
+        assert_eq!(addresses(&launches), ["A", "B"]);
+        launches.iter().for_each(|launch| feed.ack(launch));
+        drop(feed);
+
+        launchpad.launches.lock().unwrap().push(launch("C", 300));
+        let mut restarted = LaunchFeed::new(launchpad.clone(), &state_path, Duration::ZERO).unwrap();
+        assert_eq!(addresses(&restarted.poll().await.unwrap()), ["C"]);
+        let cursors = launchpad.launch_cursors.lock().unwrap().clone();
+        assert_eq!(cursors, [None, Some(Utc.timestamp_opt(200, 0).unwrap())]);
+    }
+
+    #[tokio::test]
+    async fn launches_fetched_but_not_acked_come_back_after_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("feed.json");
+        let launchpad = Arc::new(MockLaunchpad::default().with_launch(launch("A", 100)).with_launch(launch("B", 200)));
+
+        let mut feed = LaunchFeed::new(launchpad.clone(), &state_path, Duration::ZERO).unwrap();
+        let launches = feed.poll().await.unwrap();
+        feed.ack(&launches[0]);
+        drop(feed);
+
+        let mut restarted = LaunchFeed::new(launchpad, &state_path, Duration::ZERO).unwrap();
+        assert_eq!(addresses(&restarted.poll().await.unwrap()), ["B"]);
+    }
+
+    #[tokio::test]
+    async fn skip_backlog_drains_every_stale_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("feed.json");
+        let launchpad = Arc::new(MockLaunchpad::default());
+        launchpad.launches.lock().unwrap().extend((1..=5).map(|i| launch(&format!("Old{}", i), i * 100)));
+        let mut feed = LaunchFeed::new(launchpad.clone(), &state_path, Duration::ZERO).unwrap().skip_backlog(true);
+        feed.page_size = 2;
+
+        assert!(feed.poll().await.unwrap().is_empty());
+        launchpad.launches.lock().unwrap().push(launch("C", 600));
+        assert_eq!(addresses(&feed.poll().await.unwrap()), ["C"]);
+
+        // The skipped backlog is persisted too, so a restart does not replay it.
+        let mut restarted = LaunchFeed::new(launchpad, &state_path, Duration::ZERO).unwrap();
+        assert_eq!(addresses(&restarted.poll().await.unwrap()), ["C"]);
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Creator fees accrued on one launched token and not yet claimed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClaimableFee {
+    pub token_mint: String,
+    pub claimable_lamports: u64,
+    #[serde(default)]
+    pub total_claimed_lamports: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeClaimRequest {
+    pub wallet: String,
+    pub token_mint: String,
+}
+
+/// Claiming can take more than one transaction (e.g. curve fees plus fees
+/// from the migrated pool); submit them in order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeClaimTransactions {
+    pub transactions: Vec<String>,
+}
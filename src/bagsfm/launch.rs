@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// Token metadata uploaded to bags.fm before the launch transaction is built.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LaunchMetadataRequest {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telegram: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+}
+
+/// bags.fm reserves the mint address when metadata is created.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LaunchMetadata {
+    pub token_mint: String,
+    pub metadata_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchTransactionRequest {
+    pub token_mint: String,
+    pub metadata_uri: String,
+    /// Wallet that pays for and signs the launch (the creator).
+    pub wallet: String,
+    /// Creator's initial buy on the bonding curve, in lamports.
+    pub initial_buy_lamports: u64,
+}
+
+/// Unsigned (or partially signed) launch transaction, bincode-serialized
+/// `VersionedTransaction` encoded as base58.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LaunchTransaction {
+    pub transaction: String,
+}
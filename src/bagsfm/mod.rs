@@ -0,0 +1,335 @@
+use crate::config::Config;
+use anyhow::{anyhow, Result};
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Client, Method, Proxy, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::time::Duration;
+use thiserror::Error;
+
+mod cache;
+mod feed;
+mod fees;
+mod launch;
+mod rate_limit;
+mod types;
+
+pub use cache::CacheStats;
+pub use feed::LaunchFeed;
+pub use fees::{ClaimableFee, FeeClaimRequest, FeeClaimTransactions};
+pub use launch::{LaunchMetadata, LaunchMetadataRequest, LaunchTransaction, LaunchTransactionRequest};
+pub use types::{BagsFmLaunch, BagsFmToken};
+use cache::TtlCache;
+use rate_limit::RateLimiter;
+
+/// Failure modes of the bags.fm API. Callers are expected to match on these
+/// and decide whether to retry, skip or abort — there is no silent fallback.
+#[derive(Debug, Error)]
+pub enum BagsFmError {
+    #[error("bags.fm resource not found: {0}")]
+    NotFound(String),
+    #[error("bags.fm rate limit hit (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+    #[error("bags.fm rejected the API key")]
+    Unauthorized,
+    #[error("bags.fm server error {status}: {body}")]
+    Server { status: u16, body: String },
+    #[error("failed to decode bags.fm response for {path}: {message}")]
+    Decode { path: String, message: String },
+    #[error("bags.fm request failed: {0}")]
+    Transport(#[from] reqwest::Error),
+}
+
+pub const DEFAULT_BASE_URL: &str = "https://api.bags.fm";
+pub const DEFAULT_USER_AGENT: &str = "ReoswellEcho-Bot/1.0";
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+pub struct BagsFmClient {
+    client: Client,
+    base_url: String,
+    limiter: RateLimiter,
+    max_retries: u32,
+    token_cache: TtlCache<BagsFmToken>,
+}
+
+/// Cache effectiveness for the token lookups.
+#[derive(Debug, Clone, Copy)]
+pub struct BagsFmCacheStats {
+    pub tokens: CacheStats,
+}
+
+/// Builder for [`BagsFmClient`]. Every knob has a production default so the
+/// builder can be pointed at staging or a local stub by overriding only the
+/// base URL.
+pub struct BagsFmClientBuilder {
+    base_url: String,
+    api_key: Option<String>,
+    user_agent: String,
+    timeout: Duration,
+    proxy: Option<String>,
+    rate_limit_rps: f64,
+    cache_ttl: Duration,
+    max_retries: u32,
+}
+
+impl Default for BagsFmClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            timeout: Duration::from_secs(10),
+            proxy: None,
+            rate_limit_rps: 5.0,
+            cache_ttl: Duration::from_secs(30),
+            max_retries: 3,
+        }
+    }
+}
+
+impl BagsFmClientBuilder {
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn proxy(mut self, proxy: Option<String>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn rate_limit_rps(mut self, rate_limit_rps: f64) -> Self {
+        self.rate_limit_rps = rate_limit_rps;
+        self
+    }
+
+    /// Zero disables caching of token and pool lookups.
+    pub fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// How many times a 429 response is retried after waiting out `Retry-After`.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn build(self) -> Result<BagsFmClient> {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = &self.api_key {
+            let mut value = HeaderValue::from_str(key)
+                .map_err(|_| anyhow!("BAGSFM_API_KEY contains invalid header characters"))?;
+            value.set_sensitive(true);
+            headers.insert(API_KEY_HEADER, value);
+        }
+
+        let mut client = Client::builder()
+            .user_agent(self.user_agent)
+            .default_headers(headers)
+            .timeout(self.timeout);
+        if let Some(proxy) = &self.proxy {
+            client = client.proxy(Proxy::all(proxy)?);
+        }
+
+        Ok(BagsFmClient {
+            client: client.build()?,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            limiter: RateLimiter::new(self.rate_limit_rps),
+            max_retries: self.max_retries,
+            token_cache: TtlCache::new(self.cache_ttl),
+        })
+    }
+}
+
+impl BagsFmClient {
+    pub fn builder() -> BagsFmClientBuilder {
+        BagsFmClientBuilder::default()
+    }
+
+    pub fn from_config(cfg: &Config) -> Result<Self> {
+        Self::builder()
+            .base_url(&cfg.bagsfm_api_url)
+            .api_key(cfg.bagsfm_api_key.clone())
+            .timeout(Duration::from_secs(cfg.bagsfm_timeout_secs))
+            .proxy(cfg.bagsfm_proxy.clone())
+            .rate_limit_rps(cfg.bagsfm_rate_limit_rps)
+            .cache_ttl(Duration::from_secs(cfg.bagsfm_cache_ttl_secs))
+            .max_retries(cfg.max_retries)
+            .build()
+    }
+
+    pub fn cache_stats(&self) -> BagsFmCacheStats {
+        BagsFmCacheStats {
+            tokens: self.token_cache.stats(),
+        }
+    }
+
+    pub async fn get_token_info(&self, token_address: &str) -> Result<BagsFmToken, BagsFmError> {
+        if let Some(token) = self.token_cache.get(token_address) {
+            return Ok(token);
+        }
+        let token: BagsFmToken = self.get_json(&format!("/v1/tokens/{}", token_address)).await?;
+        self.token_cache.insert(token_address, token.clone());
+        Ok(token)
+    }
+
+    /// Launches at or after `since`, used as the cursor by [`LaunchFeed`].
+    pub async fn get_launches_since(&self, since: Option<DateTime<Utc>>, limit: u32) -> Result<Vec<BagsFmLaunch>, BagsFmError> {
+        let query = {
+            let mut query = url::form_urlencoded::Serializer::new(String::new());
+            query.append_pair("limit", &limit.to_string());
+            if let Some(since) = since {
+                query.append_pair("since", &since.to_rfc3339_opts(SecondsFormat::Millis, true));
+            }
+            query.finish()
+        };
+        self.get_json(&format!("/v1/launches?{}", query)).await
+    }
+
+    /// Uploads token metadata and reserves the mint address for a launch.
+    pub async fn create_launch_metadata(&self, request: &LaunchMetadataRequest) -> Result<LaunchMetadata, BagsFmError> {
+        self.post_json("/v1/launches/metadata", request).await
+    }
+
+    /// Builds the launch transaction for metadata created with
+    /// [`Self::create_launch_metadata`]. The caller signs and submits it.
+    pub async fn create_launch_transaction(&self, request: &LaunchTransactionRequest) -> Result<LaunchTransaction, BagsFmError> {
+        self.post_json("/v1/launches/transaction", request).await
+    }
+
+    /// Creator fees claimable by `wallet`, one entry per launched mint.
+    pub async fn get_claimable_fees(&self, wallet: &str) -> Result<Vec<ClaimableFee>, BagsFmError> {
+        self.get_json(&format!("/v1/fees/claimable?wallet={}", wallet)).await
+    }
+
+    pub async fn create_fee_claim_transactions(&self, request: &FeeClaimRequest) -> Result<FeeClaimTransactions, BagsFmError> {
+        self.post_json("/v1/fees/claim", request).await
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, BagsFmError> {
+        self.send_json(Method::GET, path, None::<&()>).await
+    }
+
+    async fn post_json<T, B>(&self, path: &str, body: &B) -> Result<T, BagsFmError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + Sync,
+    {
+        self.send_json(Method::POST, path, Some(body)).await
+    }
+
+    async fn send_json<T, B>(&self, method: Method, path: &str, body: Option<&B>) -> Result<T, BagsFmError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + Sync,
+    {
+        let mut attempt = 0;
+        loop {
+            self.limiter.acquire().await;
+            match self.send_json_once(method.clone(), path, body).await {
+                Err(BagsFmError::RateLimited { retry_after }) if attempt < self.max_retries => {
+                    let wait = retry_after.unwrap_or(Duration::from_secs(1));
+                    log::warn!("bags.fm rate limited {}; retrying in {:?}", path, wait);
+                    self.limiter.pause_for(wait);
+                    attempt += 1;
+                }
+                Err(BagsFmError::RateLimited { retry_after }) => {
+                    if let Some(wait) = retry_after {
+                        self.limiter.pause_for(wait);
+                    }
+                    return Err(BagsFmError::RateLimited { retry_after });
+                }
+                other => return other,
+            }
+        }
+    }
+
+    async fn send_json_once<T, B>(&self, method: Method, path: &str, body: Option<&B>) -> Result<T, BagsFmError>
+    where
+        T: DeserializeOwned,
+        B: Serialize + Sync,
+    {
+        let url = format!("{}{}", self.base_url, path);
+        let mut request = self.client.request(method, &url);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+
+        if status.is_success() {
+            let body = response.text().await?;
+            return serde_json::from_str(&body).map_err(|e| BagsFmError::Decode {
+                path: path.to_string(),
+                message: e.to_string(),
+            });
+        }
+
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+
+        Err(match status {
+            StatusCode::NOT_FOUND => BagsFmError::NotFound(path.to_string()),
+            StatusCode::TOO_MANY_REQUESTS => BagsFmError::RateLimited { retry_after },
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => BagsFmError::Unauthorized,
+            _ => BagsFmError::Server { status: status.as_u16(), body },
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::tests::serve_with_headers;
+
+    const TOKEN: &str = r#"{"address": "Mint1", "name": "T", "symbol": "T", "decimals": 6,
+        "total_supply": "1000", "created_at": "2025-01-02T03:04:05Z"}"#;
+
+    #[tokio::test]
+    async fn retries_after_a_429_then_serves_from_cache() {
+        let (base_url, server) = serve_with_headers(vec![
+            (429, "retry-after: 0\r\n", String::new()),
+            (200, "", TOKEN.to_string()),
+        ])
+        .await;
+        let client = BagsFmClient::builder().base_url(base_url).build().unwrap();
+
+        assert_eq!(client.get_token_info("Mint1").await.unwrap().address, "Mint1");
+        assert_eq!(client.get_token_info("Mint1").await.unwrap().address, "Mint1");
+        assert_eq!(server.await.unwrap().len(), 2);
+        let stats = client.cache_stats().tokens;
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn gives_up_once_retries_are_spent() {
+        let (base_url, server) = serve_with_headers(vec![
+            (429, "retry-after: 0\r\n", String::new()),
+            (429, "retry-after: 0\r\n", String::new()),
+        ])
+        .await;
+        let client = BagsFmClient::builder().base_url(base_url).max_retries(1).build().unwrap();
+
+        let err = client.get_token_info("Mint1").await.unwrap_err();
+        assert!(matches!(err, BagsFmError::RateLimited { retry_after: Some(wait) } if wait.is_zero()), "{}", err);
+        assert_eq!(server.await.unwrap().len(), 2);
+    }
+}
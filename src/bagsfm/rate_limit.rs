@@ -0,0 +1,118 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+
+/// Token-bucket limiter shared by every request a [`super::BagsFmClient`]
+/// makes. The bucket holds up to one second worth of burst.
+pub struct RateLimiter {
+    rate_per_sec: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+    paused_until: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(rate_per_sec: f64) -> Self {
+        let rate_per_sec = rate_per_sec.max(0.01);
+        let capacity = rate_per_sec.max(1.0);
+        Self {
+            rate_per_sec,
+            capacity,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+        }
+    }
+
+    /// Waits until a request may be sent and consumes one token.
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                let now = Instant::now();
+
+                match state.paused_until {
+                    Some(until) if until > now => until - now,
+                    _ => {
+                        state.paused_until = None;
+                        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                        state.tokens = (state.tokens + elapsed * self.rate_per_sec).min(self.capacity);
+                        state.last_refill = now;
+
+                        if state.tokens >= 1.0 {
+                            state.tokens -= 1.0;
+                            return;
+                        }
+                        Duration::from_secs_f64((1.0 - state.tokens) / self.rate_per_sec)
+                    }
+                }
+            };
+            sleep(wait).await;
+        }
+    }
+
+    /// Blocks every caller for `duration`, e.g. after the server answered 429
+    /// with a `Retry-After` header. The bucket is drained so requests resume
+    /// at the configured rate instead of in a burst.
+    pub fn pause_for(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+        // A shorter pause never cuts a longer one short.
+        let until = match state.paused_until {
+            Some(current) => current.max(Instant::now() + duration),
+            None => Instant::now() + duration,
+        };
+        state.paused_until = Some(until);
+        state.tokens = 0.0;
+        state.last_refill = until;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn bursts_to_capacity_then_waits_for_refill() {
+        let limiter = RateLimiter::new(2.0);
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+
+        // An idle bucket refills only up to its one-second burst.
+        sleep(Duration::from_secs(10)).await;
+        let idle = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert_eq!(idle.elapsed(), Duration::ZERO);
+        limiter.acquire().await;
+        assert_eq!(idle.elapsed(), Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retry_after_pauses_everyone_then_resumes_at_the_rate() {
+        let limiter = RateLimiter::new(2.0);
+        let start = Instant::now();
+        limiter.acquire().await;
+
+        // A 429 with Retry-After: 3; a later, shorter one does not cut it short.
+        limiter.pause_for(Duration::from_secs(3));
+        limiter.pause_for(Duration::from_secs(1));
+        // The bucket was drained, so requests resume at the rate rather than
+        // in a burst.
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(3_500));
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+}
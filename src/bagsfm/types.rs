@@ -0,0 +1,169 @@
+use crate::numeric::{self, Decimal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Token as reported by bags.fm. Integer amounts are exact `u128` base units
+/// and prices keep the digits the API sent; see [`crate::numeric`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawToken")]
+pub struct BagsFmToken {
+    pub address: String,
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    #[serde(serialize_with = "numeric::serialize_u128_str")]
+    pub total_supply: u128,
+    pub price_usd: Option<Decimal>,
+    pub market_cap: Option<Decimal>,
+    pub volume_24h: Option<Decimal>,
+    pub liquidity_usd: Option<Decimal>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "RawLaunch")]
+pub struct BagsFmLaunch {
+    pub token_address: String,
+    pub initial_liquidity_sol: Decimal,
+    pub launch_time: DateTime<Utc>,
+    pub status: String,
+}
+
+impl BagsFmToken {
+    /// Placeholder record for `--demo` runs. Never use this outside demo mode:
+    /// the decimals and supply are made up and must not drive a real trade.
+    pub fn demo(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            name: "Demo Token".to_string(),
+            symbol: "DEMO".to_string(),
+            decimals: 9,
+            total_supply: 1_000_000_000 * 10u128.pow(9),
+            price_usd: Some(Decimal::new(1, 3)),
+            market_cap: Some(Decimal::new(1000, 0)),
+            volume_24h: Some(Decimal::new(500, 0)),
+            liquidity_usd: Some(Decimal::new(100, 0)),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+// Wire shapes. Numeric fields are taken as raw JSON so each one is validated
+// on its own and the error names the field that was wrong.
+
+#[derive(Deserialize)]
+struct RawToken {
+    address: String,
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: Value,
+    price_usd: Option<Value>,
+    market_cap: Option<Value>,
+    volume_24h: Option<Value>,
+    liquidity_usd: Option<Value>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct RawLaunch {
+    token_address: String,
+    initial_liquidity_sol: Value,
+    launch_time: String,
+    status: String,
+}
+
+
+impl TryFrom<RawToken> for BagsFmToken {
+    type Error = String;
+
+    fn try_from(raw: RawToken) -> Result<Self, String> {
+        Ok(Self {
+            total_supply: field("total_supply", numeric::parse_u128(&raw.total_supply))?,
+            price_usd: optional_decimal("price_usd", raw.price_usd)?,
+            market_cap: optional_decimal("market_cap", raw.market_cap)?,
+            volume_24h: optional_decimal("volume_24h", raw.volume_24h)?,
+            liquidity_usd: optional_decimal("liquidity_usd", raw.liquidity_usd)?,
+            created_at: timestamp("created_at", &raw.created_at)?,
+            address: raw.address,
+            name: raw.name,
+            symbol: raw.symbol,
+            decimals: raw.decimals,
+        })
+    }
+}
+
+impl TryFrom<RawLaunch> for BagsFmLaunch {
+    type Error = String;
+
+    fn try_from(raw: RawLaunch) -> Result<Self, String> {
+        Ok(Self {
+            initial_liquidity_sol: decimal("initial_liquidity_sol", &raw.initial_liquidity_sol)?,
+            launch_time: timestamp("launch_time", &raw.launch_time)?,
+            token_address: raw.token_address,
+            status: raw.status,
+        })
+    }
+}
+
+fn field<T>(name: &str, result: Result<T, String>) -> Result<T, String> {
+    result.map_err(|e| format!("field `{}`: {}", name, e))
+}
+
+fn decimal(name: &str, value: &Value) -> Result<Decimal, String> {
+    field(name, Decimal::deserialize(value).map_err(|e| e.to_string()))
+}
+
+fn optional_decimal(name: &str, value: Option<Value>) -> Result<Option<Decimal>, String> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => decimal(name, &value).map(Some),
+    }
+}
+
+fn timestamp(name: &str, value: &str) -> Result<DateTime<Utc>, String> {
+    field(
+        name,
+        DateTime::parse_from_rfc3339(value)
+            .map(|t| t.with_timezone(&Utc))
+            .map_err(|e| format!("{:?} is not an RFC3339 timestamp ({})", value, e)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_parses_exact_supply_and_prices() {
+        let token: BagsFmToken = serde_json::from_str(r#"{
+            "address": "Mint1", "name": "T", "symbol": "T", "decimals": 6,
+            "total_supply": "1000000000000000000000",
+            "price_usd": "0.000012345678901234",
+            "market_cap": 12345.67, "volume_24h": null,
+            "created_at": "2025-01-02T03:04:05Z"
+        }"#).unwrap();
+
+        assert_eq!(token.total_supply, 1_000_000_000_000_000_000_000);
+        assert_eq!(token.price_usd.unwrap().to_string(), "0.000012345678901234");
+        assert_eq!(token.market_cap.unwrap().to_string(), "12345.67");
+        assert!(token.volume_24h.is_none() && token.liquidity_usd.is_none());
+        assert_eq!(token.created_at.to_rfc3339(), "2025-01-02T03:04:05+00:00");
+    }
+
+    #[test]
+    fn errors_name_the_offending_field() {
+        let err = serde_json::from_str::<BagsFmToken>(r#"{
+            "address": "Mint1", "name": "T", "symbol": "T", "decimals": 6,
+            "total_supply": "12.5", "created_at": "2025-01-02T03:04:05Z"
+        }"#).unwrap_err();
+        assert!(err.to_string().contains("field `total_supply`"), "{}", err);
+
+        let err = serde_json::from_str::<BagsFmLaunch>(r#"{
+            "token_address": "M", "initial_liquidity_sol": 1,
+            "launch_time": "yesterday", "status": "live"
+        }"#).unwrap_err();
+        assert!(err.to_string().contains("field `launch_time`"), "{}", err);
+    }
+}
@@ -1,9 +1,10 @@
 
 use crate::config::Config;
-use crate::solana::SolanaClient;
-use crate::bagsfm::BagsFmClient;
-use crate::meteora::{MeteoraClient, DbcCurveType};
-use anyhow::Result;
+use crate::bagsfm::{LaunchMetadataRequest, LaunchTransactionRequest};
+use crate::store::{Ledger, LaunchRecord};
+use crate::venue::{BondingCurveVenue, LaunchpadApi, SolanaApi};
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
 use serde::Deserialize;
 use tokio::time::{sleep, Duration};
 
@@ -16,22 +17,27 @@ struct BundlePlan {
 struct TokenPlan {
     name: String,
     symbol: String,
-    supply: u64,
     liquidity_sol: f64,
-    initial_price_sol: Option<f64>,
     description: Option<String>,
+    image: Option<String>,
+    twitter: Option<String>,
+    telegram: Option<String>,
+    website: Option<String>,
 }
 
-pub async fn run(cfg: &Config, plan_path: &str, dry_run: bool) -> Result<()> {
+pub async fn run(
+    cfg: &Config,
+    plan_path: &str,
+    dry_run: bool,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: &dyn LaunchpadApi,
+    meteora_client: &dyn BondingCurveVenue,
+    ledger: &Ledger,
+) -> Result<()> {
     let raw = std::fs::read_to_string(plan_path)?;
     let plan: BundlePlan = serde_json::from_str(&raw)?;
     log::info!("Loaded bundle plan with {} items", plan.plans.len());
 
-    // Initialize clients
-    let solana_client = SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path)?;
-    let bagsfm_client = BagsFmClient::new();
-    let meteora_client = MeteoraClient::new()?;
-
     // Check total required liquidity
     let total_liquidity: f64 = plan.plans.iter().map(|p| p.liquidity_sol).sum();
     let balance = solana_client.get_balance().await?;
@@ -50,51 +56,59 @@ pub async fn run(cfg: &Config, plan_path: &str, dry_run: bool) -> Result<()> {
 
     for (i, p) in plan.plans.iter().enumerate() {
         if dry_run {
-            log::info!("[DRY] Would launch {} ({}) supply={} liq={} SOL via bags.fm", 
-                p.name, p.symbol, p.supply, p.liquidity_sol);
+            log::info!("[DRY] Would launch {} ({}) liq={} SOL via bags.fm",
+                p.name, p.symbol, p.liquidity_sol);
         } else {
             log::info!("[{}/{}] Launching {} ({}) on bags.fm ...", 
                 i+1, plan.plans.len(), p.name, p.symbol);
             
             // Create token metadata for bags.fm
-            let token_metadata = crate::bagsfm::BagsFmToken {
-                address: format!("Token{}", i), 
+            let metadata = bagsfm_client.create_launch_metadata(&LaunchMetadataRequest {
+                name: p.name.clone(),
                 symbol: p.symbol.clone(),
-                decimals: 9,
-                total_supply: p.supply.to_string(),
-                price_usd: None,
-                market_cap: None,
-                volume_24h: None,
-                liquidity_usd: Some(p.liquidity_sol * 100.0), 
-                created_at: chrono::Utc::now().to_rfc3339(),
-            };
+                description: p.description.clone().unwrap_or_default(),
+                image_url: p.image.clone(),
+                twitter: p.twitter.clone(),
+                telegram: p.telegram.clone(),
+                website: p.website.clone(),
+            }).await.with_context(|| format!("failed to create bags.fm metadata for {}", p.symbol))?;
+            log::info!("Token metadata created: mint={} uri={}", metadata.token_mint, metadata.metadata_uri);
             
-            log::info!("Token metadata created: {} ({})", token_metadata.name, token_metadata.symbol);
+            log::info!("Requesting launch transaction from bags.fm...");
+            let launch_tx = bagsfm_client.create_launch_transaction(&LaunchTransactionRequest {
+                token_mint: metadata.token_mint.clone(),
+                metadata_uri: metadata.metadata_uri.clone(),
+                wallet: solana_client.wallet_pubkey().to_string(),
+                initial_buy_lamports: (p.liquidity_sol * 1_000_000_000.0) as u64,
+            }).await.with_context(|| format!("failed to build launch transaction for {}", p.symbol))?;
             
-    
+            let signature = solana_client.sign_and_send_serialized(&launch_tx.transaction).await
+                .with_context(|| format!("launch transaction for {} failed", p.symbol))?;
+            log::info!("Launch confirmed: {} (mint {})", signature, metadata.token_mint);
             
-            log::info!("Submitting to bags.fm for approval...");
-            sleep(Duration::from_millis(1000)).await; 
+            ledger.append(&LaunchRecord {
+                time_utc: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                wallet: solana_client.wallet_pubkey().to_string(),
+                name: p.name.clone(),
+                symbol: p.symbol.clone(),
+                mint: metadata.token_mint.clone(),
+                metadata_uri: metadata.metadata_uri.clone(),
+                signature: signature.to_string(),
+            })?;
             
-            log::info!("Creating Meteora DBC pool...");
-            let curve_type = DbcCurveType::Linear; 
-            let dbc_pool = meteora_client.create_dbc_pool(
-                &format!("Token{}", i),
-                "SOL",
-                p.initial_price_sol.unwrap_or(0.0001),
-                p.supply,
-                curve_type
-            ).await?;
-            log::info!("DBC pool created: {} ({} <-> {})", 
+            // The launch transaction creates the DBC pool; read it back rather than trust it.
+            let dbc_pool = meteora_client.find_dbc_pool(&metadata.token_mint).await
+                .with_context(|| format!("launch of {} confirmed but no Meteora DBC pool exists for {}", p.symbol, metadata.token_mint))?;
+            log::info!("DBC pool: {} ({} <-> {})", 
                 dbc_pool.address, dbc_pool.base_token, dbc_pool.token_mint);
             
             log::info!("Initial DBC price: {} SOL per token", dbc_pool.current_price);
-            log::info!("Curve type: {:?}", dbc_pool.curve_type);
+            log::info!("Curve: {} segments from sqrt price {}", dbc_pool.curve.points.len(), dbc_pool.curve.sqrt_start_price);
             
             log::info!("Launched {} with initial liquidity {:.2} SOL", p.symbol, p.liquidity_sol);
             
             if i < plan.plans.len() - 1 {
-                sleep(Duration::from_millis(2000)).await;
+                sleep(Duration::from_millis(cfg.bundle_launch_delay_ms)).await;
             }
         }
     }
@@ -102,3 +116,85 @@ pub async fn run(cfg: &Config, plan_path: &str, dry_run: bool) -> Result<()> {
     log::info!("Bundle complete. RPC primary = {}", cfg.rpc_url);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meteora::{dbc, DbcStatus, MeteoraDbcPool};
+    use crate::mock::{MockBondingCurve, MockLaunchpad, MockSolana};
+
+    fn dbc_pool(token_mint: &str) -> MeteoraDbcPool {
+        MeteoraDbcPool {
+            address: format!("Dbc{}", token_mint),
+            token_mint: token_mint.to_string(),
+            base_token: "So11111111111111111111111111111111111111112".to_string(),
+            current_price: 0.0001,
+            curve: dbc::Curve::default(),
+            status: DbcStatus::Active,
+        }
+    }
+
+    fn plan(dir: &std::path::Path) -> String {
+        let plan_path = dir.join("plan.json");
+        std::fs::write(&plan_path, r#"{"plans": [
+            {"name": "Alpha", "symbol": "ALP", "liquidity_sol": 0.5, "description": "first"},
+            {"name": "Beta", "symbol": "BET", "liquidity_sol": 0.25}
+        ]}"#).unwrap();
+        plan_path.to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn launches_every_plan_item_and_records_real_mints() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cfg = Config::from_env().unwrap();
+        cfg.bundle_launch_delay_ms = 0;
+        let solana = MockSolana::with_balance(2_000_000_000);
+        let launchpad = MockLaunchpad::default().with_mint("MintAlp").with_mint("MintBet");
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("MintAlp")).with_pool(dbc_pool("MintBet"));
+        let ledger = Ledger::new(dir.path());
+
+        run(&cfg, &plan(dir.path()), false, &solana, &launchpad, &curve, &ledger).await.unwrap();
+
+        let requests = launchpad.metadata_requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].description, "first");
+        assert_eq!(solana.sent().len(), 2);
+
+        let launches = ledger.read_all::<LaunchRecord>().unwrap();
+        assert_eq!(launches.len(), 2);
+        assert_eq!(launches[1].symbol, "BET");
+        assert_eq!(solana.sent()[1], format!("launch-tx:{}", launches[1].mint));
+        assert_eq!(launches[1].mint, "MintBet");
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_launch_left_no_dbc_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cfg = Config::from_env().unwrap();
+        cfg.bundle_launch_delay_ms = 0;
+        let solana = MockSolana::with_balance(2_000_000_000);
+        let launchpad = MockLaunchpad::default().with_mint("MintAlp").with_mint("MintBet");
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("MintAlp"));
+        let ledger = Ledger::new(dir.path());
+
+        let err = run(&cfg, &plan(dir.path()), false, &solana, &launchpad, &curve, &ledger).await.unwrap_err();
+        assert!(format!("{:#}", err).contains("no Meteora DBC pool exists for MintBet"), "{:#}", err);
+        // The launch itself landed and stays on record.
+        assert_eq!(ledger.read_all::<LaunchRecord>().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn dry_run_submits_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(0);
+        let launchpad = MockLaunchpad::default();
+        let curve = MockBondingCurve::default();
+        let ledger = Ledger::new(dir.path());
+
+        run(&cfg, "plans/example.json", true, &solana, &launchpad, &curve, &ledger).await.unwrap();
+
+        assert!(solana.sent().is_empty());
+        assert!(ledger.read_all::<LaunchRecord>().unwrap().is_empty());
+    }
+}
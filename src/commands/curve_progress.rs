@@ -0,0 +1,83 @@
+use crate::config::Config;
+use crate::meteora::graduation::{GraduationEvent, GraduationWatcher};
+use crate::meteora::DbcStatus;
+use crate::numeric::format_units;
+use crate::venue::BondingCurveVenue;
+use anyhow::Result;
+use std::sync::Arc;
+
+/// Shows how far `token_mint`'s curve is from migrating. With `watch`, keeps
+/// polling until the curve completes and its DAMM v2 pool appears.
+pub async fn run(cfg: &Config, token_mint: &str, watch: bool, meteora_client: Arc<dyn BondingCurveVenue>) -> Result<()> {
+    let mut progress = meteora_client.curve_progress(token_mint).await?;
+    if progress.migration_quote_threshold == 0 && cfg.dbc_migration_threshold > 0.0 {
+        log::warn!("Pool {} sets no migration threshold; using DBC_MIGRATION_THRESHOLD", progress.pool);
+        progress.migration_quote_threshold =
+            (cfg.dbc_migration_threshold * 10f64.powi(progress.quote_decimals as i32)) as u64;
+    }
+
+    let quote = quote_symbol(&progress.quote_mint);
+    log::info!("{} curve: DBC pool {} ({:?})", token_mint, progress.pool, progress.status);
+    log::info!("Raised {} / {} {} ({:.2}% to graduation)",
+        format_units(progress.quote_reserve as u128, progress.quote_decimals),
+        format_units(progress.migration_quote_threshold as u128, progress.quote_decimals),
+        quote,
+        progress.percent()
+    );
+    if progress.status == DbcStatus::Active {
+        log::info!("~{} {} of buys remaining (after fees)",
+            format_units(progress.quote_remaining() as u128, progress.quote_decimals), quote);
+    }
+    if !watch {
+        return Ok(());
+    }
+
+    log::info!("Watching for graduation (poll every {} ms)", cfg.dbc_progress_poll_ms);
+    let mut events = GraduationWatcher::from_config(meteora_client, token_mint, cfg).spawn(4);
+    while let Some(event) = events.recv().await {
+        match event {
+            GraduationEvent::CurveCompleted(progress) => {
+                log::info!("Curve complete: {} {} raised in {}; waiting for migration",
+                    format_units(progress.quote_reserve as u128, progress.quote_decimals), quote, progress.pool);
+            }
+            GraduationEvent::Migrated { token_mint, dbc_pool, damm_pool } => {
+                log::info!("{} graduated from {}: DAMM v2 pool {}", token_mint, dbc_pool, damm_pool);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn quote_symbol(quote_mint: &str) -> &str {
+    if quote_mint == spl_token::native_mint::id().to_string() {
+        "SOL"
+    } else {
+        quote_mint
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meteora::DbcProgress;
+    use crate::mock::MockBondingCurve;
+
+    #[tokio::test]
+    async fn watch_returns_once_the_damm_pool_is_live() {
+        let cfg = Config::from_env().unwrap();
+        let curve = MockBondingCurve::default().with_progress(DbcProgress {
+            pool: "DbcPool1".to_string(),
+            token_mint: "Mint1".to_string(),
+            quote_mint: spl_token::native_mint::id().to_string(),
+            quote_decimals: 9,
+            quote_reserve: 85_000_000_000,
+            migration_quote_threshold: 85_000_000_000,
+            status: DbcStatus::Migrated,
+        });
+        curve.damm_pools.lock().unwrap().insert("Mint1".to_string(), "DammPool1".to_string());
+
+        run(&cfg, "Mint1", true, Arc::new(curve)).await.unwrap();
+        let err = run(&cfg, "Mint2", false, Arc::new(MockBondingCurve::default())).await.unwrap_err();
+        assert!(err.to_string().contains("No Meteora DBC pool"), "{}", err);
+    }
+}
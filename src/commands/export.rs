@@ -1,14 +1,19 @@
 
 use crate::config::Config;
-use crate::solana::SolanaClient;
-use crate::bagsfm::BagsFmClient;
+use crate::bagsfm::BagsFmToken;
+use crate::store::{FeeClaimRecord, FillRecord, Ledger, LpFeeRecord};
+use crate::venue::{LaunchpadApi, SolanaApi};
 use anyhow::{Context, Result};
-use chrono::{Utc, SecondsFormat};
+use chrono::{DateTime, Utc, SecondsFormat};
 use csv::WriterBuilder;
 use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
+/// How many of the wallet's latest transactions go into `wallet_activity.csv`.
+const ACTIVITY_LIMIT: usize = 100;
+
 #[derive(Serialize)]
 struct TradeRow {
     time_utc: String,
@@ -41,16 +46,41 @@ struct WalletActivityRow {
     fee_paid: f64,
 }
 
-pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
+#[derive(Serialize)]
+struct FeeClaimRow {
+    time_utc: String,
+    wallet: String,
+    mint: String,
+    amount_sol: f64,
+    signatures: String,
+}
+
+#[derive(Serialize)]
+struct LpFeeRow {
+    time_utc: String,
+    wallet: String,
+    pool: String,
+    position: String,
+    token_a_mint: String,
+    amount_a: u64,
+    token_b_mint: String,
+    amount_b: u64,
+    transaction_hash: String,
+}
+
+pub async fn run(
+    cfg: &Config,
+    out_path: &str,
+    demo: bool,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: &dyn LaunchpadApi,
+    ledger: &Ledger,
+) -> Result<()> {
     let out = Path::new(out_path);
     if let Some(parent) = out.parent() {
         fs::create_dir_all(parent).ok();
     }
 
-    // Initialize clients for real data
-    let solana_client = SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path).ok();
-    let bagsfm_client = BagsFmClient::new();
-
     // Export trades
     let trades_file = out.with_file_name("trades.csv");
     let mut trades_wtr = WriterBuilder::new().from_path(&trades_file).context("failed to open trades CSV")?;
@@ -60,10 +90,242 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
     let mut activity_wtr = WriterBuilder::new().from_path(&activity_file).context("failed to open activity CSV")?;
 
     let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
-    //this is demo data
-    let trades = vec![
+    let wallet = solana_client.wallet_pubkey().to_string();
+    let (trades, mut activities) = if demo {
+        (demo_trades(cfg, &now), demo_activities(cfg, &now))
+    } else {
+        let fills = ledger.read_all::<FillRecord>()?;
+        (trade_rows(&fills, &wallet, bagsfm_client).await, recent_activity(solana_client, &wallet).await?)
+    };
+    let fee_claims: Vec<FeeClaimRow> = ledger
+        .read_all::<FeeClaimRecord>()?
+        .into_iter()
+        .map(|claim| FeeClaimRow {
+            time_utc: claim.time_utc,
+            wallet: claim.wallet,
+            mint: claim.mint,
+            amount_sol: claim.amount_lamports as f64 / 1_000_000_000.0,
+            signatures: claim.signatures.join(";"),
+        })
+        .collect();
+    let lp_fees = ledger.read_all::<LpFeeRecord>()?;
+    activities.extend(lp_fees.iter().map(lp_fee_activity));
+    let total_volume: f64 = trades.iter().map(|t| t.qty * t.price).sum();
+    let avg_trade_size = if trades.is_empty() { 0.0 } else { total_volume / trades.len() as f64 };
+
+    for trade in &trades {
+        trades_wtr.serialize(trade)?;
+    }
+    trades_wtr.flush()?;
+
+    for activity in &activities {
+        activity_wtr.serialize(activity)?;
+    }
+    activity_wtr.flush()?;
+
+    let fee_claims_file = out.with_file_name("fee_claims.csv");
+    let mut fee_claims_wtr = WriterBuilder::new().from_path(&fee_claims_file).context("failed to open fee claims CSV")?;
+    for claim in &fee_claims {
+        fee_claims_wtr.serialize(claim)?;
+    }
+    fee_claims_wtr.flush()?;
+    let total_fees_claimed: f64 = fee_claims.iter().map(|c| c.amount_sol).sum();
+
+    let lp_fees_file = out.with_file_name("lp_fees.csv");
+    let mut lp_fees_wtr = WriterBuilder::new().from_path(&lp_fees_file).context("failed to open LP fees CSV")?;
+    for claim in &lp_fees {
+        lp_fees_wtr.serialize(LpFeeRow {
+            time_utc: claim.time_utc.clone(),
+            wallet: claim.wallet.clone(),
+            pool: claim.pool.clone(),
+            position: claim.position.clone(),
+            token_a_mint: claim.token_a_mint.clone(),
+            amount_a: claim.amount_a,
+            token_b_mint: claim.token_b_mint.clone(),
+            amount_b: claim.amount_b,
+            transaction_hash: claim.signature.clone(),
+        })?;
+    }
+    lp_fees_wtr.flush()?;
+    let summary_file = out.with_file_name("summary.txt");
+    let summary_content = format!(
+        "ReoswellEcho Trading Bot Export Summary
+Generated: {}
+Wallet: {}
+Total Trades: {}
+Total Volume: {:.4} SOL
+Average Trade Size: {:.4} SOL
+Creator Fee Claims: {} ({:.6} SOL)
+LP Fee Claims: {}
+Export Directory: {}
+
+Configuration:
+- RPC: {}
+- Slippage: {} bps
+- Max Retries: {}
+- Budget Cap: {:.4} SOL
+- Buy Cap: {:.4} SOL
+
+Files Generated:
+- trades.csv: Detailed trade history
+- wallet_activity.csv: Wallet transaction activity
+- fee_claims.csv: bags.fm creator fee claims
+- lp_fees.csv: Meteora DAMM v2 LP fee claims, per token
+- summary.txt: This summary report
+
+{}",
+        now,
+        wallet,
+        trades.len(),
+        total_volume,
+        avg_trade_size,
+        fee_claims.len(),
+        total_fees_claimed,
+        lp_fees.len(),
+        cfg.export_dir,
+        cfg.rpc_url,
+        cfg.slippage_bps,
+        cfg.max_retries,
+        cfg.budget_sol,
+        cfg.buy_cap_sol,
+        if demo { "Note: generated in --demo mode; all rows are sample data." } else { "" }
+    );
+
+    fs::write(&summary_file, summary_content)?;
+
+    log::info!("Export completed successfully!");
+    log::info!("Trades CSV: {}", trades_file.display());
+    log::info!("Activity CSV: {}", activity_file.display());
+    log::info!("Fee claims CSV: {}", fee_claims_file.display());
+    log::info!("LP fees CSV: {}", lp_fees_file.display());
+    log::info!("Summary: {}", summary_file.display());
+
+    Ok(())
+}
+
+/// One row per recorded fill that swapped SOL for a token or back. Sells
+/// carry PnL against the average cost of the tokens held, and the time
+/// since that position was opened.
+async fn trade_rows(fills: &[FillRecord], wallet: &str, bagsfm_client: &dyn LaunchpadApi) -> Vec<TradeRow> {
+    let native = spl_token::native_mint::id().to_string();
+    let mut tokens: HashMap<String, Option<BagsFmToken>> = HashMap::new();
+    // Per token: base units held, SOL paid for them, when the position opened.
+    let mut positions: HashMap<String, (u64, f64, DateTime<Utc>)> = HashMap::new();
+    let mut rows = Vec::new();
+
+    for fill in fills {
+        let (side, token, token_amount, sol_lamports) = if fill.input_mint == native {
+            ("BUY", &fill.output_mint, fill.actual_out, fill.amount_in)
+        } else if fill.output_mint == native {
+            ("SELL", &fill.input_mint, fill.amount_in, fill.actual_out)
+        } else {
+            log::debug!("Skipping token-to-token fill {} in the trade export", fill.signature);
+            continue;
+        };
+        if !tokens.contains_key(token) {
+            let info = match bagsfm_client.get_token_info(token).await {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    log::warn!("No bags.fm info for {} ({}); exporting its amounts in base units", token, e);
+                    None
+                }
+            };
+            tokens.insert(token.clone(), info);
+        }
+        let info = tokens[token].as_ref();
+        let decimals = info.map_or(0, |info| info.decimals);
+        let qty = token_amount as f64 / 10f64.powi(decimals as i32);
+        let sol = sol_lamports as f64 / 1_000_000_000.0;
+        let time = DateTime::parse_from_rfc3339(&fill.time_utc).map_or_else(|_| Utc::now(), |t| t.with_timezone(&Utc));
+
+        let (held, cost, opened) = positions.entry(token.clone()).or_insert((0, 0.0, time));
+        let (pnl, roi_pct, hold_minutes) = if side == "BUY" {
+            if *held == 0 {
+                *opened = time;
+            }
+            *held += token_amount;
+            *cost += sol;
+            (0.0, 0.0, 0)
+        } else {
+            let sold = token_amount.min(*held);
+            let basis = if *held == 0 { 0.0 } else { *cost * sold as f64 / *held as f64 };
+            let hold_minutes = (time - *opened).num_minutes().max(0) as u64;
+            *held -= sold;
+            *cost -= basis;
+            let pnl = sol - basis;
+            (pnl, if basis > 0.0 { pnl / basis * 100.0 } else { 0.0 }, hold_minutes)
+        };
+
+        rows.push(TradeRow {
+            time_utc: time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            wallet: wallet.to_string(),
+            token: token.clone(),
+            token_name: info.map_or_else(String::new, |info| info.name.clone()),
+            token_symbol: info.map_or_else(String::new, |info| info.symbol.clone()),
+            side: side.into(),
+            qty,
+            price: if qty > 0.0 { sol / qty } else { 0.0 },
+            price_usd: None,
+            pnl,
+            roi_pct,
+            hold_minutes,
+            transaction_hash: fill.signature.clone(),
+            gas_fee: 0.0,
+            pool_address: Some(fill.pool.clone()),
+            dex_type: Some(fill.venue.clone()),
+        });
+    }
+    rows
+}
+
+/// The wallet's latest transactions, newest first.
+async fn recent_activity(solana_client: &dyn SolanaApi, wallet: &str) -> Result<Vec<WalletActivityRow>> {
+    let signatures = solana_client.get_signatures_for_address(&solana_client.wallet_pubkey(), ACTIVITY_LIMIT).await
+        .context("failed to fetch the wallet's transaction history")?;
+    Ok(signatures
+        .into_iter()
+        .map(|sig| WalletActivityRow {
+            time_utc: sig.block_time
+                .and_then(|t| DateTime::from_timestamp(t, 0))
+                .map_or_else(String::new, |t| t.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            wallet: wallet.to_string(),
+            event_type: if sig.err.is_some() { "FAILED_TX" } else { "TX" }.into(),
+            token_address: None,
+            amount_sol: 0.0,
+            transaction_hash: sig.signature,
+            block_number: sig.slot,
+            fee_paid: 0.0,
+        })
+        .collect())
+}
+
+/// An LP fee claim as wallet activity. `amount_sol` is the SOL side of the
+/// pool, if it has one; the other token's fee is in `lp_fees.csv`.
+fn lp_fee_activity(claim: &LpFeeRecord) -> WalletActivityRow {
+    let native = spl_token::native_mint::id().to_string();
+    let (token, sol_lamports) = if claim.token_b_mint == native {
+        (&claim.token_a_mint, claim.amount_b)
+    } else if claim.token_a_mint == native {
+        (&claim.token_b_mint, claim.amount_a)
+    } else {
+        (&claim.token_a_mint, 0)
+    };
+    WalletActivityRow {
+        time_utc: claim.time_utc.clone(),
+        wallet: claim.wallet.clone(),
+        event_type: "LP_FEE".into(),
+        token_address: Some(token.clone()),
+        amount_sol: sol_lamports as f64 / 1_000_000_000.0,
+        transaction_hash: claim.signature.clone(),
+        block_number: 0,
+        fee_paid: 0.0,
+    }
+}
+
+fn demo_trades(cfg: &Config, now: &str) -> Vec<TradeRow> {
+    vec![
         TradeRow { 
-            time_utc: now.clone(), 
+            time_utc: now.to_string(), 
             wallet: cfg.wallet_address.clone(), 
             token: "DemoToken123".into(), 
             token_name: "Demo Token Alpha".into(),
@@ -81,7 +343,7 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             dex_type: Some("Raydium".into()),
         },
         TradeRow { 
-            time_utc: now.clone(), 
+            time_utc: now.to_string(), 
             wallet: cfg.wallet_address.clone(), 
             token: "DemoToken123".into(), 
             token_name: "Demo Token Alpha".into(),
@@ -99,7 +361,7 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             dex_type: Some("Raydium".into()),
         },
         TradeRow { 
-            time_utc: now.clone(), 
+            time_utc: now.to_string(), 
             wallet: cfg.wallet_address.clone(), 
             token: "DemoToken456".into(), 
             token_name: "Demo Token Beta".into(),
@@ -116,11 +378,13 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             pool_address: Some("Pool456".into()),
             dex_type: Some("Orca".into()),
         },
-    ];
+    ]
+}
 
-    let activities = vec![
+fn demo_activities(cfg: &Config, now: &str) -> Vec<WalletActivityRow> {
+    vec![
         WalletActivityRow {
-            time_utc: now.clone(),
+            time_utc: now.to_string(),
             wallet: cfg.wallet_address.clone(),
             event_type: "SWAP".into(),
             token_address: Some("DemoToken123".into()),
@@ -130,7 +394,7 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             fee_paid: 0.000005,
         },
         WalletActivityRow {
-            time_utc: now.clone(),
+            time_utc: now.to_string(),
             wallet: cfg.wallet_address.clone(),
             event_type: "LP_ADD".into(),
             token_address: Some("DemoToken456".into()),
@@ -140,7 +404,7 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             fee_paid: 0.000005,
         },
         WalletActivityRow {
-            time_utc: now.clone(),
+            time_utc: now.to_string(),
             wallet: cfg.wallet_address.clone(),
             event_type: "TRANSFER".into(),
             token_address: None,
@@ -149,60 +413,59 @@ pub async fn run(cfg: &Config, out_path: &str) -> Result<()> {
             block_number: 123456791,
             fee_paid: 0.000005,
         },
-    ];
+    ]
+}
 
-    for trade in trades {
-        trades_wtr.serialize(trade)?;
-    }
-    trades_wtr.flush()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockLaunchpad, MockSolana};
 
-    for activity in activities {
-        activity_wtr.serialize(activity)?;
+    fn fill(time_utc: &str, input_mint: &str, output_mint: &str, amount_in: u64, actual_out: u64, signature: &str) -> FillRecord {
+        FillRecord {
+            time_utc: time_utc.to_string(),
+            venue: "meteora-dbc".to_string(),
+            pool: "Pool1".to_string(),
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.to_string(),
+            amount_in,
+            quoted_out: actual_out,
+            min_out: actual_out,
+            actual_out,
+            slippage_bps: 100,
+            realized_slippage_bps: 0,
+            price_impact_bps: 0,
+            signature: signature.to_string(),
+        }
     }
-    activity_wtr.flush()?;
-    let summary_file = out.with_file_name("summary.txt");
-    let summary_content = format!(
-        "ReoswellEcho Trading Bot Export Summary
-Generated: {}
-Wallet: {}
-Total Trades: {}
-Total Volume: {:.4} SOL
-Average Trade Size: {:.4} SOL
-Export Directory: {}
-
-Configuration:
-- RPC: {}
-- Slippage: {} bps
-- Max Retries: {}
-- Budget Cap: {:.4} SOL
-- Buy Cap: {:.4} SOL
-
-Files Generated:
-- trades.csv: Detailed trade history
-- wallet_activity.csv: Wallet transaction activity
-- summary.txt: This summary report
 
-Note: This is a development export with sample data.
-Real implementation would include actual blockchain transaction data.",
-        now,
-        cfg.wallet_address,
-        trades.len(),
-        2.1, 
-        0.7, 
-        cfg.export_dir,
-        cfg.rpc_url,
-        cfg.slippage_bps,
-        cfg.max_retries,
-        cfg.budget_sol,
-        cfg.buy_cap_sol
-    );
+    #[tokio::test]
+    async fn exports_recorded_fills_with_pnl() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Config::from_env().unwrap();
+        let sol = spl_token::native_mint::id().to_string();
+        let solana = MockSolana::with_balance(0);
+        let launchpad = MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1"));
+        let ledger = Ledger::new(dir.path().join("ledger"));
+        // 1 SOL for 1000 tokens, then half of them back for 0.75 SOL.
+        ledger.append(&fill("2025-01-02T03:00:00Z", &sol, "Mint1", 1_000_000_000, 1_000_000_000_000, "Buy1")).unwrap();
+        ledger.append(&fill("2025-01-02T03:30:00Z", "Mint1", &sol, 500_000_000_000, 750_000_000, "Sell1")).unwrap();
 
-    fs::write(&summary_file, summary_content)?;
+        let out = dir.path().join("export").join("summary.txt");
+        run(&cfg, out.to_str().unwrap(), false, &solana, &launchpad, &ledger).await.unwrap();
 
-    log::info!("Export completed successfully!");
-    log::info!("Trades CSV: {}", trades_file.display());
-    log::info!("Activity CSV: {}", activity_file.display());
-    log::info!("Summary: {}", summary_file.display());
+        let mut trades = csv::Reader::from_path(out.with_file_name("trades.csv")).unwrap();
+        let rows: Vec<HashMap<String, String>> = trades.deserialize().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0]["side"].as_str(), rows[0]["qty"].as_str(), rows[0]["token_symbol"].as_str()), ("BUY", "1000.0", "DEMO"));
+        assert_eq!(rows[0]["wallet"], solana.pubkey.to_string());
+        assert_eq!((rows[1]["side"].as_str(), rows[1]["qty"].as_str()), ("SELL", "500.0"));
+        assert_eq!(rows[1]["pnl"].parse::<f64>().unwrap(), 0.25);
+        assert_eq!(rows[1]["roi_pct"].parse::<f64>().unwrap(), 50.0);
+        assert_eq!(rows[1]["hold_minutes"], "30");
 
-    Ok(())
+        let summary = fs::read_to_string(&out).unwrap();
+        assert!(summary.contains("Total Trades: 2"), "{}", summary);
+        assert!(!summary.contains("--demo"), "{}", summary);
+    }
 }
@@ -0,0 +1,140 @@
+use crate::config::Config;
+use crate::bagsfm::{ClaimableFee, FeeClaimRequest};
+use crate::store::{FeeClaimRecord, LaunchRecord, Ledger};
+use crate::venue::{LaunchpadApi, SolanaApi};
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use std::collections::BTreeMap;
+
+/// Shows claimable creator fees for every mint we launched, plus any other
+/// mint bags.fm reports for the wallet.
+pub async fn list(
+    _cfg: &Config,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: &dyn LaunchpadApi,
+    ledger: &Ledger,
+) -> Result<()> {
+    let wallet = solana_client.wallet_pubkey().to_string();
+    let claimable = claimable_by_mint(bagsfm_client, &wallet).await?;
+    let launches = ledger.read_all::<LaunchRecord>()?;
+
+    let mut total = 0;
+    for launch in &launches {
+        let lamports = claimable.get(&launch.mint).map_or(0, |fee| fee.claimable_lamports);
+        total += lamports;
+        log::info!("{:<8} {}  claimable {:.6} SOL", launch.symbol, launch.mint, lamports_to_sol(lamports));
+    }
+    for (mint, fee) in &claimable {
+        if !launches.iter().any(|launch| &launch.mint == mint) {
+            total += fee.claimable_lamports;
+            log::info!("{:<8} {}  claimable {:.6} SOL (not in launch ledger)",
+                "?", mint, lamports_to_sol(fee.claimable_lamports));
+        }
+    }
+    let claimed: u64 = claimable.values().map(|fee| fee.total_claimed_lamports).sum();
+
+    log::info!("Total claimable creator fees: {:.6} SOL ({:.6} SOL claimed so far)",
+        lamports_to_sol(total), lamports_to_sol(claimed));
+    Ok(())
+}
+
+/// Claims creator fees for `mint`, or for every mint with a positive balance
+/// when `all` is set.
+pub async fn claim(
+    _cfg: &Config,
+    mint: Option<&str>,
+    all: bool,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: &dyn LaunchpadApi,
+    ledger: &Ledger,
+) -> Result<()> {
+    let wallet = solana_client.wallet_pubkey().to_string();
+    let claimable = claimable_by_mint(bagsfm_client, &wallet).await?;
+
+    let targets: Vec<&ClaimableFee> = match (mint, all) {
+        (Some(mint), _) => vec![claimable.get(mint)
+            .ok_or_else(|| anyhow::anyhow!("No claimable fees for mint {}", mint))?],
+        (None, true) => claimable.values().filter(|fee| fee.claimable_lamports > 0).collect(),
+        (None, false) => return Err(anyhow::anyhow!("Pass --mint <MINT> or --all")),
+    };
+
+    if targets.is_empty() {
+        log::info!("Nothing to claim");
+        return Ok(());
+    }
+
+    for fee in targets {
+        log::info!("Claiming {:.6} SOL of creator fees for {}...",
+            lamports_to_sol(fee.claimable_lamports), fee.token_mint);
+
+        let claim_txs = bagsfm_client.create_fee_claim_transactions(&FeeClaimRequest {
+            wallet: wallet.clone(),
+            token_mint: fee.token_mint.clone(),
+        }).await.with_context(|| format!("failed to build claim transactions for {}", fee.token_mint))?;
+
+        let mut signatures = Vec::with_capacity(claim_txs.transactions.len());
+        for tx in &claim_txs.transactions {
+            let signature = solana_client.sign_and_send_serialized(tx).await
+                .with_context(|| format!("claim transaction for {} failed", fee.token_mint))?;
+            log::info!("  Claim confirmed: {}", signature);
+            signatures.push(signature.to_string());
+        }
+
+        ledger.append(&FeeClaimRecord {
+            time_utc: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            wallet: wallet.clone(),
+            mint: fee.token_mint.clone(),
+            amount_lamports: fee.claimable_lamports,
+            signatures,
+        })?;
+    }
+
+    Ok(())
+}
+
+async fn claimable_by_mint(client: &dyn LaunchpadApi, wallet: &str) -> Result<BTreeMap<String, ClaimableFee>> {
+    let fees = client.get_claimable_fees(wallet).await
+        .context("failed to fetch claimable fees from bags.fm")?;
+    Ok(fees.into_iter().map(|fee| (fee.token_mint.clone(), fee)).collect())
+}
+
+fn lamports_to_sol(lamports: u64) -> f64 {
+    lamports as f64 / 1_000_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockLaunchpad, MockSolana};
+
+    #[tokio::test]
+    async fn claim_all_skips_empty_balances_and_records_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(0);
+        let launchpad = MockLaunchpad::default()
+            .with_claimable("MintA", 1_500_000)
+            .with_claimable("MintB", 0);
+        let ledger = Ledger::new(dir.path());
+
+        claim(&cfg, None, true, &solana, &launchpad, &ledger).await.unwrap();
+
+        assert_eq!(solana.sent(), vec!["claim-tx:MintA".to_string()]);
+        let claims = ledger.read_all::<FeeClaimRecord>().unwrap();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[0].mint, "MintA");
+        assert_eq!(claims[0].amount_lamports, 1_500_000);
+    }
+
+    #[tokio::test]
+    async fn claim_unknown_mint_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(0);
+        let launchpad = MockLaunchpad::default();
+        let ledger = Ledger::new(dir.path());
+
+        assert!(claim(&cfg, Some("MintZ"), false, &solana, &launchpad, &ledger).await.is_err());
+        assert!(solana.sent().is_empty());
+    }
+}
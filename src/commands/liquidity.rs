@@ -0,0 +1,274 @@
+use crate::config::Config;
+use crate::dex::{DexPool, LiquidityQuote};
+use crate::trade::SlippagePolicy;
+use crate::venue::{AmmVenue, BondingCurveVenue, SolanaApi};
+use anyhow::{bail, Context, Result};
+
+/// Deposits up to `max_a` / `max_b` into `pool` at its current ratio. On
+/// DAMM v2 the liquidity goes into the existing `position`.
+pub async fn add(
+    cfg: &Config,
+    pool: &str,
+    max_a: u64,
+    max_b: u64,
+    position: Option<&str>,
+    solana_client: &dyn SolanaApi,
+    dex_client: &dyn AmmVenue,
+) -> Result<()> {
+    let pool = dex_client.get_pool_info(pool).await?;
+    let quote = dex_client.add_liquidity(&pool, max_a, max_b).await?;
+    log_quote("Deposit", &pool, &quote);
+    if quote.lp_amount == 0 {
+        bail!("Deposit of {} / {} is too small to mint any LP", max_a, max_b);
+    }
+
+    for (mint, needed) in [(&pool.token_a, quote.token_a_amount), (&pool.token_b, quote.token_b_amount)] {
+        let balance = solana_client.get_token_balance(mint).await?;
+        if balance < needed {
+            bail!("Insufficient {} balance: {} < {} (native SOL must be wrapped first)", mint, balance, needed);
+        }
+    }
+
+    let slippage_bps = SlippagePolicy::from_config(cfg).slippage_bps;
+    let owner = solana_client.wallet_pubkey();
+    let instructions = dex_client
+        .create_add_liquidity_instructions(&pool, &owner, &quote, slippage_bps, position)
+        .await?;
+    let signature = solana_client.send_instructions(&instructions).await
+        .with_context(|| format!("deposit into {} failed", pool.address))?;
+    log::info!("Deposit confirmed: {}", signature);
+    Ok(())
+}
+
+/// Withdraws `lp_amount`, or everything the wallet (or `position`) holds
+/// when `all` is set.
+pub async fn remove(
+    cfg: &Config,
+    pool: &str,
+    lp_amount: Option<u128>,
+    all: bool,
+    position: Option<&str>,
+    solana_client: &dyn SolanaApi,
+    dex_client: &dyn AmmVenue,
+) -> Result<()> {
+    let pool = dex_client.get_pool_info(pool).await?;
+    let owner = solana_client.wallet_pubkey();
+    let held = dex_client.lp_balance(&pool, &owner, position).await?;
+    let lp_amount = match (lp_amount, all) {
+        (Some(amount), _) => amount,
+        (None, true) => held,
+        (None, false) => bail!("Pass --lp-amount <AMOUNT> or --all"),
+    };
+    if lp_amount == 0 {
+        bail!("Nothing to withdraw from {}", pool.address);
+    }
+    if lp_amount > held {
+        bail!("Cannot withdraw {} LP from {}: only {} held", lp_amount, pool.address, held);
+    }
+
+    let quote = dex_client.remove_liquidity(&pool, lp_amount).await?;
+    log_quote("Withdrawal", &pool, &quote);
+
+    let slippage_bps = SlippagePolicy::from_config(cfg).slippage_bps;
+    let instructions = dex_client
+        .create_remove_liquidity_instructions(&pool, &owner, &quote, slippage_bps, position)
+        .await?;
+    let signature = solana_client.send_instructions(&instructions).await
+        .with_context(|| format!("withdrawal from {} failed", pool.address))?;
+    log::info!("Withdrawal confirmed: {}", signature);
+    Ok(())
+}
+
+/// Shows the pool's reserves and LP supply and what the wallet's share
+/// (or `position`) would withdraw right now.
+pub async fn show(
+    _cfg: &Config,
+    pool: &str,
+    position: Option<&str>,
+    solana_client: &dyn SolanaApi,
+    dex_client: &dyn AmmVenue,
+) -> Result<()> {
+    let pool = dex_client.get_pool_info(pool).await?;
+    log::info!("{:?} pool {}: {} {} / {} {}",
+        pool.dex_type, pool.address, pool.reserve_a, pool.token_a, pool.reserve_b, pool.token_b);
+
+    let held = dex_client.lp_balance(&pool, &solana_client.wallet_pubkey(), position).await?;
+    if held == 0 {
+        log::info!("No liquidity held in this pool");
+        return Ok(());
+    }
+    let quote = dex_client.remove_liquidity(&pool, held).await?;
+    log::info!("Held: {} of {} LP ({:.4}%), redeemable for {} {} + {} {}",
+        held,
+        quote.lp_supply,
+        share_percent(&quote),
+        quote.token_a_amount, pool.token_a,
+        quote.token_b_amount, pool.token_b
+    );
+    Ok(())
+}
+
+/// Opens a new DAMM v2 position in `pool` holding up to `max_a` / `max_b`.
+pub async fn open(
+    cfg: &Config,
+    pool: &str,
+    max_a: u64,
+    max_b: u64,
+    solana_client: &dyn SolanaApi,
+    meteora_client: &dyn BondingCurveVenue,
+) -> Result<()> {
+    let pool = meteora_client.get_damm_pool(pool).await?;
+    log::info!("Pool {}: {} {} / {} {} in reserve, {:.2}% fee",
+        pool.address, pool.reserve_a, pool.token_a, pool.reserve_b, pool.token_b, pool.fee_rate * 100.0);
+    for (mint, needed) in [(&pool.token_a, max_a), (&pool.token_b, max_b)] {
+        let balance = solana_client.get_token_balance(mint).await?;
+        if balance < needed {
+            bail!("Insufficient {} balance: {} < {} (native SOL must be wrapped first)", mint, balance, needed);
+        }
+    }
+
+    let slippage_bps = SlippagePolicy::from_config(cfg).slippage_bps;
+    let change = meteora_client.open_damm_position(&pool, max_a, max_b, slippage_bps, solana_client).await?;
+    log::info!("Opened position {} in {}: {} liquidity for {} {} + {} {} ({})",
+        change.position, pool.address, change.liquidity_delta,
+        change.token_a_amount, pool.token_a,
+        change.token_b_amount, pool.token_b,
+        change.signature
+    );
+    Ok(())
+}
+
+/// Withdraws whatever liquidity a DAMM v2 position still holds, then closes
+/// it and reclaims its rent.
+pub async fn close(
+    cfg: &Config,
+    position: &str,
+    solana_client: &dyn SolanaApi,
+    meteora_client: &dyn BondingCurveVenue,
+) -> Result<()> {
+    let state = meteora_client.get_damm_position(position).await?;
+    if state.unlocked_liquidity > 0 {
+        let slippage_bps = SlippagePolicy::from_config(cfg).slippage_bps;
+        let change = meteora_client.decrease_damm_position(position, None, slippage_bps, solana_client).await?;
+        log::info!("Withdrew {} liquidity ({} A + {} B): {}",
+            change.liquidity_delta, change.token_a_amount, change.token_b_amount, change.signature);
+    }
+    let signature = meteora_client.close_damm_position(position, solana_client).await
+        .with_context(|| format!("closing position {} failed", position))?;
+    log::info!("Position {} closed: {}", position, signature);
+    Ok(())
+}
+
+fn log_quote(action: &str, pool: &DexPool, quote: &LiquidityQuote) {
+    log::info!("{} on {:?} pool {}: {} {} + {} {} for {} LP ({:.4}% of supply)",
+        action, pool.dex_type, pool.address,
+        quote.token_a_amount, pool.token_a,
+        quote.token_b_amount, pool.token_b,
+        quote.lp_amount,
+        share_percent(quote)
+    );
+}
+
+fn share_percent(quote: &LiquidityQuote) -> f64 {
+    quote.lp_amount as f64 / quote.lp_supply.max(1) as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::DexType;
+    use crate::meteora::MeteoraDammPool;
+    use crate::mock::{MockAmm, MockBondingCurve, MockSolana};
+
+    fn pool() -> DexPool {
+        DexPool {
+            address: "Pool1".to_string(),
+            token_a: "MintA".to_string(),
+            token_b: "MintB".to_string(),
+            reserve_a: 1_000_000,
+            reserve_b: 4_000_000,
+            fee_numerator: 25,
+            fee_denominator: 10_000,
+            dex_type: DexType::Raydium,
+        }
+    }
+
+    fn words(data: &[u8]) -> Vec<u64> {
+        data.chunks(8).map(|word| u64::from_le_bytes(word.try_into().unwrap())).collect()
+    }
+
+    #[tokio::test]
+    async fn add_balances_the_deposit_and_bounds_it() {
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(1_000_000_000)
+            .with_token_balance("MintA", 50_000)
+            .with_token_balance("MintB", 50_000);
+        let amm = MockAmm::default().with_pool(pool()).with_lp("Pool1", 1_000, 0);
+
+        add(&cfg, "Pool1", 50_000, 20_000, None, &solana, &amm).await.unwrap();
+
+        let sent = solana.sent_instructions.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        // Token B is the binding side: 5_000 A + 20_000 B mints 5 LP.
+        let [lp, max_a, max_b] = words(&sent[0][0].data)[..] else { panic!() };
+        assert_eq!(lp, 5);
+        assert_eq!(max_a, crate::trade::max_in(5_000, cfg.slippage_bps));
+        assert_eq!(max_b, crate::trade::max_in(20_000, cfg.slippage_bps));
+    }
+
+    #[tokio::test]
+    async fn add_refuses_without_the_tokens() {
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(1_000_000_000).with_token_balance("MintA", 50_000);
+        let amm = MockAmm::default().with_pool(pool()).with_lp("Pool1", 1_000, 0);
+
+        let err = add(&cfg, "Pool1", 50_000, 20_000, None, &solana, &amm).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient MintB"), "{}", err);
+        assert!(solana.sent_instructions.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn remove_all_withdraws_the_held_share_with_minimums() {
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(1_000_000_000);
+        let amm = MockAmm::default().with_pool(pool()).with_lp("Pool1", 1_000, 10);
+
+        let err = remove(&cfg, "Pool1", Some(11), false, None, &solana, &amm).await.unwrap_err();
+        assert!(err.to_string().contains("only 10 held"), "{}", err);
+
+        remove(&cfg, "Pool1", None, true, None, &solana, &amm).await.unwrap();
+        let sent = solana.sent_instructions.lock().unwrap();
+        let [lp, min_a, min_b] = words(&sent[0][0].data)[..] else { panic!() };
+        assert_eq!(lp, 10);
+        assert_eq!(min_a, crate::trade::min_out(10_000, cfg.slippage_bps));
+        assert_eq!(min_b, crate::trade::min_out(40_000, cfg.slippage_bps));
+    }
+
+    #[tokio::test]
+    async fn open_then_close_a_damm_position() {
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(1_000_000_000)
+            .with_token_balance("MintA", 50_000)
+            .with_token_balance("MintB", 50_000);
+        let meteora = MockBondingCurve::default().with_damm_pool(MeteoraDammPool {
+            address: "Damm1".to_string(),
+            token_a: "MintA".to_string(),
+            token_b: "MintB".to_string(),
+            reserve_a: 1_000_000,
+            reserve_b: 1_000_000,
+            fee_rate: 0.0025,
+        });
+
+        let err = open(&cfg, "Damm1", 60_000, 1_000, &solana, &meteora).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient MintA"), "{}", err);
+
+        open(&cfg, "Damm1", 20_000, 30_000, &solana, &meteora).await.unwrap();
+        let position = meteora.positions.lock().unwrap().keys().next().unwrap().clone();
+        assert_eq!(meteora.positions.lock().unwrap()[&position], 20_000);
+
+        close(&cfg, &position, &solana, &meteora).await.unwrap();
+        assert!(meteora.positions.lock().unwrap().is_empty());
+        // Open, withdraw, close.
+        assert_eq!(solana.sent_instructions.lock().unwrap().len(), 3);
+    }
+}
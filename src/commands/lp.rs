@@ -0,0 +1,104 @@
+use crate::config::Config;
+use crate::meteora::DammPositionFees;
+use crate::store::{Ledger, LpFeeRecord};
+use crate::venue::{BondingCurveVenue, SolanaApi};
+use anyhow::{anyhow, Context, Result};
+use chrono::{SecondsFormat, Utc};
+
+/// Lists unclaimed LP fees on every DAMM v2 position the wallet holds, or
+/// only `position`. With `claim`, claims each one that has any and records
+/// the income.
+pub async fn fees(
+    _cfg: &Config,
+    position: Option<&str>,
+    claim: bool,
+    solana_client: &dyn SolanaApi,
+    meteora_client: &dyn BondingCurveVenue,
+    ledger: &Ledger,
+) -> Result<()> {
+    let wallet = solana_client.wallet_pubkey();
+    let mut positions = meteora_client.unclaimed_damm_fees(&wallet).await
+        .context("failed to read DAMM v2 positions")?;
+    if let Some(position) = position {
+        positions.retain(|fees| fees.position == position);
+        if positions.is_empty() {
+            return Err(anyhow!("Position {} is not held by {}", position, wallet));
+        }
+    }
+
+    if positions.is_empty() {
+        log::info!("No DAMM v2 positions held by {}", wallet);
+        return Ok(());
+    }
+    for fees in &positions {
+        log::info!("{} (pool {}): {} {} + {} {} unclaimed",
+            fees.position, fees.pool, fees.fee_a, fees.token_a, fees.fee_b, fees.token_b);
+    }
+    if !claim {
+        return Ok(());
+    }
+
+    let claimable: Vec<&DammPositionFees> = positions.iter().filter(|fees| !fees.is_zero()).collect();
+    if claimable.is_empty() {
+        log::info!("Nothing to claim");
+        return Ok(());
+    }
+    for fees in claimable {
+        let signature = meteora_client.claim_damm_fees(&fees.position, solana_client).await?;
+        log::info!("  Claimed fees for {}: {}", fees.position, signature);
+        ledger.append(&LpFeeRecord {
+            time_utc: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            wallet: wallet.to_string(),
+            pool: fees.pool.clone(),
+            position: fees.position.clone(),
+            token_a_mint: fees.token_a.clone(),
+            token_b_mint: fees.token_b.clone(),
+            amount_a: fees.fee_a,
+            amount_b: fees.fee_b,
+            signature: signature.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockBondingCurve, MockSolana};
+
+    fn position_fees(position: &str, fee_a: u64, fee_b: u64) -> DammPositionFees {
+        DammPositionFees {
+            position: position.to_string(),
+            pool: "Damm1".to_string(),
+            token_a: "MintA".to_string(),
+            token_b: "So11111111111111111111111111111111111111112".to_string(),
+            fee_a,
+            fee_b,
+        }
+    }
+
+    #[tokio::test]
+    async fn claims_positions_with_fees_and_records_the_income() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Config::from_env().unwrap();
+        let solana = MockSolana::with_balance(0);
+        let meteora = MockBondingCurve::default()
+            .with_lp_fees(position_fees("Pos1", 1_000, 250_000))
+            .with_lp_fees(position_fees("Pos2", 0, 0));
+        let ledger = Ledger::new(dir.path());
+
+        fees(&cfg, None, false, &solana, &meteora, &ledger).await.unwrap();
+        assert!(solana.sent_instructions.lock().unwrap().is_empty());
+
+        fees(&cfg, None, true, &solana, &meteora, &ledger).await.unwrap();
+        assert_eq!(solana.sent_instructions.lock().unwrap().len(), 1);
+        let records = ledger.read_all::<LpFeeRecord>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!((records[0].position.as_str(), records[0].amount_a, records[0].amount_b), ("Pos1", 1_000, 250_000));
+
+        // Claimed fees are gone; nothing left to send.
+        fees(&cfg, Some("Pos1"), true, &solana, &meteora, &ledger).await.unwrap();
+        assert_eq!(ledger.read_all::<LpFeeRecord>().unwrap().len(), 1);
+        assert!(fees(&cfg, Some("Pos3"), false, &solana, &meteora, &ledger).await.is_err());
+    }
+}
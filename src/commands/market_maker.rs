@@ -1,19 +1,40 @@
 
 use crate::config::Config;
-use crate::solana::SolanaClient;
-use crate::dex::DexClient;
-use anyhow::Result;
+use crate::dex::{DexPool, DexType};
+use crate::meteora::graduation::TokenMarket;
+use crate::venue::{AmmVenue, BondingCurveVenue};
+use anyhow::{anyhow, bail, Result};
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
-pub async fn run(cfg: &Config, pair: &str, spread_bps: u32) -> Result<()> {
+/// Market-makes on `pair`, or on the deepest pool for `mint` when no pool
+/// address is given. A Meteora DBC pool is followed onto its DAMM v2 pool
+/// when the curve graduates.
+pub async fn run(
+    cfg: &Config,
+    pair: Option<&str>,
+    mint: Option<&str>,
+    spread_bps: u32,
+    dex_client: &dyn AmmVenue,
+    meteora_client: Arc<dyn BondingCurveVenue>,
+) -> Result<()> {
+    let mut pair = match (pair, mint) {
+        (Some(pair), _) => pair.to_string(),
+        (None, Some(mint)) => {
+            let pools = dex_client.find_pools(mint).await?;
+            for pool in &pools {
+                log::info!("Found {:?} pool {} ({} <-> {})", pool.dex_type, pool.address, pool.token_a, pool.token_b);
+            }
+            pools.first()
+                .map(|pool| pool.address.clone())
+                .ok_or_else(|| anyhow!("No pools found for mint {}", mint))?
+        }
+        (None, None) => bail!("Either a pool address or a mint is required"),
+    };
     log::info!("Starting market-maker for pair={} target_spread={} bps", pair, spread_bps);
     
-    // Initialize clients
-    let solana_client = SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path)?;
-    let dex_client = DexClient::new()?;
-    
     // Get pool information
-    let pool = dex_client.get_pool_info(pair).await?;
+    let pool = dex_client.get_pool_info(&pair).await?;
     log::info!("Pool: {} ({} <-> {})", pool.address, pool.token_a, pool.token_b);
     log::info!("Current reserves: {} SOL, {} tokens", 
         pool.reserve_a as f64 / 1_000_000_000.0,
@@ -29,7 +50,12 @@ pub async fn run(cfg: &Config, pair: &str, spread_bps: u32) -> Result<()> {
         log::info!("Market-making round {}/5", round);
         
         // Get current pool state
-        let current_pool = dex_client.get_pool_info(pair).await?;
+        let current_pool = dex_client.get_pool_info(&pair).await?;
+        let current_pool = follow_migration(cfg, current_pool, dex_client, meteora_client.clone()).await?;
+        if current_pool.address != pair {
+            log::info!("{} graduated; market-making moves to DAMM v2 pool {}", current_pool.token_a, current_pool.address);
+            pair = current_pool.address.clone();
+        }
         
         // Calculate optimal bid/ask prices
         let mid_price = (current_pool.reserve_b as f64) / (current_pool.reserve_a as f64);
@@ -39,7 +65,7 @@ pub async fn run(cfg: &Config, pair: &str, spread_bps: u32) -> Result<()> {
         log::info!("Mid price: {:.8}, Bid: {:.8}, Ask: {:.8}", mid_price, bid_price, ask_price);
         
         let base_order_size = 0.1; // 0.1 SOL per order
-        let base_order_lamports = (base_order_size * 1_000_000_000.0) as u64;
+        let _base_order_lamports = (base_order_size * 1_000_000_000.0) as u64;
         
         // Simulate placing orders
         log::debug!("Placing bid order: {} SOL at {:.8}", base_order_size, bid_price);
@@ -61,7 +87,7 @@ pub async fn run(cfg: &Config, pair: &str, spread_bps: u32) -> Result<()> {
         log::debug!("Rebalancing depth and inventory...");
         sleep(Duration::from_millis(300)).await;
         
-        let round_pnl = if filled_bid && filled_ask {
+        let _round_pnl = if filled_bid && filled_ask {
             let profit = (ask_price - bid_price) * base_order_size;
             log::info!("Round {} PnL: {:.6} SOL", round, profit);
             profit
@@ -75,7 +101,90 @@ pub async fn run(cfg: &Config, pair: &str, spread_bps: u32) -> Result<()> {
     }
     
     log::info!("Market-making session completed");
-    log::info!("Final pool state: {} ({} <-> {})", pool.address, pool.token_a, pool.token_b);
+    log::info!("Final pool state: {} ({} <-> {})", pair, pool.token_a, pool.token_b);
     
     Ok(())
 }
+
+/// The pool to quote on this round: `pool` itself unless it is a DBC curve
+/// that has graduated, in which case its DAMM v2 pool. A completed curve
+/// waits here until the migration lands, since neither pool trades.
+async fn follow_migration(
+    cfg: &Config,
+    pool: DexPool,
+    dex_client: &dyn AmmVenue,
+    meteora_client: Arc<dyn BondingCurveVenue>,
+) -> Result<DexPool> {
+    if pool.dex_type != DexType::MeteoraDbc {
+        return Ok(pool);
+    }
+    match TokenMarket::resolve(meteora_client, &pool.token_a, cfg).await? {
+        TokenMarket::Curve(_) => Ok(pool),
+        TokenMarket::Damm { pool: damm_pool, .. } => dex_client.get_pool_info(&damm_pool.address).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meteora::{dbc, DbcProgress, DbcStatus, MeteoraDammPool, MeteoraDbcPool};
+    use crate::mock::{MockAmm, MockBondingCurve};
+
+    fn pool(address: &str, dex_type: DexType) -> DexPool {
+        DexPool {
+            address: address.to_string(),
+            token_a: "Mint1".to_string(),
+            token_b: "So11111111111111111111111111111111111111112".to_string(),
+            reserve_a: 1_000_000,
+            reserve_b: 1_000_000,
+            fee_numerator: 25,
+            fee_denominator: 10_000,
+            dex_type,
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_a_graduated_curve_onto_its_damm_pool() {
+        let cfg = Config::from_env().unwrap();
+        let amm = MockAmm::default()
+            .with_pool(pool("Dbc1", DexType::MeteoraDbc))
+            .with_pool(pool("Damm1", DexType::MeteoraDammV2));
+        let progress = DbcProgress {
+            pool: "Dbc1".to_string(),
+            token_mint: "Mint1".to_string(),
+            quote_mint: "So11111111111111111111111111111111111111112".to_string(),
+            quote_decimals: 9,
+            quote_reserve: 40_000_000_000,
+            migration_quote_threshold: 85_000_000_000,
+            status: DbcStatus::Active,
+        };
+        let curve = Arc::new(MockBondingCurve::default()
+            .with_progress(progress.clone())
+            .with_pool(MeteoraDbcPool {
+                address: "Dbc1".to_string(),
+                token_mint: "Mint1".to_string(),
+                base_token: "SOL".to_string(),
+                current_price: 0.001,
+                curve: dbc::Curve::default(),
+                status: DbcStatus::Active,
+            }));
+
+        let raydium = follow_migration(&cfg, pool("Ray1", DexType::Raydium), &amm, curve.clone()).await.unwrap();
+        assert_eq!(raydium.address, "Ray1");
+        let live = follow_migration(&cfg, pool("Dbc1", DexType::MeteoraDbc), &amm, curve.clone()).await.unwrap();
+        assert_eq!(live.address, "Dbc1");
+
+        curve.progress.lock().unwrap().insert("Mint1".to_string(), DbcProgress { status: DbcStatus::Migrated, ..progress });
+        curve.damm_pools.lock().unwrap().insert("Mint1".to_string(), "Damm1".to_string());
+        curve.damms.lock().unwrap().insert("Damm1".to_string(), MeteoraDammPool {
+            address: "Damm1".to_string(),
+            token_a: "Mint1".to_string(),
+            token_b: "So11111111111111111111111111111111111111112".to_string(),
+            reserve_a: 1_000_000,
+            reserve_b: 1_000_000,
+            fee_rate: 0.0025,
+        });
+        let followed = follow_migration(&cfg, pool("Dbc1", DexType::MeteoraDbc), &amm, curve).await.unwrap();
+        assert_eq!((followed.address.as_str(), followed.dex_type), ("Damm1", DexType::MeteoraDammV2));
+    }
+}
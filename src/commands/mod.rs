@@ -4,3 +4,9 @@ pub mod sniper;
 pub mod market_maker;
 pub mod tracker;
 pub mod export;
+pub mod watch_launches;
+pub mod fees;
+pub mod liquidity;
+pub mod swap;
+pub mod curve_progress;
+pub mod lp;
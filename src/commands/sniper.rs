@@ -1,81 +1,458 @@
 
 use crate::config::Config;
-use crate::solana::SolanaClient;
-use crate::bagsfm::BagsFmClient;
-use crate::meteora::MeteoraClient;
-use anyhow::Result;
-use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
-
-pub async fn run(cfg: &Config, token: &str, budget_sol: f64) -> Result<()> {
-    log::info!("Sniper armed for token={} with budget {:.4} SOL", token, budget_sol);
-    
-    // Initialize clients
-    let solana_client = SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path)?;
-    let bagsfm_client = BagsFmClient::new();
-    let meteora_client = MeteoraClient::new()?;
-    
-    // Check wallet balance
-    let balance = solana_client.get_balance().await?;
-    let balance_sol = balance as f64 / 1_000_000_000.0;
-    log::info!("Wallet balance: {:.4} SOL", balance_sol);
-    
-    if balance_sol < budget_sol {
-        return Err(anyhow::anyhow!("Insufficient balance: {:.4} SOL < {:.4} SOL", balance_sol, budget_sol));
-    }
-    
+use crate::bagsfm::{BagsFmError, BagsFmToken, LaunchFeed};
+use crate::meteora::fee::FEE_DENOMINATOR;
+use crate::meteora::graduation::TokenMarket;
+use crate::meteora::{DbcSide, MeteoraDbcPool};
+use crate::numeric::format_units;
+use crate::store::Ledger;
+use crate::trade::{self, Fill, SlippagePolicy};
+use crate::venue::{BondingCurveVenue, LaunchpadApi, SolanaApi};
+use anyhow::{bail, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Which token to buy.
+#[derive(Debug, Clone, Copy)]
+pub enum TargetToken<'a> {
+    Mint(&'a str),
+    /// The next bags.fm launch from the launch feed (`--watch`).
+    NextLaunch,
+}
+
+/// What to snipe and how much of the curve's quote token to spend.
+#[derive(Debug, Clone, Copy)]
+pub struct Target<'a> {
+    pub token: TargetToken<'a>,
+    pub budget_sol: f64,
+}
+
+/// Buys `target` on its Meteora DBC curve and records the fill.
+pub async fn run(
+    cfg: &Config,
+    target: Target<'_>,
+    demo: bool,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: Arc<dyn LaunchpadApi>,
+    meteora_client: &dyn BondingCurveVenue,
+    ledger: &Ledger,
+) -> Result<()> {
+    let Target { token, budget_sol } = target;
+    let token = match token {
+        TargetToken::Mint(token) => token.to_string(),
+        TargetToken::NextLaunch => {
+            log::info!("Sniper waiting for the next bags.fm launch...");
+            let mut launches = LaunchFeed::from_config(bagsfm_client.clone(), cfg)?
+                .skip_backlog(true)
+                .spawn(1);
+            let launch = launches.recv().await
+                .ok_or_else(|| anyhow::anyhow!("Launch feed stopped before a launch was seen"))?;
+            log::info!("New launch detected: {} at {}", launch.token_address, launch.launch_time);
+            launch.token_address
+        }
+    };
+    let token = token.as_str();
+    log::info!("Sniper armed for token={} with budget {:.4} of the curve's quote token", token, budget_sol);
+
     // Get token info from bags.fm
     log::info!("Fetching token info from bags.fm...");
-    let token_info = match bagsfm_client.get_token_info(token).await {
+    let (token_info, placeholder) = match bagsfm_client.get_token_info(token).await {
         Ok(info) => {
-            log::info!("Token: {} ({}) - Supply: {}", info.name, info.symbol, info.total_supply);
-            info
+            log::info!("Token: {} ({}) - Supply: {}", info.name, info.symbol, format_units(info.total_supply, info.decimals));
+            (info, false)
+        },
+        Err(e) if demo => {
+            log::warn!("Failed to fetch token info from bags.fm ({}), using demo data", e);
+            (BagsFmToken::demo(token), true)
+        },
+        Err(BagsFmError::NotFound(_)) => {
+            return Err(anyhow::anyhow!("Token {} is not listed on bags.fm", token));
         },
-        Err(_) => {
-            log::warn!("Failed to fetch token info from bags.fm, using fallback data");
-            crate::bagsfm::BagsFmToken {
-                address: token.to_string(),
-                name: "Unknown Token".to_string(),
-                symbol: "UNK".to_string(),
-                decimals: 9,
-                total_supply: "1000000000".to_string(),
-                price_usd: None,
-                market_cap: None,
-                volume_24h: None,
-                liquidity_usd: None,
-                created_at: chrono::Utc::now().to_rfc3339(),
-            }
+        Err(e) => {
+            return Err(anyhow::Error::new(e).context("Refusing to snipe without token info from bags.fm"));
         }
     };
     
     log::info!("Monitoring for Meteora DBC pool creation...");
     
-    let dbc_pool_address = format!("DbcPool{}", token);
-    let dbc_pool = meteora_client.get_dbc_pool_info(&dbc_pool_address).await?;
+    let dbc_pool = meteora_client.find_dbc_pool(token).await?;
     log::info!("DBC pool detected: {} ({} <-> {})", 
         dbc_pool.address, dbc_pool.base_token, dbc_pool.token_mint);
-    
-    let (tokens_received, price_impact) = meteora_client.buy_from_dbc(
-        &dbc_pool,
-        budget_sol
-    ).await?;
-    
-    log::info!("DBC calculation: {:.4} SOL -> {} tokens (price impact: {:.2}%)", 
-        budget_sol, 
-        tokens_received as f64 / 10_f64.powi(token_info.decimals as i32),
-        price_impact * 100.0
+    wait_for_fee(cfg, meteora_client, &dbc_pool).await?;
+
+    let quote = meteora_client.buy_from_dbc(&dbc_pool, budget_sol).await?;
+    log::info!("DBC calculation: {} {} -> {} tokens (price impact: {:.2}%)",
+        quote.amount_in,
+        dbc_pool.base_token,
+        format_units(quote.amount_out as u128, token_info.decimals),
+        quote.price_impact_bps as f64 / 100.0
     );
-    
+
+    // The quote is in the curve's quote mint, which is not always SOL.
+    let balance = if dbc_pool.base_token == spl_token::native_mint::id().to_string() {
+        solana_client.get_balance().await?
+    } else {
+        solana_client.get_token_balance(&dbc_pool.base_token).await?
+    };
+    log::info!("Wallet balance: {} {}", balance, dbc_pool.base_token);
+    if balance < quote.amount_in {
+        bail!("Insufficient balance: {} < {} {}", balance, quote.amount_in, dbc_pool.base_token);
+    }
+
+    let plan = SlippagePolicy::sniper(cfg).plan(quote)?;
+    log::info!("Minimum out at {} bps slippage: {} tokens",
+        plan.slippage_bps,
+        format_units(plan.min_out as u128, token_info.decimals)
+    );
+    if placeholder {
+        log::warn!("Token info is demo data; stopping before the swap is signed");
+        return Ok(());
+    }
+
     log::info!("Executing swap transaction...");
-    
-  
-    
-    let signature = "DemoSignature123456789".to_string();
-    log::info!("BUY executed successfully! Signature: {}", signature);
-    log::info!("Received {} tokens for {:.4} SOL", 
-        tokens_received as f64 / 10_f64.powi(token_info.decimals as i32),
-        budget_sol
+
+    let fill = meteora_client
+        .swap_dbc(&dbc_pool, DbcSide::Buy, plan.quote.amount_in, plan.min_out, solana_client)
+        .await?;
+    trade::record_fill(
+        ledger,
+        &Fill {
+            venue: "meteora-dbc",
+            pool: &dbc_pool.address,
+            input_mint: &fill.input_mint,
+            output_mint: &fill.output_mint,
+            plan: &plan,
+            actual_out: fill.amount_out,
+            signature: &fill.signature.to_string(),
+        },
+    )?;
+    log::info!("BUY executed successfully! Signature: {}", fill.signature);
+    log::info!("Received {} tokens for {} {}",
+        format_units(fill.amount_out as u128, token_info.decimals),
+        plan.quote.amount_in,
+        dbc_pool.base_token
     );
     
     Ok(())
 }
+
+/// Sells `amount` of a sniped `token` (the whole balance when `None`) and
+/// records the fill. Before graduation that is on its DBC curve; once the
+/// curve has migrated the sale follows it onto the DAMM v2 pool.
+pub async fn exit(
+    cfg: &Config,
+    token: &str,
+    amount: Option<u64>,
+    solana_client: &dyn SolanaApi,
+    meteora_client: Arc<dyn BondingCurveVenue>,
+    ledger: &Ledger,
+) -> Result<()> {
+    let balance = solana_client.get_token_balance(token).await?;
+    let amount = amount.unwrap_or(balance);
+    if amount == 0 {
+        bail!("No {} to sell", token);
+    }
+    if amount > balance {
+        bail!("Insufficient balance: {} < {} {}", balance, amount, token);
+    }
+
+    let market = TokenMarket::resolve(meteora_client.clone(), token, cfg).await?;
+    log::info!("Selling {} {} on {} pool {}", amount, token, market.venue_name(), market.pool_address());
+    let plan = SlippagePolicy::sniper(cfg).plan(market.quote_sell(meteora_client.as_ref(), amount).await?)?;
+    log::info!("Minimum out at {} bps slippage: {}", plan.slippage_bps, plan.min_out);
+
+    let fill = market.sell(meteora_client.as_ref(), amount, plan.min_out, solana_client).await?;
+    trade::record_fill(
+        ledger,
+        &Fill {
+            venue: market.venue_name(),
+            pool: market.pool_address(),
+            input_mint: &fill.input_mint,
+            output_mint: &fill.output_mint,
+            plan: &plan,
+            actual_out: fill.amount_out,
+            signature: &fill.signature.to_string(),
+        },
+    )?;
+    log::info!("SELL executed successfully! Signature: {}", fill.signature);
+    Ok(())
+}
+
+/// Logs the curve's current trading fee and, with `SNIPER_MAX_FEE_BPS` set,
+/// sleeps until the launch fee has decayed below it.
+async fn wait_for_fee(cfg: &Config, meteora_client: &dyn BondingCurveVenue, dbc_pool: &MeteoraDbcPool) -> Result<()> {
+    let max_fee_numerator = cfg.sniper_max_fee_bps as u64 * FEE_DENOMINATOR / 10_000;
+    let deadline = Instant::now() + Duration::from_millis(cfg.sniper_max_fee_wait_ms);
+    loop {
+        let (schedule, now) = meteora_client.dbc_fee_schedule(dbc_pool).await?;
+        let fee = schedule.at(now);
+        log::info!("Trading fee now {:.2}% (base {:.2}% + volatility {:.2}%), {:.2}% once decayed",
+            fee.fee_bps() / 100.0,
+            fee.base_fee_numerator as f64 * 100.0 / FEE_DENOMINATOR as f64,
+            fee.variable_fee_numerator as f64 * 100.0 / FEE_DENOMINATOR as f64,
+            schedule.base.min_fee_numerator() as f64 * 100.0 / FEE_DENOMINATOR as f64
+        );
+        if cfg.sniper_max_fee_bps == 0 || fee.fee_numerator() <= max_fee_numerator {
+            return Ok(());
+        }
+
+        let Some(wait) = schedule.wait_until(now, max_fee_numerator) else {
+            bail!("Fee of {:.0} bps never decays below SNIPER_MAX_FEE_BPS={}", fee.fee_bps(), cfg.sniper_max_fee_bps);
+        };
+        // Slot times are estimates; never spin faster than one slot.
+        let wait = wait.max(Duration::from_millis(400));
+        if Instant::now() + wait > deadline {
+            bail!("Fee of {:.0} bps needs ~{:?} to decay below SNIPER_MAX_FEE_BPS={}, beyond SNIPER_MAX_FEE_WAIT_MS",
+                fee.fee_bps(), wait, cfg.sniper_max_fee_bps);
+        }
+        log::info!("Waiting ~{:?} for the fee to decay below {} bps", wait, cfg.sniper_max_fee_bps);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meteora::{dbc, DbcStatus, MeteoraDbcPool};
+    use crate::mock::{MockBondingCurve, MockLaunchpad, MockSolana};
+    use solana_sdk::pubkey::Pubkey;
+
+    fn dbc_pool(token: &str) -> MeteoraDbcPool {
+        MeteoraDbcPool {
+            address: Pubkey::new_unique().to_string(),
+            token_mint: token.to_string(),
+            base_token: spl_token::native_mint::id().to_string(),
+            current_price: 0.001,
+            curve: dbc::Curve::default(),
+            status: DbcStatus::Active,
+        }
+    }
+
+    fn target(token: &str) -> Target<'_> {
+        Target { token: TargetToken::Mint(token), budget_sol: 1.0 }
+    }
+
+    #[tokio::test]
+    async fn watch_snipes_the_next_launch_not_the_backlog() {
+        use crate::bagsfm::BagsFmLaunch;
+        use crate::numeric::Decimal;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut cfg = Config::from_env().unwrap();
+        cfg.launch_feed_state_path = dir.path().join("feed.json").to_string_lossy().into_owned();
+        cfg.launch_feed_poll_ms = 10;
+        let launch = |token: &str, launch_time: i64| BagsFmLaunch {
+            token_address: token.to_string(),
+            initial_liquidity_sol: Decimal::new(1, 0),
+            launch_time: chrono::DateTime::from_timestamp(launch_time, 0).unwrap(),
+            status: "live".to_string(),
+        };
+        let launchpad = Arc::new(
+            MockLaunchpad::default()
+                .with_launch(launch("Old", 100))
+                .with_token(BagsFmToken::demo("Old"))
+                .with_token(BagsFmToken::demo("New")),
+        );
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Old")).with_pool(dbc_pool("New"));
+
+        let ledger = Ledger::new(dir.path());
+        let target = Target { token: TargetToken::NextLaunch, budget_sol: 1.0 };
+        let sniping = run(&cfg, target, false, &solana, launchpad.clone(), &curve, &ledger);
+        let launching = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            launchpad.launches.lock().unwrap().push(launch("New", 200));
+        };
+        let (result, _) = tokio::join!(sniping, launching);
+        result.unwrap();
+
+        let swaps = curve.swaps.lock().unwrap().clone();
+        assert_eq!(swaps.len(), 1);
+        assert_eq!(curve.pools.lock().unwrap()[&swaps[0].0].token_mint, "New");
+    }
+
+    #[tokio::test]
+    async fn refuses_to_snipe_unlisted_token_outside_demo_mode() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default());
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint1"));
+
+        let err = run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("not listed"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn demo_data_quotes_but_never_sends() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default());
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint1"));
+        let ledger = Ledger::new(dir.path());
+
+        run(&cfg, target("Mint1"), true, &solana, launchpad, &curve, &ledger).await.unwrap();
+        assert!(curve.swaps.lock().unwrap().is_empty());
+        assert!(ledger.read_all::<crate::store::FillRecord>().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn snipes_listed_token() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint1"));
+
+        let ledger = Ledger::new(dir.path());
+        run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &ledger).await.unwrap();
+
+        let swaps = curve.swaps.lock().unwrap().clone();
+        assert_eq!(swaps.len(), 1);
+        let (_, side, amount_in, min_out) = swaps[0].clone();
+        assert_eq!((side, amount_in), (DbcSide::Buy, 1_000_000_000));
+        let fills: Vec<crate::store::FillRecord> = ledger.read_all().unwrap();
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].venue.as_str(), fills[0].output_mint.as_str()), ("meteora-dbc", "Mint1"));
+        assert_eq!((fills[0].min_out, fills[0].actual_out), (min_out, min_out));
+        assert_eq!(fills[0].signature, solana_sdk::signature::Signature::from([1u8; 64]).to_string());
+    }
+
+    #[tokio::test]
+    async fn fails_without_a_live_dbc_pool_for_the_mint() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint2"));
+
+        let err = run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("No active Meteora DBC pool"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn aborts_before_signing_when_price_impact_is_too_high() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        let curve = MockBondingCurve::default()
+            .with_pool(dbc_pool("Mint1"))
+            .with_price_impact(cfg.max_price_impact_bps as f64 / 10_000.0 + 0.01);
+
+        let err = run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("Price impact"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rejects_budget_above_balance() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(100_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint1"));
+
+        let err = run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient balance"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn spends_the_budget_in_the_quote_mints_own_units() {
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let pool = MeteoraDbcPool { base_token: usdc.to_string(), ..dbc_pool("Mint1") };
+        let curve = MockBondingCurve::default().with_pool(pool).with_quote_decimals(6);
+
+        // Plenty of SOL is no help on a USDC-quoted curve.
+        let poor = MockSolana::with_balance(5_000_000_000).with_token_balance(usdc, 999_999);
+        let err = run(&cfg, target("Mint1"), false, &poor, launchpad.clone(), &curve, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient balance"), "{}", err);
+
+        let solana = MockSolana::with_balance(0).with_token_balance(usdc, 1_000_000);
+        run(&cfg, target("Mint1"), false, &solana, launchpad, &curve, &Ledger::new(dir.path())).await.unwrap();
+        let swaps = curve.swaps.lock().unwrap().clone();
+        assert_eq!((swaps[0].1, swaps[0].2), (DbcSide::Buy, 1_000_000));
+    }
+
+    #[tokio::test]
+    async fn exits_on_the_damm_pool_once_the_curve_has_migrated() {
+        use crate::meteora::{DbcProgress, MeteoraDammPool};
+        use crate::store::FillRecord;
+
+        let cfg = Config::from_env().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::new(dir.path());
+        let solana = MockSolana::with_balance(5_000_000_000).with_token_balance("Mint1", 1_000_000);
+        let pool = dbc_pool("Mint1");
+        let progress = DbcProgress {
+            pool: pool.address.clone(),
+            token_mint: "Mint1".to_string(),
+            quote_mint: spl_token::native_mint::id().to_string(),
+            quote_decimals: 9,
+            quote_reserve: 10_000_000_000,
+            migration_quote_threshold: 85_000_000_000,
+            status: DbcStatus::Active,
+        };
+        let curve = Arc::new(MockBondingCurve::default().with_pool(pool.clone()).with_progress(progress.clone()));
+
+        exit(&cfg, "Mint1", Some(400_000), &solana, curve.clone(), &ledger).await.unwrap();
+        assert_eq!(curve.swaps.lock().unwrap()[0].1, DbcSide::Sell);
+
+        curve.progress.lock().unwrap().insert("Mint1".to_string(), DbcProgress { status: DbcStatus::Migrated, ..progress });
+        curve.damm_pools.lock().unwrap().insert("Mint1".to_string(), "Damm1".to_string());
+        curve.damms.lock().unwrap().insert("Damm1".to_string(), MeteoraDammPool {
+            address: "Damm1".to_string(),
+            token_a: "Mint1".to_string(),
+            token_b: spl_token::native_mint::id().to_string(),
+            reserve_a: 1_000_000_000,
+            reserve_b: 1_000_000_000,
+            fee_rate: 0.0025,
+        });
+        exit(&cfg, "Mint1", None, &solana, curve.clone(), &ledger).await.unwrap();
+
+        assert_eq!(curve.swaps.lock().unwrap().len(), 1);
+        let damm_swaps = curve.damm_swaps.lock().unwrap().clone();
+        assert_eq!(damm_swaps.len(), 1);
+        assert_eq!((damm_swaps[0].0.as_str(), damm_swaps[0].1.as_str(), damm_swaps[0].2), ("Damm1", "Mint1", 1_000_000));
+        let fills: Vec<FillRecord> = ledger.read_all().unwrap();
+        let venues: Vec<&str> = fills.iter().map(|fill| fill.venue.as_str()).collect();
+        assert_eq!(venues, ["meteora-dbc", "meteora-damm-v2"]);
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_launch_fee_to_decay() {
+        use crate::meteora::fee::{ActivationType, BaseFee, FeeSchedule, FeeSchedulerMode};
+
+        let mut cfg = Config::from_env().unwrap();
+        cfg.sniper_max_fee_bps = 200;
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(5_000_000_000);
+        let launchpad: Arc<dyn LaunchpadApi> = Arc::new(MockLaunchpad::default().with_token(BagsFmToken::demo("Mint1")));
+        // 50% for the activation slot, then 1%.
+        let schedule = FeeSchedule {
+            base: BaseFee {
+                cliff_fee_numerator: 500_000_000,
+                number_of_period: 1,
+                period_frequency: 1,
+                reduction_factor: 490_000_000,
+                mode: FeeSchedulerMode::Linear,
+            },
+            activation_type: ActivationType::Slot,
+            ..FeeSchedule::constant(0)
+        };
+        let curve = MockBondingCurve::default().with_pool(dbc_pool("Mint1")).with_fee_schedule(schedule);
+
+        run(&cfg, target("Mint1"), false, &solana, launchpad.clone(), &curve, &Ledger::new(dir.path())).await.unwrap();
+        assert_eq!(*curve.clock.lock().unwrap(), 2);
+        assert_eq!(curve.swaps.lock().unwrap().len(), 1);
+
+        // A fee that stays at 50% is never worth waiting for.
+        let stuck = MockBondingCurve::default().with_pool(dbc_pool("Mint1")).with_fee_schedule(FeeSchedule::constant(500_000_000));
+        let err = run(&cfg, target("Mint1"), false, &solana, launchpad, &stuck, &Ledger::new(dir.path())).await.unwrap_err();
+        assert!(err.to_string().contains("never decays"), "{}", err);
+        assert!(stuck.swaps.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::executor::{Executor, Source};
+use crate::store::Ledger;
+use crate::venue::SolanaApi;
+use anyhow::{bail, Result};
+
+/// Swaps `amount` of `input_mint` for `output_mint` through whichever of
+/// the router and the quote provider returns more. `dry_run` stops after
+/// the comparison.
+pub async fn run(
+    executor: &Executor<'_>,
+    input_mint: &str,
+    output_mint: &str,
+    amount: u64,
+    dry_run: bool,
+    solana_client: &dyn SolanaApi,
+    ledger: &Ledger,
+) -> Result<()> {
+    let balance = solana_client.get_token_balance(input_mint).await?;
+    if balance < amount {
+        bail!("Insufficient {} balance: {} < {} (native SOL must be wrapped first)", input_mint, balance, amount);
+    }
+
+    let choice = executor.choose(input_mint, output_mint, amount).await?;
+    match &choice.source {
+        Source::Router(route) => {
+            for hop in &route.hops {
+                log::info!("  {:?} {}: {} {} -> {} {}",
+                    hop.pool.dex_type, hop.pool.address, hop.amount_in, hop.input_mint, hop.amount_out, hop.output_mint);
+            }
+            log::info!("Routing internally: {} out, min {}", choice.plan.quote.amount_out, choice.plan.min_out);
+        }
+        Source::Provider(quote) => {
+            log::info!("Routing via the provider ({}): {} out, min {}",
+                quote.route.join(" > "), choice.plan.quote.amount_out, choice.plan.min_out);
+        }
+    }
+    if dry_run {
+        log::info!("Dry run: not signing");
+        return Ok(());
+    }
+
+    let fill = executor.execute(&choice, input_mint, output_mint, solana_client, ledger).await?;
+    log::info!("Swap confirmed: {} ({} out)", fill.signature, fill.actual_out);
+    Ok(())
+}
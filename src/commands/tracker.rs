@@ -1,7 +1,7 @@
 
 use crate::config::Config;
-use crate::solana::SolanaClient;
-use crate::bagsfm::BagsFmClient;
+use crate::bagsfm::BagsFmError;
+use crate::venue::{LaunchpadApi, SolanaApi};
 use anyhow::Result;
 use tokio::time::{sleep, Duration};
 use std::str::FromStr;
@@ -16,17 +16,19 @@ struct WalletEvent {
     signature: Option<String>,
 }
 
-pub async fn run(cfg: &Config, wallet: &str) -> Result<()> {
+pub async fn run(
+    _cfg: &Config,
+    wallet: &str,
+    demo: bool,
+    solana_client: &dyn SolanaApi,
+    bagsfm_client: &dyn LaunchpadApi,
+) -> Result<()> {
     log::info!("Tracking wallet={} for swaps/transfers/LP events", wallet);
     
     // Validate wallet address
     let wallet_pubkey = Pubkey::from_str(wallet)
         .map_err(|e| anyhow::anyhow!("Invalid wallet address: {}", e))?;
     
-    // Initialize clients
-    let solana_client = SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path)?;
-    let bagsfm_client = BagsFmClient::new();
-    
     // Get initial wallet state
     let initial_balance = solana_client.get_balance().await?;
     let initial_balance_sol = initial_balance as f64 / 1_000_000_000.0;
@@ -34,52 +36,28 @@ pub async fn run(cfg: &Config, wallet: &str) -> Result<()> {
     
     // Get recent transactions
     log::info!("Fetching recent transaction history...");
+    let events = if demo {
+        demo_events()
+    } else {
+        recent_events(solana_client, &wallet_pubkey).await?
+    };
     
- 
     let mut event_count = 0;
     let mut total_volume = 0.0;
     
-    for i in 1..=10 {
-        sleep(Duration::from_millis(800)).await;
-        
-        let event = match i % 4 {
-            0 => WalletEvent {
-                event_type: "SWAP".to_string(),
-                token_address: Some("DemoToken123".to_string()),
-                amount: Some(0.5),
-                timestamp: chrono::Utc::now(),
-                signature: Some(format!("SwapSig{}", i)),
-            },
-            1 => WalletEvent {
-                event_type: "TRANSFER".to_string(),
-                token_address: None,
-                amount: Some(0.1),
-                timestamp: chrono::Utc::now(),
-                signature: Some(format!("TransferSig{}", i)),
-            },
-            2 => WalletEvent {
-                event_type: "LP_ADD".to_string(),
-                token_address: Some("DemoToken456".to_string()),
-                amount: Some(1.0),
-                timestamp: chrono::Utc::now(),
-                signature: Some(format!("LPAddSig{}", i)),
-            },
-            _ => WalletEvent {
-                event_type: "LP_REMOVE".to_string(),
-                token_address: Some("DemoToken789".to_string()),
-                amount: Some(0.3),
-                timestamp: chrono::Utc::now(),
-                signature: Some(format!("LPRemoveSig{}", i)),
-            },
-        };
+    for event in events {
+        if demo {
+            sleep(Duration::from_millis(800)).await;
+        }
         
         event_count += 1;
         if let Some(amount) = event.amount {
             total_volume += amount;
         }
         
-        log::info!("Event #{}: {} detected - {} SOL - Sig: {}", 
+        log::info!("Event #{} at {}: {} detected - {} SOL - Sig: {}",
             event_count,
+            event.timestamp.to_rfc3339(),
             event.event_type,
             event.amount.unwrap_or(0.0),
             event.signature.as_ref().unwrap_or(&"Unknown".to_string())
@@ -91,11 +69,18 @@ pub async fn run(cfg: &Config, wallet: &str) -> Result<()> {
                     log::info!("  Token: {} ({}) - Market Cap: ${:.2}", 
                         token_info.name, 
                         token_info.symbol,
-                        token_info.market_cap.unwrap_or(0.0)
+                        token_info.market_cap.map_or(0.0, |m| m.to_f64())
                     );
                 },
-                Err(_) => {
-                    log::debug!("  Token info not available for {}", token_addr);
+                Err(BagsFmError::NotFound(_)) => {
+                    log::debug!("  Token {} is not listed on bags.fm", token_addr);
+                },
+                Err(BagsFmError::RateLimited { retry_after }) => {
+                    log::warn!("  bags.fm rate limited token lookup for {}", token_addr);
+                    sleep(retry_after.unwrap_or(Duration::from_secs(1))).await;
+                },
+                Err(e) => {
+                    log::warn!("  Token info unavailable for {}: {}", token_addr, e);
                 }
             }
         }
@@ -106,9 +91,18 @@ pub async fn run(cfg: &Config, wallet: &str) -> Result<()> {
     }
     
     log::info!("Tracking session completed");
+    if let Some(cache_stats) = bagsfm_client.cache_stats() {
+        log::info!("bags.fm token cache: {} hits / {} misses ({:.0}% hit rate)",
+            cache_stats.tokens.hits,
+            cache_stats.tokens.misses,
+            cache_stats.tokens.hit_rate() * 100.0
+        );
+    }
     log::info!("Total events: {}", event_count);
     log::info!("Total volume: {:.2} SOL", total_volume);
-    log::info!("Average event size: {:.2} SOL", total_volume / event_count as f64);
+    if event_count > 0 {
+        log::info!("Average event size: {:.2} SOL", total_volume / event_count as f64);
+    }
     
     let final_balance = solana_client.get_balance().await?;
     let final_balance_sol = final_balance as f64 / 1_000_000_000.0;
@@ -119,3 +113,56 @@ pub async fn run(cfg: &Config, wallet: &str) -> Result<()> {
     
     Ok(())
 }
+
+async fn recent_events(solana_client: &dyn SolanaApi, wallet: &Pubkey) -> Result<Vec<WalletEvent>> {
+    let signatures = solana_client.get_signatures_for_address(wallet, 10).await?;
+    let events = signatures
+        .into_iter()
+        .map(|sig| WalletEvent {
+            event_type: if sig.err.is_some() { "FAILED_TX" } else { "TX" }.to_string(),
+            token_address: None,
+            amount: None,
+            timestamp: sig
+                .block_time
+                .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                .unwrap_or_else(chrono::Utc::now),
+            signature: Some(sig.signature),
+        })
+        .collect();
+    Ok(events)
+}
+
+fn demo_events() -> Vec<WalletEvent> {
+    (1..=10)
+        .map(|i| match i % 4 {
+            0 => WalletEvent {
+                event_type: "SWAP".to_string(),
+                token_address: Some("DemoToken123".to_string()),
+                amount: Some(0.5),
+                timestamp: chrono::Utc::now(),
+                signature: Some(format!("SwapSig{}", i)),
+            },
+            1 => WalletEvent {
+                event_type: "TRANSFER".to_string(),
+                token_address: None,
+                amount: Some(0.1),
+                timestamp: chrono::Utc::now(),
+                signature: Some(format!("TransferSig{}", i)),
+            },
+            2 => WalletEvent {
+                event_type: "LP_ADD".to_string(),
+                token_address: Some("DemoToken456".to_string()),
+                amount: Some(1.0),
+                timestamp: chrono::Utc::now(),
+                signature: Some(format!("LPAddSig{}", i)),
+            },
+            _ => WalletEvent {
+                event_type: "LP_REMOVE".to_string(),
+                token_address: Some("DemoToken789".to_string()),
+                amount: Some(0.3),
+                timestamp: chrono::Utc::now(),
+                signature: Some(format!("LPRemoveSig{}", i)),
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,37 @@
+use crate::config::Config;
+use crate::bagsfm::LaunchFeed;
+use crate::venue::LaunchpadApi;
+use anyhow::Result;
+use std::sync::Arc;
+
+pub async fn run(
+    cfg: &Config,
+    max_launches: Option<usize>,
+    skip_backlog: bool,
+    bagsfm_client: Arc<dyn LaunchpadApi>,
+) -> Result<()> {
+    log::info!("Watching bags.fm for new launches (poll every {} ms)", cfg.launch_feed_poll_ms);
+
+    let mut launches = LaunchFeed::from_config(bagsfm_client, cfg)?
+        .skip_backlog(skip_backlog)
+        .spawn(64);
+
+    let mut count = 0;
+    while let Some(launch) = launches.recv().await {
+        count += 1;
+        log::info!("Launch #{}: {} - {} SOL initial liquidity - {} ({})",
+            count,
+            launch.token_address,
+            launch.initial_liquidity_sol,
+            launch.launch_time,
+            launch.status
+        );
+
+        if max_launches.is_some_and(|max| count >= max) {
+            break;
+        }
+    }
+
+    log::info!("Launch watcher stopped after {} launches", count);
+    Ok(())
+}
@@ -1,5 +1,5 @@
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::env;
 
@@ -10,12 +10,28 @@ pub struct Config {
     pub wallet_path: String,
     pub wallet_address: String,
     pub slippage_bps: u32,
+    pub sniper_max_slippage_bps: u32,
+    pub sniper_max_fee_bps: u32,
+    pub sniper_max_fee_wait_ms: u64,
+    pub max_price_impact_bps: u32,
+    pub quote_api_url: Option<String>,
     pub max_retries: u32,
     pub budget_sol: f64,
     pub buy_cap_sol: f64,
     pub export_dir: String,
     pub report_prefix: String,
     pub log_level: String,
+    pub bagsfm_api_url: String,
+    pub bagsfm_api_key: Option<String>,
+    pub bagsfm_timeout_secs: u64,
+    pub bagsfm_proxy: Option<String>,
+    pub bagsfm_rate_limit_rps: f64,
+    pub bagsfm_cache_ttl_secs: u64,
+    pub launch_feed_state_path: String,
+    pub launch_feed_poll_ms: u64,
+    pub bundle_launch_delay_ms: u64,
+    pub dbc_migration_threshold: f64,
+    pub dbc_progress_poll_ms: u64,
 }
 
 impl Config {
@@ -34,12 +50,28 @@ impl Config {
             wallet_path: env::var("WALLET_PATH").unwrap_or_else(|_| "wallets/hot.json".into()),
             wallet_address: env::var("WALLET_ADDRESS").unwrap_or_default(),
             slippage_bps: env::var("SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(75),
+            sniper_max_slippage_bps: env::var("SNIPER_MAX_SLIPPAGE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(200),
+            sniper_max_fee_bps: env::var("SNIPER_MAX_FEE_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(0),
+            sniper_max_fee_wait_ms: env::var("SNIPER_MAX_FEE_WAIT_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(60_000),
+            max_price_impact_bps: env::var("MAX_PRICE_IMPACT_BPS").ok().and_then(|v| v.parse().ok()).unwrap_or(500),
+            quote_api_url: env::var("QUOTE_API_URL").ok().filter(|v| !v.trim().is_empty()),
             max_retries: env::var("MAX_RETRIES").ok().and_then(|v| v.parse().ok()).unwrap_or(3),
             budget_sol: env::var("BUDGET_SOL").ok().and_then(|v| v.parse().ok()).unwrap_or(2.0),
             buy_cap_sol: env::var("BUY_CAP_SOL").ok().and_then(|v| v.parse().ok()).unwrap_or(0.5),
             export_dir: env::var("EXPORT_DIR").unwrap_or_else(|_| "out".into()),
             report_prefix: env::var("REPORT_PREFIX").unwrap_or_else(|_| "trades".into()),
             log_level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".into()),
+            bagsfm_api_url: env::var("BAGSFM_API_URL").unwrap_or_else(|_| crate::bagsfm::DEFAULT_BASE_URL.into()),
+            bagsfm_api_key: env::var("BAGSFM_API_KEY").ok().filter(|v| !v.trim().is_empty()),
+            bagsfm_timeout_secs: env::var("BAGSFM_TIMEOUT_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(10),
+            bagsfm_proxy: env::var("BAGSFM_PROXY").ok().filter(|v| !v.trim().is_empty()),
+            bagsfm_rate_limit_rps: env::var("BAGSFM_RATE_LIMIT_RPS").ok().and_then(|v| v.parse().ok()).unwrap_or(5.0),
+            bagsfm_cache_ttl_secs: env::var("BAGSFM_CACHE_TTL_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(30),
+            launch_feed_state_path: env::var("LAUNCH_FEED_STATE_PATH").unwrap_or_else(|_| "state/launch_feed.json".into()),
+            launch_feed_poll_ms: env::var("LAUNCH_FEED_POLL_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(2000),
+            bundle_launch_delay_ms: env::var("BUNDLE_LAUNCH_DELAY_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(2000),
+            dbc_migration_threshold: env::var("DBC_MIGRATION_THRESHOLD").ok().and_then(|v| v.parse().ok()).unwrap_or(0.0),
+            dbc_progress_poll_ms: env::var("DBC_PROGRESS_POLL_MS").ok().and_then(|v| v.parse().ok()).unwrap_or(5000),
         })
     }
 
@@ -50,16 +82,34 @@ impl Config {
             wallet_path: &self.wallet_path,
             wallet_address: "<redacted>",
             slippage_bps: self.slippage_bps,
+            sniper_max_slippage_bps: self.sniper_max_slippage_bps,
+            sniper_max_fee_bps: self.sniper_max_fee_bps,
+            sniper_max_fee_wait_ms: self.sniper_max_fee_wait_ms,
+            max_price_impact_bps: self.max_price_impact_bps,
+            quote_api_url: self.quote_api_url.as_deref(),
             max_retries: self.max_retries,
             budget_sol: self.budget_sol,
             buy_cap_sol: self.buy_cap_sol,
             export_dir: &self.export_dir,
             report_prefix: &self.report_prefix,
             log_level: &self.log_level,
+            bagsfm_api_url: &self.bagsfm_api_url,
+            bagsfm_api_key: self.bagsfm_api_key.as_ref().map(|_| "<redacted>"),
+            bagsfm_timeout_secs: self.bagsfm_timeout_secs,
+            bagsfm_proxy: self.bagsfm_proxy.as_deref(),
+            bagsfm_rate_limit_rps: self.bagsfm_rate_limit_rps,
+            bagsfm_cache_ttl_secs: self.bagsfm_cache_ttl_secs,
+            launch_feed_state_path: &self.launch_feed_state_path,
+            launch_feed_poll_ms: self.launch_feed_poll_ms,
+            bundle_launch_delay_ms: self.bundle_launch_delay_ms,
+            dbc_migration_threshold: self.dbc_migration_threshold,
+            dbc_progress_poll_ms: self.dbc_progress_poll_ms,
         }
     }
 }
 
+/// Only ever formatted, for the startup log.
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct SafeConfig<'a> {
     pub rpc_url: &'a str,
@@ -67,10 +117,26 @@ pub struct SafeConfig<'a> {
     pub wallet_path: &'a str,
     pub wallet_address: &'a str,
     pub slippage_bps: u32,
+    pub sniper_max_slippage_bps: u32,
+    pub sniper_max_fee_bps: u32,
+    pub sniper_max_fee_wait_ms: u64,
+    pub max_price_impact_bps: u32,
+    pub quote_api_url: Option<&'a str>,
     pub max_retries: u32,
     pub budget_sol: f64,
     pub buy_cap_sol: f64,
     pub export_dir: &'a str,
     pub report_prefix: &'a str,
     pub log_level: &'a str,
+    pub bagsfm_api_url: &'a str,
+    pub bagsfm_api_key: Option<&'a str>,
+    pub bagsfm_timeout_secs: u64,
+    pub bagsfm_proxy: Option<&'a str>,
+    pub bagsfm_rate_limit_rps: f64,
+    pub bagsfm_cache_ttl_secs: u64,
+    pub launch_feed_state_path: &'a str,
+    pub launch_feed_poll_ms: u64,
+    pub bundle_launch_delay_ms: u64,
+    pub dbc_migration_threshold: f64,
+    pub dbc_progress_poll_ms: u64,
 }
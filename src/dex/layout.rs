@@ -0,0 +1,71 @@
+//! Bounds-checked little-endian readers for fixed-layout program accounts.
+
+use super::math::U256;
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+fn slice<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| anyhow!("account data too short: need {} bytes at offset {}, have {}", N, offset, data.len()))
+}
+
+pub fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    Ok(slice::<1>(data, offset)?[0])
+}
+
+pub fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes(slice(data, offset)?))
+}
+
+pub fn read_i32(data: &[u8], offset: usize) -> Result<i32> {
+    Ok(i32::from_le_bytes(slice(data, offset)?))
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes(slice(data, offset)?))
+}
+
+pub fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    Ok(u64::from_le_bytes(slice(data, offset)?))
+}
+
+pub fn read_u128(data: &[u8], offset: usize) -> Result<u128> {
+    Ok(u128::from_le_bytes(slice(data, offset)?))
+}
+
+pub fn read_i128(data: &[u8], offset: usize) -> Result<i128> {
+    Ok(i128::from_le_bytes(slice(data, offset)?))
+}
+
+/// A little-endian `u256`, stored as two `u128` words.
+pub fn read_u256(data: &[u8], offset: usize) -> Result<U256> {
+    Ok(U256::from_le_words(read_u128(data, offset)?, read_u128(data, offset + 16)?))
+}
+
+pub fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    Ok(Pubkey::new_from_array(slice(data, offset)?))
+}
+
+/// `amount` of an SPL Token (or Token-2022) account.
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    read_u64(data, 64)
+}
+
+/// `supply` of an SPL Token (or Token-2022) mint.
+pub fn mint_supply(data: &[u8]) -> Result<u64> {
+    read_u64(data, 36)
+}
+
+/// `decimals` of an SPL Token (or Token-2022) mint.
+pub fn mint_decimals(data: &[u8]) -> Result<u8> {
+    read_u8(data, 44)
+}
+
+/// Anchor's 8-byte discriminator: the first bytes of `sha256(preimage)`,
+/// e.g. `"account:Whirlpool"` or `"global:swap"`.
+pub fn anchor_discriminator(preimage: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
@@ -0,0 +1,289 @@
+//! Fixed-point helpers shared by the concentrated-liquidity venues.
+//!
+//! Pool programs compute in Q64.64 with 256-bit intermediates; `U256` covers
+//! exactly the operations those formulas need, with on-chain rounding.
+
+use anyhow::{anyhow, Context, Result};
+use std::cmp::Ordering;
+
+const LO_MASK: u128 = u64::MAX as u128;
+
+/// Whirlpool and Raydium CLMM fee rates are both in millionths.
+pub const FEE_RATE_DENOMINATOR: u128 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct U256 {
+    hi: u128,
+    lo: u128,
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hi.cmp(&other.hi).then(self.lo.cmp(&other.lo))
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(lo: u128) -> Self {
+        Self { hi: 0, lo }
+    }
+}
+
+impl U256 {
+    pub const ZERO: Self = Self { hi: 0, lo: 0 };
+
+    /// Full 256-bit product of two u128s.
+    pub fn mul(a: u128, b: u128) -> Self {
+        let (a1, a0) = (a >> 64, a & LO_MASK);
+        let (b1, b0) = (b >> 64, b & LO_MASK);
+        let p00 = a0 * b0;
+        let p01 = a0 * b1;
+        let p10 = a1 * b0;
+        let p11 = a1 * b1;
+        let mid = (p00 >> 64) + (p01 & LO_MASK) + (p10 & LO_MASK);
+        Self {
+            hi: p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64),
+            lo: (p00 & LO_MASK) | (mid << 64),
+        }
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.hi == 0 && self.lo == 0
+    }
+
+    pub fn to_u128(self) -> Option<u128> {
+        (self.hi == 0).then_some(self.lo)
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (lo, carry) = self.lo.overflowing_add(other.lo);
+        let hi = self.hi.checked_add(other.hi)?.checked_add(carry as u128)?;
+        Some(Self { hi, lo })
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (lo, borrow) = self.lo.overflowing_sub(other.lo);
+        let hi = self.hi.checked_sub(other.hi)?.checked_sub(borrow as u128)?;
+        Some(Self { hi, lo })
+    }
+
+    /// Left shift that fails instead of dropping set bits.
+    pub fn checked_shl(self, bits: u32) -> Option<Self> {
+        let shifted = self.shl(bits);
+        (shifted.shr(bits) == self).then_some(shifted)
+    }
+
+    fn shl(self, bits: u32) -> Self {
+        match bits {
+            0 => self,
+            1..=127 => Self { hi: (self.hi << bits) | (self.lo >> (128 - bits)), lo: self.lo << bits },
+            128..=255 => Self { hi: self.lo << (bits - 128), lo: 0 },
+            _ => Self::ZERO,
+        }
+    }
+
+    pub fn shr(self, bits: u32) -> Self {
+        match bits {
+            0 => self,
+            1..=127 => Self { hi: self.hi >> bits, lo: (self.lo >> bits) | (self.hi << (128 - bits)) },
+            128..=255 => Self { hi: 0, lo: self.hi >> (bits - 128) },
+            _ => Self::ZERO,
+        }
+    }
+
+    /// Low 128 bits, discarding the rest.
+    pub fn low_u128(self) -> u128 {
+        self.lo
+    }
+
+    pub fn high_u128(self) -> u128 {
+        self.hi
+    }
+
+    /// From the little-endian 32-byte layout programs store `u256` in.
+    pub fn from_le_words(lo: u128, hi: u128) -> Self {
+        Self { hi, lo }
+    }
+
+    /// Quotient and remainder; `None` on division by zero.
+    pub fn div_rem(self, divisor: Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        if let (Some(n), Some(d)) = (self.to_u128(), divisor.to_u128()) {
+            return Some(((n / d).into(), (n % d).into()));
+        }
+        let mut quotient = Self::ZERO;
+        let mut remainder = Self::ZERO;
+        for bit in (0..256).rev() {
+            let carry = remainder.hi >> 127 == 1;
+            remainder = remainder.shl(1);
+            remainder.lo |= self.shr(bit).lo & 1;
+            if carry || remainder >= divisor {
+                remainder = remainder.wrapping_sub(divisor);
+                quotient = quotient.set_bit(bit);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    pub fn div_round_up(self, divisor: Self) -> Option<Self> {
+        let (quotient, remainder) = self.div_rem(divisor)?;
+        if remainder.is_zero() {
+            Some(quotient)
+        } else {
+            quotient.checked_add(1u128.into())
+        }
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        let (lo, borrow) = self.lo.overflowing_sub(other.lo);
+        Self { hi: self.hi.wrapping_sub(other.hi).wrapping_sub(borrow as u128), lo }
+    }
+
+    fn set_bit(mut self, bit: u32) -> Self {
+        if bit >= 128 {
+            self.hi |= 1 << (bit - 128);
+        } else {
+            self.lo |= 1 << bit;
+        }
+        self
+    }
+}
+
+/// `a * b / denominator`, rounding up when asked. `None` on overflow or zero denominator.
+pub fn mul_div(a: u128, b: u128, denominator: u128, round_up: bool) -> Option<u128> {
+    let product = U256::mul(a, b);
+    let denominator = U256::from(denominator);
+    let quotient = if round_up { product.div_round_up(denominator)? } else { product.div_rem(denominator)?.0 };
+    quotient.to_u128()
+}
+
+/// Token A owed between two prices: `L * (upper - lower) / (upper * lower)`.
+pub fn amount_delta_a(price_0: u128, price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (lower, upper) = (price_0.min(price_1), price_0.max(price_1));
+    let numerator = U256::mul(liquidity, upper - lower).checked_shl(64)?;
+    let denominator = U256::mul(upper, lower);
+    let quotient = if round_up { numerator.div_round_up(denominator)? } else { numerator.div_rem(denominator)?.0 };
+    quotient.to_u128()?.try_into().ok()
+}
+
+/// Token B owed between two prices: `L * (upper - lower)`.
+pub fn amount_delta_b(price_0: u128, price_1: u128, liquidity: u128, round_up: bool) -> Option<u64> {
+    let (lower, upper) = (price_0.min(price_1), price_0.max(price_1));
+    let product = U256::mul(liquidity, upper - lower);
+    let mut result = product.shr(64).to_u128()?;
+    if round_up && product.low_u128() as u64 != 0 {
+        result += 1;
+    }
+    result.try_into().ok()
+}
+
+pub fn next_sqrt_price_from_a_round_up(price: u128, liquidity: u128, amount: u64, is_input: bool) -> Result<u128> {
+    if amount == 0 {
+        return Ok(price);
+    }
+    let overflow = || anyhow!("sqrt price overflow");
+    let product = U256::mul(price, amount as u128);
+    let numerator = U256::mul(liquidity, price).checked_shl(64).ok_or_else(overflow)?;
+    let liquidity_x64 = U256::from(liquidity).checked_shl(64).ok_or_else(overflow)?;
+    let denominator = if is_input { liquidity_x64.checked_add(product) } else { liquidity_x64.checked_sub(product) }
+        .ok_or_else(overflow)?;
+    numerator.div_round_up(denominator).and_then(U256::to_u128).ok_or_else(overflow)
+}
+
+pub fn next_sqrt_price_from_b_round_down(price: u128, liquidity: u128, amount: u64, is_input: bool) -> Result<u128> {
+    let amount_x64 = (amount as u128) << 64;
+    let delta = if is_input { amount_x64 / liquidity } else { amount_x64.div_ceil(liquidity) };
+    if is_input { price.checked_add(delta) } else { price.checked_sub(delta) }
+        .ok_or_else(|| anyhow!("sqrt price overflow"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStep {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub next_sqrt_price: u128,
+    pub fee_amount: u64,
+}
+
+/// One step of a concentrated-liquidity swap towards `target_sqrt_price`,
+/// as both the Whirlpool and Raydium CLMM programs compute it.
+pub fn compute_swap_step(
+    amount_remaining: u64,
+    fee_rate: u32,
+    liquidity: u128,
+    sqrt_price: u128,
+    target_sqrt_price: u128,
+    is_input: bool,
+    a_to_b: bool,
+) -> Result<SwapStep> {
+    // Which side of the trade is pinned by the caller and which one follows from the price move.
+    let fixed_delta = |from: u128, to: u128| {
+        if a_to_b == is_input { amount_delta_a(from, to, liquidity, is_input) } else { amount_delta_b(from, to, liquidity, is_input) }
+    };
+    let unfixed_delta = |from: u128, to: u128| {
+        if a_to_b == is_input { amount_delta_b(from, to, liquidity, !is_input) } else { amount_delta_a(from, to, liquidity, !is_input) }
+    };
+
+    let amount_calc = if is_input {
+        mul_div(amount_remaining as u128, FEE_RATE_DENOMINATOR - fee_rate as u128, FEE_RATE_DENOMINATOR, false)
+            .context("fee overflow")? as u64
+    } else {
+        amount_remaining
+    };
+
+    let initial_fixed = fixed_delta(sqrt_price, target_sqrt_price);
+    let next_sqrt_price = match initial_fixed {
+        Some(delta) if delta <= amount_calc => target_sqrt_price,
+        _ if liquidity == 0 => target_sqrt_price,
+        _ if a_to_b == is_input => next_sqrt_price_from_a_round_up(sqrt_price, liquidity, amount_calc, is_input)?,
+        _ => next_sqrt_price_from_b_round_down(sqrt_price, liquidity, amount_calc, is_input)?,
+    };
+    let is_max_swap = next_sqrt_price == target_sqrt_price;
+
+    let unfixed = unfixed_delta(sqrt_price, next_sqrt_price).context("swap amount overflow")?;
+    let fixed = match initial_fixed {
+        Some(delta) if is_max_swap => delta,
+        _ => fixed_delta(sqrt_price, next_sqrt_price).context("swap amount overflow")?,
+    };
+
+    let (amount_in, mut amount_out) = if is_input { (fixed, unfixed) } else { (unfixed, fixed) };
+    if !is_input && amount_out > amount_remaining {
+        amount_out = amount_remaining;
+    }
+
+    let fee_amount = if is_input && !is_max_swap {
+        amount_remaining - amount_in
+    } else {
+        mul_div(amount_in as u128, fee_rate as u128, FEE_RATE_DENOMINATOR - fee_rate as u128, true)
+            .context("fee overflow")? as u64
+    };
+
+    Ok(SwapStep { amount_in, amount_out, next_sqrt_price, fee_amount })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_multiply_and_divide_round_trip() {
+        let a = u128::MAX - 12345;
+        let b = (1u128 << 100) + 7;
+        let product = U256::mul(a, b);
+        let (quotient, remainder) = product.div_rem(b.into()).unwrap();
+        assert_eq!(quotient.to_u128(), Some(a));
+        assert!(remainder.is_zero());
+        assert_eq!(mul_div(a, b, b, false), Some(a));
+        assert_eq!(mul_div(10, 10, 3, false), Some(33));
+        assert_eq!(mul_div(10, 10, 3, true), Some(34));
+        assert_eq!(mul_div(u128::MAX, 2, 1, false), None);
+    }
+}
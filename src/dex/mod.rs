@@ -0,0 +1,952 @@
+pub mod layout;
+pub mod math;
+pub mod orca;
+pub mod raydium;
+pub mod raydium_clmm;
+pub mod raydium_cpmm;
+pub mod router;
+
+use crate::meteora::{self, damm_v2, dbc};
+use crate::trade;
+use anyhow::{anyhow, bail, Context, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    instruction::Instruction,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_associated_token_account::{get_associated_token_address, get_associated_token_address_with_program_id};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Most accounts one `getMultipleAccounts` request may ask for.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct DexPool {
+    pub address: String,
+    pub token_a: String,
+    pub token_b: String,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    /// Trading fee as the program stores it: `fee_numerator / fee_denominator`.
+    pub fee_numerator: u64,
+    pub fee_denominator: u64,
+    pub dex_type: DexType,
+}
+
+impl DexPool {
+    /// The pool's reserve of `mint`, if it trades it.
+    pub fn reserve_of(&self, mint: &str) -> Option<u64> {
+        if self.token_a == mint {
+            Some(self.reserve_a)
+        } else if self.token_b == mint {
+            Some(self.reserve_b)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DexType {
+    Raydium,
+    RaydiumCpmm,
+    RaydiumClmm,
+    Orca,
+    MeteoraDbc,
+    MeteoraDammV2,
+}
+
+/// A balanced deposit or a withdrawal, priced against the pool's real LP supply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityQuote {
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+    /// LP tokens (Raydium) or position liquidity (DAMM v2) minted or burned.
+    pub lp_amount: u128,
+    pub lp_supply: u128,
+}
+
+pub struct DexClient {
+    rpc_client: RpcClient,
+    raydium_program_id: Pubkey,
+    raydium_cpmm_program_id: Pubkey,
+    raydium_clmm_program_id: Pubkey,
+    orca_program_id: Pubkey,
+}
+
+impl DexClient {
+    pub fn new(rpc_url: &str) -> Result<Self> {
+        Ok(Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            raydium_program_id: raydium::AMM_V4_PROGRAM_ID,
+            raydium_cpmm_program_id: raydium_cpmm::CPMM_PROGRAM_ID,
+            raydium_clmm_program_id: raydium_clmm::CLMM_PROGRAM_ID,
+            orca_program_id: orca::WHIRLPOOL_PROGRAM_ID,
+        })
+    }
+
+    /// Fetches the pool account, decodes it according to its owning program
+    /// and reads reserves from the pool's token vaults.
+    pub async fn get_pool_info(&self, pool_address: &str) -> Result<DexPool> {
+        let address = Pubkey::from_str(pool_address).context("invalid pool address")?;
+        let account = self.fetch_account(&address)?;
+        self.decode_pool(&address, &account)
+    }
+
+    /// Every pool trading `mint` on Raydium AMM v4, CPMM and CLMM, Orca
+    /// Whirlpools, Meteora DBC and Meteora DAMM v2, deepest first. Depth is the pool's reserve of
+    /// `mint` itself, so pools quoted against different assets rank on the
+    /// same scale.
+    pub async fn find_pools(&self, mint: &str) -> Result<Vec<DexPool>> {
+        let mint_key = Pubkey::from_str(mint).context("invalid mint")?;
+        let by_mint = |offset: usize| RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, mint_key.as_ref()));
+        let size = RpcFilterType::DataSize;
+        let discriminator = |bytes: [u8; 8]| RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &bytes));
+
+        let queries = [
+            (self.raydium_program_id, vec![size(752), by_mint(400)]),
+            (self.raydium_program_id, vec![size(752), by_mint(432)]),
+            (self.raydium_cpmm_program_id, vec![discriminator(raydium_cpmm::PoolState::discriminator()), by_mint(raydium_cpmm::POOL_TOKEN_0_MINT_OFFSET)]),
+            (self.raydium_cpmm_program_id, vec![discriminator(raydium_cpmm::PoolState::discriminator()), by_mint(raydium_cpmm::POOL_TOKEN_1_MINT_OFFSET)]),
+            (self.raydium_clmm_program_id, vec![size(raydium_clmm::POOL_STATE_LEN), by_mint(raydium_clmm::POOL_TOKEN_MINT_0_OFFSET)]),
+            (self.raydium_clmm_program_id, vec![size(raydium_clmm::POOL_STATE_LEN), by_mint(raydium_clmm::POOL_TOKEN_MINT_1_OFFSET)]),
+            (self.orca_program_id, vec![size(653), by_mint(101)]),
+            (self.orca_program_id, vec![size(653), by_mint(181)]),
+            (dbc::DBC_PROGRAM_ID, vec![discriminator(dbc::VirtualPool::discriminator()), by_mint(dbc::VIRTUAL_POOL_BASE_MINT_OFFSET)]),
+            (damm_v2::DAMM_V2_PROGRAM_ID, vec![discriminator(damm_v2::Pool::discriminator()), by_mint(damm_v2::POOL_TOKEN_A_MINT_OFFSET)]),
+            (damm_v2::DAMM_V2_PROGRAM_ID, vec![discriminator(damm_v2::Pool::discriminator()), by_mint(damm_v2::POOL_TOKEN_B_MINT_OFFSET)]),
+        ];
+
+        let mut candidates = Vec::new();
+        for (program_id, filters) in queries {
+            for (address, account) in get_program_accounts(&self.rpc_client, &program_id, filters)? {
+                match self.pool_dependencies(&address, &account) {
+                    Ok(dependencies) => candidates.push((address, account, dependencies)),
+                    Err(e) => log::warn!("Skipping pool {}: {:#}", address, e),
+                }
+            }
+        }
+
+        // Every pool's vaults and configs in as few requests as the RPC allows,
+        // rather than a round-trip per pool.
+        let mut keys: Vec<Pubkey> = candidates.iter().flat_map(|(_, _, dependencies)| dependencies.iter().copied()).collect();
+        keys.sort();
+        keys.dedup();
+        let mut fetched = HashMap::with_capacity(keys.len());
+        for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            for (key, account) in chunk.iter().zip(self.rpc_client.get_multiple_accounts(chunk)?) {
+                if let Some(account) = account {
+                    fetched.insert(*key, account);
+                }
+            }
+        }
+
+        let mut pools = Vec::new();
+        for (address, account, dependencies) in candidates {
+            let decoded = dependencies.iter()
+                .map(|key| fetched.get(key).cloned().ok_or_else(|| anyhow!("account {} not found", key)))
+                .collect::<Result<Vec<_>>>()
+                .and_then(|accounts| self.decode_pool_with(&address, &account, &accounts));
+            match decoded {
+                Ok(pool) => pools.push(pool),
+                Err(e) => log::warn!("Skipping pool {}: {:#}", address, e),
+            }
+        }
+        pools.sort_by_key(|pool| std::cmp::Reverse(pool.reserve_of(mint).unwrap_or(0)));
+        Ok(pools)
+    }
+
+    fn decode_pool(&self, address: &Pubkey, account: &Account) -> Result<DexPool> {
+        let dependencies = self.pool_dependencies(address, account)?;
+        let accounts = self.rpc_client.get_multiple_accounts(&dependencies)?;
+        let accounts = accounts.into_iter()
+            .zip(&dependencies)
+            .map(|(account, address)| account.ok_or_else(|| anyhow!("account {} not found", address)))
+            .collect::<Result<Vec<_>>>()?;
+        self.decode_pool_with(address, account, &accounts)
+    }
+
+    /// The accounts besides the pool itself that [`Self::decode_pool_with`]
+    /// reads, in the order it expects them: vaults, then open orders or config.
+    fn pool_dependencies(&self, address: &Pubkey, account: &Account) -> Result<Vec<Pubkey>> {
+        Ok(if account.owner == self.raydium_program_id {
+            let info = raydium::AmmInfo::decode(&account.data)?;
+            vec![info.coin_vault, info.pc_vault, info.open_orders]
+        } else if account.owner == self.raydium_cpmm_program_id {
+            let state = raydium_cpmm::PoolState::decode(&account.data)?;
+            vec![state.token_0_vault, state.token_1_vault, state.amm_config]
+        } else if account.owner == self.raydium_clmm_program_id {
+            let state = raydium_clmm::PoolState::decode(&account.data)?;
+            vec![state.token_vault_0, state.token_vault_1, state.amm_config]
+        } else if account.owner == self.orca_program_id {
+            let whirlpool = orca::Whirlpool::decode(&account.data)?;
+            vec![whirlpool.token_vault_a, whirlpool.token_vault_b]
+        } else if account.owner == dbc::DBC_PROGRAM_ID {
+            vec![dbc::VirtualPool::decode(&account.data)?.config]
+        } else if account.owner == damm_v2::DAMM_V2_PROGRAM_ID {
+            let pool = damm_v2::Pool::decode(&account.data)?;
+            vec![pool.token_a_vault, pool.token_b_vault]
+        } else {
+            bail!("{} is owned by {}, which is not a supported DEX program", address, account.owner)
+        })
+    }
+
+    /// Decodes a pool from its account and its [`Self::pool_dependencies`].
+    fn decode_pool_with(&self, address: &Pubkey, account: &Account, dependencies: &[Account]) -> Result<DexPool> {
+        if account.owner == self.raydium_program_id {
+            let info = raydium::AmmInfo::decode(&account.data)?;
+            let [coin_vault, pc_vault, open_orders] = expect_accounts(dependencies)?;
+            let (reserve_a, reserve_b) = info.reserves(
+                layout::token_account_amount(&coin_vault.data)?,
+                layout::token_account_amount(&pc_vault.data)?,
+                &raydium::OpenOrders::decode(&open_orders.data)?,
+            );
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: info.coin_mint.to_string(),
+                token_b: info.pc_mint.to_string(),
+                reserve_a,
+                reserve_b,
+                fee_numerator: info.swap_fee_numerator,
+                fee_denominator: info.swap_fee_denominator,
+                dex_type: DexType::Raydium,
+            })
+        } else if account.owner == self.raydium_cpmm_program_id {
+            let state = raydium_cpmm::PoolState::decode(&account.data)?;
+            let [vault_0, vault_1, config] = expect_accounts(dependencies)?;
+            let (reserve_a, reserve_b) = state.reserves(
+                layout::token_account_amount(&vault_0.data)?,
+                layout::token_account_amount(&vault_1.data)?,
+            );
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: state.token_0_mint.to_string(),
+                token_b: state.token_1_mint.to_string(),
+                reserve_a,
+                reserve_b,
+                fee_numerator: raydium_cpmm::AmmConfig::decode(&config.data)?.trade_fee_rate,
+                fee_denominator: raydium_cpmm::FEE_RATE_DENOMINATOR,
+                dex_type: DexType::RaydiumCpmm,
+            })
+        } else if account.owner == self.raydium_clmm_program_id {
+            let state = raydium_clmm::PoolState::decode(&account.data)?;
+            let [vault_0, vault_1, config] = expect_accounts(dependencies)?;
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: state.token_mint_0.to_string(),
+                token_b: state.token_mint_1.to_string(),
+                reserve_a: layout::token_account_amount(&vault_0.data)?,
+                reserve_b: layout::token_account_amount(&vault_1.data)?,
+                fee_numerator: raydium_clmm::AmmConfig::decode(&config.data)?.trade_fee_rate as u64,
+                fee_denominator: math::FEE_RATE_DENOMINATOR as u64,
+                dex_type: DexType::RaydiumClmm,
+            })
+        } else if account.owner == self.orca_program_id {
+            let whirlpool = orca::Whirlpool::decode(&account.data)?;
+            let [vault_a, vault_b] = expect_accounts(dependencies)?;
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: whirlpool.token_mint_a.to_string(),
+                token_b: whirlpool.token_mint_b.to_string(),
+                reserve_a: layout::token_account_amount(&vault_a.data)?,
+                reserve_b: layout::token_account_amount(&vault_b.data)?,
+                fee_numerator: whirlpool.fee_rate as u64,
+                fee_denominator: orca::FEE_RATE_DENOMINATOR as u64,
+                dex_type: DexType::Orca,
+            })
+        } else if account.owner == dbc::DBC_PROGRAM_ID {
+            let pool = dbc::VirtualPool::decode(&account.data)?;
+            let [config] = expect_accounts(dependencies)?;
+            let config = dbc::PoolConfig::decode(&config.data)?;
+            // Curve reserves are tracked in the pool; the vaults also hold unclaimed fees.
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: pool.base_mint.to_string(),
+                token_b: config.quote_mint.to_string(),
+                reserve_a: pool.base_reserve,
+                reserve_b: pool.quote_reserve,
+                // The launch fee; quotes apply the decayed and dynamic fee.
+                fee_numerator: config.base_fee.cliff_fee_numerator,
+                fee_denominator: dbc::FEE_DENOMINATOR,
+                dex_type: DexType::MeteoraDbc,
+            })
+        } else if account.owner == damm_v2::DAMM_V2_PROGRAM_ID {
+            let pool = damm_v2::Pool::decode(&account.data)?;
+            let [vault_a, vault_b] = expect_accounts(dependencies)?;
+            Ok(DexPool {
+                address: address.to_string(),
+                token_a: pool.token_a_mint.to_string(),
+                token_b: pool.token_b_mint.to_string(),
+                reserve_a: layout::token_account_amount(&vault_a.data)?,
+                reserve_b: layout::token_account_amount(&vault_b.data)?,
+                fee_numerator: pool.fees.base.cliff_fee_numerator,
+                fee_denominator: damm_v2::FEE_DENOMINATOR,
+                dex_type: DexType::MeteoraDammV2,
+            })
+        } else {
+            bail!("{} is owned by {}, which is not a supported DEX program", address, account.owner)
+        }
+    }
+
+    fn fetch_account(&self, address: &Pubkey) -> Result<Account> {
+        self.rpc_client.get_account(address)
+            .with_context(|| format!("failed to fetch account {}", address))
+    }
+
+    /// Fetches several accounts in one request, failing if any is missing.
+    fn fetch_accounts<const N: usize>(&self, addresses: [Pubkey; N]) -> Result<[Account; N]> {
+        let accounts = self.rpc_client.get_multiple_accounts(&addresses)?;
+        let accounts = accounts.into_iter()
+            .zip(addresses)
+            .map(|(account, address)| account.ok_or_else(|| anyhow!("account {} not found", address)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(accounts.try_into().expect("one account per address"))
+    }
+
+    pub async fn calculate_swap_amounts(
+        &self,
+        pool: &DexPool,
+        input_amount: u64,
+        is_input_a: bool,
+    ) -> Result<(u64, u64)> {
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (reserve_in, reserve_out) = if is_input_a {
+                    (pool.reserve_a, pool.reserve_b)
+                } else {
+                    (pool.reserve_b, pool.reserve_a)
+                };
+                raydium::quote_base_in(input_amount, reserve_in, reserve_out, pool.fee_numerator, pool.fee_denominator)
+                    .ok_or_else(|| anyhow!("Raydium quote overflow for pool {}", pool.address))
+            }
+            DexType::RaydiumCpmm => {
+                // Reserves are re-read so the quote sees the vaults as of now.
+                let (_, state) = self.fetch_raydium_cpmm_pool(pool)?;
+                let [vault_0, vault_1, config] = self.fetch_accounts([state.token_0_vault, state.token_1_vault, state.amm_config])?;
+                let (reserve_0, reserve_1) = state.reserves(
+                    layout::token_account_amount(&vault_0.data)?,
+                    layout::token_account_amount(&vault_1.data)?,
+                );
+                let (reserve_in, reserve_out) = if is_input_a { (reserve_0, reserve_1) } else { (reserve_1, reserve_0) };
+                let fee_rate = raydium_cpmm::AmmConfig::decode(&config.data)?.trade_fee_rate;
+                raydium_cpmm::quote_base_input(input_amount, reserve_in, reserve_out, fee_rate)
+                    .ok_or_else(|| anyhow!("Raydium CPMM quote overflow for pool {}", pool.address))
+            }
+            DexType::RaydiumClmm => self.raydium_clmm_quote(pool, input_amount, is_input_a),
+            DexType::Orca => self.orca_quote(pool, input_amount, is_input_a),
+            DexType::MeteoraDammV2 => {
+                let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+                let state = damm_v2::Pool::decode(&self.fetch_account(&address)?.data)?;
+                let now = meteora::fee_clock(&self.rpc_client)?;
+                state.quote_exact_in(input_amount, is_input_a, now)
+            }
+            DexType::MeteoraDbc => {
+                // token_a is the launched token, so a-to-b sells into the curve.
+                let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+                let state = dbc::VirtualPool::decode(&self.fetch_account(&address)?.data)?;
+                let [config] = self.fetch_accounts([state.config])?;
+                let config = dbc::PoolConfig::decode(&config.data)?;
+                let now = meteora::fee_clock(&self.rpc_client)?;
+                let quote = state.quote_exact_in(&config, input_amount, is_input_a, now)?;
+                Ok((quote.amount_out, quote.fee))
+            }
+        }
+    }
+
+    pub async fn create_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        match pool.dex_type {
+            DexType::Raydium => self.create_raydium_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+            DexType::RaydiumCpmm => self.create_raydium_cpmm_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+            DexType::RaydiumClmm => self.create_raydium_clmm_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+            DexType::Orca => self.create_orca_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+            DexType::MeteoraDbc => self.create_meteora_dbc_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+            DexType::MeteoraDammV2 => self.create_meteora_damm_v2_swap_instruction(
+                pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount
+            ).await,
+        }
+    }
+
+    async fn create_raydium_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let (keys, source, destination) = self.raydium_swap_accounts(pool, user_wallet, input_mint, output_mint)?;
+        Ok(raydium::swap_base_in(&keys, source, destination, *user_wallet, input_amount, min_output_amount))
+    }
+
+    /// Decodes the pool and its market and resolves the user's source and
+    /// destination token accounts (ATAs) for the swap direction.
+    fn raydium_swap_accounts(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+    ) -> Result<(raydium::SwapKeys, Pubkey, Pubkey)> {
+        let (keys, info) = self.raydium_pool_keys(pool)?;
+        let amm = keys.amm;
+
+        let input_mint = Pubkey::from_str(input_mint).context("invalid input mint")?;
+        let output_mint = Pubkey::from_str(output_mint).context("invalid output mint")?;
+        if !(input_mint == info.coin_mint && output_mint == info.pc_mint
+            || input_mint == info.pc_mint && output_mint == info.coin_mint)
+        {
+            bail!("pool {} trades {}/{}, not {}/{}", amm, info.coin_mint, info.pc_mint, input_mint, output_mint);
+        }
+
+        Ok((
+            keys,
+            get_associated_token_address(user_wallet, &input_mint),
+            get_associated_token_address(user_wallet, &output_mint),
+        ))
+    }
+
+    async fn create_raydium_cpmm_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let (address, state) = self.fetch_raydium_cpmm_pool(pool)?;
+        let zero_for_one = swap_direction(&address, (state.token_0_mint, state.token_1_mint), input_mint, output_mint)?;
+        let (input, output) = if zero_for_one {
+            ((state.token_0_mint, state.token_0_program), (state.token_1_mint, state.token_1_program))
+        } else {
+            ((state.token_1_mint, state.token_1_program), (state.token_0_mint, state.token_0_program))
+        };
+        let params = raydium_cpmm::SwapParams { amount_in: input_amount, minimum_amount_out: min_output_amount, zero_for_one };
+        Ok(raydium_cpmm::swap_base_input(
+            address,
+            &state,
+            *user_wallet,
+            get_associated_token_address_with_program_id(user_wallet, &input.0, &input.1),
+            get_associated_token_address_with_program_id(user_wallet, &output.0, &output.1),
+            &params,
+        ))
+    }
+
+    /// The program owning `mint`, which is also the program its ATAs live under.
+    pub async fn token_program(&self, mint: &str) -> Result<Pubkey> {
+        let mint = Pubkey::from_str(mint).context("invalid mint")?;
+        Ok(self.fetch_account(&mint)?.owner)
+    }
+
+    async fn create_raydium_clmm_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let (address, state) = self.fetch_raydium_clmm_pool(pool)?;
+        let zero_for_one = swap_direction(&address, (state.token_mint_0, state.token_mint_1), input_mint, output_mint)?;
+        let (input, output) = if zero_for_one {
+            (state.token_mint_0, state.token_mint_1)
+        } else {
+            (state.token_mint_1, state.token_mint_0)
+        };
+        // CLMM pools take Token-2022 mints; the owner program picks each ATA.
+        let [input_mint_account, output_mint_account] = self.fetch_accounts([input, output])?;
+        let params = raydium_clmm::SwapParams { amount_in: input_amount, minimum_amount_out: min_output_amount, zero_for_one };
+        Ok(raydium_clmm::swap_v2(
+            address,
+            &state,
+            *user_wallet,
+            get_associated_token_address_with_program_id(user_wallet, &input, &input_mint_account.owner),
+            get_associated_token_address_with_program_id(user_wallet, &output, &output_mint_account.owner),
+            &state.swap_tick_array_starts(zero_for_one, RAYDIUM_CLMM_TICK_ARRAYS),
+            &params,
+        ))
+    }
+
+    fn fetch_raydium_cpmm_pool(&self, pool: &DexPool) -> Result<(Pubkey, raydium_cpmm::PoolState)> {
+        let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        let account = self.fetch_account(&address)?;
+        if account.owner != self.raydium_cpmm_program_id {
+            bail!("{} is not owned by the Raydium CPMM program", address);
+        }
+        Ok((address, raydium_cpmm::PoolState::decode(&account.data)?))
+    }
+
+    fn fetch_raydium_clmm_pool(&self, pool: &DexPool) -> Result<(Pubkey, raydium_clmm::PoolState)> {
+        let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        let account = self.fetch_account(&address)?;
+        if account.owner != self.raydium_clmm_program_id {
+            bail!("{} is not owned by the Raydium CLMM program", address);
+        }
+        Ok((address, raydium_clmm::PoolState::decode(&account.data)?))
+    }
+
+    /// Exact-input CLMM quote over the tick arrays the swap instruction
+    /// passes. `is_input_a` means selling the pool's token 0.
+    fn raydium_clmm_quote(&self, pool: &DexPool, input_amount: u64, is_input_a: bool) -> Result<(u64, u64)> {
+        let (address, state) = self.fetch_raydium_clmm_pool(pool)?;
+        let [config] = self.fetch_accounts([state.amm_config])?;
+        let fee_rate = raydium_clmm::AmmConfig::decode(&config.data)?.trade_fee_rate;
+        let addresses: Vec<Pubkey> = state.swap_tick_array_starts(is_input_a, RAYDIUM_CLMM_TICK_ARRAYS)
+            .iter()
+            .map(|&start| raydium_clmm::tick_array_address(&address, start))
+            .collect();
+        let tick_arrays = self.rpc_client.get_multiple_accounts(&addresses)?
+            .into_iter()
+            .map_while(|account| account.map(|account| raydium_clmm::TickArray::decode(&account.data)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let quote = raydium_clmm::quote(&state, fee_rate, &tick_arrays, input_amount, is_input_a)?;
+        if quote.amount_in < input_amount {
+            bail!("CLMM pool {} cannot fill {} within its loaded tick arrays", pool.address, input_amount);
+        }
+        Ok((quote.amount_out, quote.fee_amount))
+    }
+
+    async fn create_orca_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let (address, whirlpool) = self.fetch_whirlpool(pool)?;
+        let input_mint = Pubkey::from_str(input_mint).context("invalid input mint")?;
+        let output_mint = Pubkey::from_str(output_mint).context("invalid output mint")?;
+        let a_to_b = if (input_mint, output_mint) == (whirlpool.token_mint_a, whirlpool.token_mint_b) {
+            true
+        } else if (input_mint, output_mint) == (whirlpool.token_mint_b, whirlpool.token_mint_a) {
+            false
+        } else {
+            bail!("whirlpool {} trades {}/{}, not {}/{}", address, whirlpool.token_mint_a, whirlpool.token_mint_b, input_mint, output_mint);
+        };
+
+        let params = orca::SwapParams {
+            amount: input_amount,
+            other_amount_threshold: min_output_amount,
+            sqrt_price_limit: if a_to_b { orca::MIN_SQRT_PRICE } else { orca::MAX_SQRT_PRICE },
+            amount_specified_is_input: true,
+            a_to_b,
+        };
+        Ok(orca::swap(
+            address,
+            &whirlpool,
+            *user_wallet,
+            get_associated_token_address(user_wallet, &whirlpool.token_mint_a),
+            get_associated_token_address(user_wallet, &whirlpool.token_mint_b),
+            &params,
+        ))
+    }
+
+    async fn create_meteora_dbc_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        let state = dbc::VirtualPool::decode(&self.fetch_account(&address)?.data)?;
+        let [config] = self.fetch_accounts([state.config])?;
+        let config = dbc::PoolConfig::decode(&config.data)?;
+        let base_for_quote = swap_direction(&address, (state.base_mint, config.quote_mint), input_mint, output_mint)?;
+        let base_account = get_associated_token_address_with_program_id(user_wallet, &state.base_mint, &state.base_token_program());
+        let quote_account = get_associated_token_address_with_program_id(user_wallet, &config.quote_mint, &config.quote_token_program());
+        let (input_account, output_account) = if base_for_quote { (base_account, quote_account) } else { (quote_account, base_account) };
+        let params = dbc::SwapParams { amount_in: input_amount, minimum_amount_out: min_output_amount, base_for_quote };
+        Ok(dbc::swap(address, &state, &config, *user_wallet, input_account, output_account, &params))
+    }
+
+    async fn create_meteora_damm_v2_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let (address, state) = self.fetch_damm_v2_pool(pool)?;
+        let a_to_b = swap_direction(&address, (state.token_a_mint, state.token_b_mint), input_mint, output_mint)?;
+        let account_a = get_associated_token_address_with_program_id(user_wallet, &state.token_a_mint, &state.token_a_program());
+        let account_b = get_associated_token_address_with_program_id(user_wallet, &state.token_b_mint, &state.token_b_program());
+        let (input_account, output_account) = if a_to_b { (account_a, account_b) } else { (account_b, account_a) };
+        let params = damm_v2::SwapParams { amount_in: input_amount, minimum_amount_out: min_output_amount };
+        Ok(damm_v2::swap(address, &state, *user_wallet, input_account, output_account, &params))
+    }
+
+    fn fetch_whirlpool(&self, pool: &DexPool) -> Result<(Pubkey, orca::Whirlpool)> {
+        let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        let account = self.fetch_account(&address)?;
+        if account.owner != self.orca_program_id {
+            bail!("{} is not owned by the Orca Whirlpool program", address);
+        }
+        Ok((address, orca::Whirlpool::decode(&account.data)?))
+    }
+
+    /// Exact-input Whirlpool quote over the three tick arrays the swap would
+    /// load. `is_input_a` means selling the pool's mint A.
+    fn orca_quote(&self, pool: &DexPool, input_amount: u64, is_input_a: bool) -> Result<(u64, u64)> {
+        let (address, whirlpool) = self.fetch_whirlpool(pool)?;
+        let addresses: Vec<Pubkey> = orca::swap_tick_array_starts(&whirlpool, is_input_a)
+            .iter()
+            .map(|&start| orca::tick_array_address(&address, start))
+            .collect();
+        // Arrays past the initialized range may not exist; the quote stops where they end.
+        let tick_arrays = self.rpc_client.get_multiple_accounts(&addresses)?
+            .into_iter()
+            .map_while(|account| account.map(|account| orca::TickArray::decode(&account.data)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let quote = orca::quote(&whirlpool, &tick_arrays, input_amount, true, is_input_a)?;
+        Ok((quote.amount_out, quote.fee_amount))
+    }
+
+    /// The largest deposit within `max_a` / `max_b` at the pool's current
+    /// ratio, sized against its real LP supply: the LP mint's supply on
+    /// Raydium, the pool's total liquidity on DAMM v2.
+    pub async fn add_liquidity(&self, pool: &DexPool, max_a: u64, max_b: u64) -> Result<LiquidityQuote> {
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (_, info) = self.raydium_pool_keys(pool)?;
+                let (reserve_a, reserve_b) = self.raydium_reserves(&info)?;
+                let supply = self.mint_supply(&info.lp_mint)?;
+                let (a, b, lp) = raydium::deposit_amounts(max_a, max_b, reserve_a, reserve_b, supply)
+                    .ok_or_else(|| anyhow!("pool {} has no liquidity to match", pool.address))?;
+                Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount: lp as u128, lp_supply: supply as u128 })
+            }
+            DexType::MeteoraDammV2 => {
+                let (_, state) = self.fetch_damm_v2_pool(pool)?;
+                let liquidity = state.liquidity_for_amounts(max_a, max_b)?;
+                let (a, b) = state.amounts_for_liquidity(liquidity, true)?;
+                Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount: liquidity, lp_supply: state.liquidity })
+            }
+            DexType::RaydiumCpmm | DexType::RaydiumClmm | DexType::Orca | DexType::MeteoraDbc => bail!("{:?} pool {} does not take LP deposits", pool.dex_type, pool.address),
+        }
+    }
+
+    /// What burning `lp_amount` returns, rounded down as the program does.
+    pub async fn remove_liquidity(&self, pool: &DexPool, lp_amount: u128) -> Result<LiquidityQuote> {
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (_, info) = self.raydium_pool_keys(pool)?;
+                let (reserve_a, reserve_b) = self.raydium_reserves(&info)?;
+                let supply = self.mint_supply(&info.lp_mint)?;
+                let (a, b) = u64::try_from(lp_amount).ok()
+                    .and_then(|lp| raydium::withdraw_amounts(lp, reserve_a, reserve_b, supply))
+                    .ok_or_else(|| anyhow!("{} LP exceeds the supply of {}", lp_amount, supply))?;
+                Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount, lp_supply: supply as u128 })
+            }
+            DexType::MeteoraDammV2 => {
+                let (_, state) = self.fetch_damm_v2_pool(pool)?;
+                if lp_amount > state.liquidity {
+                    bail!("{} liquidity exceeds the pool total of {}", lp_amount, state.liquidity);
+                }
+                let (a, b) = state.amounts_for_liquidity(lp_amount, false)?;
+                Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount, lp_supply: state.liquidity })
+            }
+            DexType::RaydiumCpmm | DexType::RaydiumClmm | DexType::Orca | DexType::MeteoraDbc => bail!("{:?} pool {} does not take LP deposits", pool.dex_type, pool.address),
+        }
+    }
+
+    /// `owner`'s LP tokens (Raydium) or the unlocked liquidity of `position` (DAMM v2).
+    pub async fn lp_balance(&self, pool: &DexPool, owner: &Pubkey, position: Option<&str>) -> Result<u128> {
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (_, info) = self.raydium_pool_keys(pool)?;
+                let lp_account = get_associated_token_address(owner, &info.lp_mint);
+                match self.rpc_client.get_account_with_commitment(&lp_account, self.rpc_client.commitment())?.value {
+                    Some(account) => Ok(layout::token_account_amount(&account.data)? as u128),
+                    None => Ok(0),
+                }
+            }
+            DexType::MeteoraDammV2 => {
+                let (_, position) = self.fetch_damm_v2_position(pool, position)?;
+                Ok(position.unlocked_liquidity)
+            }
+            DexType::RaydiumCpmm | DexType::RaydiumClmm | DexType::Orca | DexType::MeteoraDbc => bail!("{:?} pool {} does not take LP deposits", pool.dex_type, pool.address),
+        }
+    }
+
+    /// Deposit instructions for `quote`. The quoted token A amount is spent
+    /// exactly; token B may move by `slippage_bps` either way before the
+    /// program rejects it. DAMM v2 deposits go into an existing `position`.
+    pub async fn create_add_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (keys, info) = self.raydium_pool_keys(pool)?;
+                let user = raydium_lp_accounts(owner, &info);
+                Ok(vec![
+                    create_associated_token_account_idempotent(owner, owner, &info.lp_mint, &spl_token::id()),
+                    raydium::deposit(
+                        &keys,
+                        &user,
+                        quote.token_a_amount,
+                        trade::max_in(quote.token_b_amount, slippage_bps),
+                        trade::min_out(quote.token_b_amount, slippage_bps),
+                    ),
+                ])
+            }
+            DexType::MeteoraDammV2 => {
+                let (keys, state, position) = self.damm_v2_position_keys(pool, owner, position)?;
+                Ok(vec![damm_v2::add_liquidity(
+                    &keys,
+                    &state,
+                    &position,
+                    quote.lp_amount,
+                    trade::max_in(quote.token_a_amount, slippage_bps),
+                    trade::max_in(quote.token_b_amount, slippage_bps),
+                )])
+            }
+            DexType::RaydiumCpmm | DexType::RaydiumClmm | DexType::Orca | DexType::MeteoraDbc => bail!("{:?} pool {} does not take LP deposits", pool.dex_type, pool.address),
+        }
+    }
+
+    /// Withdraw instructions for `quote`, each side floored at its quoted
+    /// amount less `slippage_bps`.
+    pub async fn create_remove_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        let min_a = trade::min_out(quote.token_a_amount, slippage_bps);
+        let min_b = trade::min_out(quote.token_b_amount, slippage_bps);
+        match pool.dex_type {
+            DexType::Raydium => {
+                let (keys, info) = self.raydium_pool_keys(pool)?;
+                let user = raydium_lp_accounts(owner, &info);
+                let lp_amount = u64::try_from(quote.lp_amount).context("LP amount out of range")?;
+                Ok(vec![
+                    create_associated_token_account_idempotent(owner, owner, &info.coin_mint, &spl_token::id()),
+                    create_associated_token_account_idempotent(owner, owner, &info.pc_mint, &spl_token::id()),
+                    raydium::withdraw(&keys, &user, lp_amount, min_a, min_b),
+                ])
+            }
+            DexType::MeteoraDammV2 => {
+                let (keys, state, position) = self.damm_v2_position_keys(pool, owner, position)?;
+                Ok(vec![
+                    create_associated_token_account_idempotent(owner, owner, &state.token_a_mint, &state.token_a_program()),
+                    create_associated_token_account_idempotent(owner, owner, &state.token_b_mint, &state.token_b_program()),
+                    damm_v2::remove_liquidity(&keys, &state, &position, quote.lp_amount, min_a, min_b),
+                ])
+            }
+            DexType::RaydiumCpmm | DexType::RaydiumClmm | DexType::Orca | DexType::MeteoraDbc => bail!("{:?} pool {} does not take LP deposits", pool.dex_type, pool.address),
+        }
+    }
+
+    fn raydium_pool_keys(&self, pool: &DexPool) -> Result<(raydium::SwapKeys, raydium::AmmInfo)> {
+        let amm = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        let amm_account = self.fetch_account(&amm)?;
+        if amm_account.owner != self.raydium_program_id {
+            bail!("{} is not owned by the Raydium AMM v4 program", amm);
+        }
+        let info = raydium::AmmInfo::decode(&amm_account.data)?;
+        let market_account = self.fetch_account(&info.market)?;
+        let market = raydium::SerumMarket::decode(&market_account.data)?;
+        let keys = raydium::SwapKeys::from_state(self.raydium_program_id, amm, &info, &market)?;
+        Ok((keys, info))
+    }
+
+    fn raydium_reserves(&self, info: &raydium::AmmInfo) -> Result<(u64, u64)> {
+        let [coin_vault, pc_vault, open_orders] = self.fetch_accounts([info.coin_vault, info.pc_vault, info.open_orders])?;
+        Ok(info.reserves(
+            layout::token_account_amount(&coin_vault.data)?,
+            layout::token_account_amount(&pc_vault.data)?,
+            &raydium::OpenOrders::decode(&open_orders.data)?,
+        ))
+    }
+
+    fn mint_supply(&self, mint: &Pubkey) -> Result<u64> {
+        layout::mint_supply(&self.fetch_account(mint)?.data)
+    }
+
+    fn fetch_damm_v2_pool(&self, pool: &DexPool) -> Result<(Pubkey, damm_v2::Pool)> {
+        let address = Pubkey::from_str(&pool.address).context("invalid pool address")?;
+        Ok((address, damm_v2::Pool::decode(&self.fetch_account(&address)?.data)?))
+    }
+
+    fn fetch_damm_v2_position(&self, pool: &DexPool, position: Option<&str>) -> Result<(Pubkey, damm_v2::Position)> {
+        let position = position
+            .ok_or_else(|| anyhow!("DAMM v2 liquidity lives in a position; pass the position address"))?;
+        let address = Pubkey::from_str(position).context("invalid position address")?;
+        let state = damm_v2::Position::decode(&self.fetch_account(&address)?.data)?;
+        if state.pool.to_string() != pool.address {
+            bail!("position {} belongs to pool {}, not {}", address, state.pool, pool.address);
+        }
+        Ok((address, state))
+    }
+
+    fn damm_v2_position_keys(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        position: Option<&str>,
+    ) -> Result<(damm_v2::PositionKeys, damm_v2::Pool, damm_v2::Position)> {
+        let (pool_address, state) = self.fetch_damm_v2_pool(pool)?;
+        let (position_address, position) = self.fetch_damm_v2_position(pool, position)?;
+        let keys = damm_v2::PositionKeys {
+            pool: pool_address,
+            position: position_address,
+            owner: *owner,
+            token_a_account: get_associated_token_address_with_program_id(owner, &state.token_a_mint, &state.token_a_program()),
+            token_b_account: get_associated_token_address_with_program_id(owner, &state.token_b_mint, &state.token_b_program()),
+        };
+        Ok((keys, state, position))
+    }
+}
+
+/// Tick arrays passed to a CLMM swap, as the Raydium SDK does.
+const RAYDIUM_CLMM_TICK_ARRAYS: usize = 3;
+
+/// Whether `input_mint` → `output_mint` sells the first of `mints`.
+fn swap_direction(pool: &Pubkey, mints: (Pubkey, Pubkey), input_mint: &str, output_mint: &str) -> Result<bool> {
+    let input_mint = Pubkey::from_str(input_mint).context("invalid input mint")?;
+    let output_mint = Pubkey::from_str(output_mint).context("invalid output mint")?;
+    if (input_mint, output_mint) == mints {
+        Ok(true)
+    } else if (output_mint, input_mint) == mints {
+        Ok(false)
+    } else {
+        bail!("pool {} trades {}/{}, not {}/{}", pool, mints.0, mints.1, input_mint, output_mint)
+    }
+}
+
+fn raydium_lp_accounts(owner: &Pubkey, info: &raydium::AmmInfo) -> raydium::LpAccounts {
+    raydium::LpAccounts {
+        coin: get_associated_token_address(owner, &info.coin_mint),
+        pc: get_associated_token_address(owner, &info.pc_mint),
+        lp: get_associated_token_address(owner, &info.lp_mint),
+        owner: *owner,
+    }
+}
+
+/// `getProgramAccounts` with base64 data, which every pool layout here needs
+/// (base58 is limited to 128 bytes).
+/// `N` accounts out of a pool's dependency list.
+fn expect_accounts<const N: usize>(accounts: &[Account]) -> Result<&[Account; N]> {
+    accounts.try_into().map_err(|_| anyhow!("expected {} pool accounts, got {}", N, accounts.len()))
+}
+
+pub(crate) fn get_program_accounts(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    filters: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, Account)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    rpc_client.get_program_accounts_with_config(program_id, config)
+        .with_context(|| format!("getProgramAccounts failed for {}", program_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::tests::serve;
+    use base64::Engine;
+    use layout::anchor_discriminator;
+
+    fn rpc_result(result: serde_json::Value) -> (u16, String) {
+        (200, serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }).to_string())
+    }
+
+    fn ui_account(owner: &Pubkey, data: &[u8]) -> serde_json::Value {
+        let data = base64::engine::general_purpose::STANDARD.encode(data);
+        serde_json::json!({ "data": [data, "base64"], "executable": false, "lamports": 1, "owner": owner.to_string(), "rentEpoch": 0, "space": 0 })
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn find_pools_reads_every_vault_in_one_request() {
+        let mint = Pubkey::new_unique();
+        let whirlpool = |vault_a: Pubkey, vault_b: Pubkey| {
+            let mut data = vec![0u8; 653];
+            data[..8].copy_from_slice(&anchor_discriminator("account:Whirlpool"));
+            data[101..133].copy_from_slice(mint.as_ref());
+            data[133..165].copy_from_slice(vault_a.as_ref());
+            data[181..213].copy_from_slice(Pubkey::new_unique().as_ref());
+            data[213..245].copy_from_slice(vault_b.as_ref());
+            data
+        };
+        let vaults: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let pools: Vec<serde_json::Value> = vaults.chunks(2)
+            .map(|pair| serde_json::json!({
+                "pubkey": Pubkey::new_unique().to_string(),
+                "account": ui_account(&orca::WHIRLPOOL_PROGRAM_ID, &whirlpool(pair[0], pair[1])),
+            }))
+            .collect();
+        let mut sorted = vaults.clone();
+        sorted.sort();
+        let token_accounts: Vec<serde_json::Value> = sorted.iter()
+            .map(|vault| {
+                let mut data = vec![0u8; 165];
+                let amount = 1_000 * (vaults.iter().position(|v| v == vault).unwrap() as u64 + 1);
+                data[64..72].copy_from_slice(&amount.to_le_bytes());
+                ui_account(&spl_token::id(), &data)
+            })
+            .collect();
+
+        // getVersion, then the eleven pool queries (Orca token A is the
+        // seventh), then a single getMultipleAccounts.
+        let mut responses = vec![rpc_result(serde_json::json!({ "solana-core": "1.18.0", "feature-set": 0 }))];
+        responses.extend((0..11).map(|i| rpc_result(if i == 6 { serde_json::json!(pools) } else { serde_json::json!([]) })));
+        responses.push(rpc_result(serde_json::json!({ "context": { "slot": 1 }, "value": token_accounts })));
+        let (url, server) = serve(responses).await;
+
+        let found = DexClient::new(&url).unwrap().find_pools(&mint.to_string()).await.unwrap();
+        let requests = server.await.unwrap();
+        assert_eq!(requests.iter().filter(|r| r.contains("getMultipleAccounts")).count(), 1);
+        assert_eq!(requests.len(), 13);
+        let reserves: Vec<(u64, u64)> = found.iter().map(|pool| (pool.reserve_a, pool.reserve_b)).collect();
+        assert_eq!(reserves, [(3_000, 4_000), (1_000, 2_000)]);
+    }
+}
@@ -0,0 +1,473 @@
+//! Orca Whirlpool state, tick arrays, swap quoting and the `swap` instruction.
+//!
+//! Whirlpools are concentrated-liquidity pools: liquidity is only constant
+//! between initialized ticks, so a quote walks the tick arrays and adjusts
+//! liquidity at every tick it crosses. The math mirrors the on-chain program
+//! (Q64.64 sqrt prices, same rounding direction at every step).
+
+use super::layout::{anchor_discriminator, read_i128, read_i32, read_pubkey, read_u128, read_u16, read_u8};
+use super::math::{compute_swap_step, U256};
+pub use super::math::FEE_RATE_DENOMINATOR;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const WHIRLPOOL_PROGRAM_ID: Pubkey = pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+
+pub const TICK_ARRAY_SIZE: i32 = 88;
+pub const MIN_TICK_INDEX: i32 = -443_636;
+pub const MAX_TICK_INDEX: i32 = 443_636;
+pub const MIN_SQRT_PRICE: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE: u128 = 79_226_673_515_401_279_992_447_579_055;
+
+const WHIRLPOOL_LEN: usize = 653;
+const TICK_ARRAY_LEN: usize = 9988;
+const TICK_LEN: usize = 113;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Whirlpool {
+    pub tick_spacing: u16,
+    /// Swap fee in hundredths of a basis point (1e-6).
+    pub fee_rate: u16,
+    pub liquidity: u128,
+    pub sqrt_price: u128,
+    pub tick_current_index: i32,
+    pub token_mint_a: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_mint_b: Pubkey,
+    pub token_vault_b: Pubkey,
+}
+
+impl Whirlpool {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() != WHIRLPOOL_LEN || data[..8] != anchor_discriminator("account:Whirlpool") {
+            bail!("not a Whirlpool account ({} bytes)", data.len());
+        }
+        Ok(Self {
+            tick_spacing: read_u16(data, 41)?,
+            fee_rate: read_u16(data, 45)?,
+            liquidity: read_u128(data, 49)?,
+            sqrt_price: read_u128(data, 65)?,
+            tick_current_index: read_i32(data, 81)?,
+            token_mint_a: read_pubkey(data, 101)?,
+            token_vault_a: read_pubkey(data, 133)?,
+            token_mint_b: read_pubkey(data, 181)?,
+            token_vault_b: read_pubkey(data, 213)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tick {
+    pub initialized: bool,
+    pub liquidity_net: i128,
+    pub liquidity_gross: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickArray {
+    pub start_tick_index: i32,
+    pub ticks: Vec<Tick>,
+}
+
+impl TickArray {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() != TICK_ARRAY_LEN || data[..8] != anchor_discriminator("account:TickArray") {
+            bail!("not a Whirlpool tick array ({} bytes)", data.len());
+        }
+        let ticks = (0..TICK_ARRAY_SIZE as usize)
+            .map(|i| {
+                let offset = 12 + i * TICK_LEN;
+                Ok(Tick {
+                    initialized: read_u8(data, offset)? != 0,
+                    liquidity_net: read_i128(data, offset + 1)?,
+                    liquidity_gross: read_u128(data, offset + 17)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { start_tick_index: read_i32(data, 8)?, ticks })
+    }
+
+    fn tick(&self, tick_index: i32, tick_spacing: u16) -> Option<&Tick> {
+        let offset = tick_index - self.start_tick_index;
+        let spacing = tick_spacing as i32;
+        if offset < 0 || offset % spacing != 0 {
+            return None;
+        }
+        self.ticks.get((offset / spacing) as usize)
+    }
+}
+
+/// First tick of the array containing `tick_index`.
+pub fn tick_array_start_index(tick_index: i32, tick_spacing: u16) -> i32 {
+    let span = TICK_ARRAY_SIZE * tick_spacing as i32;
+    tick_index.div_euclid(span) * span
+}
+
+pub fn tick_array_address(whirlpool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"tick_array", whirlpool.as_ref(), start_tick_index.to_string().as_bytes()],
+        &WHIRLPOOL_PROGRAM_ID,
+    )
+    .0
+}
+
+pub fn oracle_address(whirlpool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"oracle", whirlpool.as_ref()], &WHIRLPOOL_PROGRAM_ID).0
+}
+
+/// Start indices of the three tick arrays a swap walks, in swap order.
+pub fn swap_tick_array_starts(pool: &Whirlpool, a_to_b: bool) -> [i32; 3] {
+    let span = TICK_ARRAY_SIZE * pool.tick_spacing as i32;
+    // Moving up, a pool sitting on the last tick of an array starts in the next one.
+    let shift = if a_to_b { 0 } else { pool.tick_spacing as i32 };
+    let start = tick_array_start_index(pool.tick_current_index + shift, pool.tick_spacing);
+    let step = if a_to_b { -span } else { span };
+    [start, start + step, start + 2 * step]
+}
+
+/// Q64.64 square root of `1.0001^tick`, bit-for-bit with the program.
+pub fn sqrt_price_from_tick_index(tick: i32) -> u128 {
+    const POSITIVE: [u128; 18] = [
+        79236085330515764027303304731,
+        79244008939048815603706035061,
+        79259858533276714757314932305,
+        79291567232598584799939703904,
+        79355022692464371645785046466,
+        79482085999252804386437311141,
+        79736823300114093921829183326,
+        80248749790819932309965073892,
+        81282483887344747381513967011,
+        83390072131320151908154831281,
+        87770609709833776024991924138,
+        97234110755111693312479820773,
+        119332217159966728226237229890,
+        179736315981702064433883588727,
+        407748233172238350107850275304,
+        2098478828474011932436660412517,
+        55581415166113811149459800483533,
+        38992368544603139932233054999993551,
+    ];
+    const NEGATIVE: [u128; 18] = [
+        18444899583751176498,
+        18443055278223354162,
+        18439367220385604838,
+        18431993317065449817,
+        18417254355718160513,
+        18387811781193591352,
+        18329067761203520168,
+        18212142134806087854,
+        17980523815641551639,
+        17526086738831147013,
+        16651378430235024244,
+        15030750278693429944,
+        12247334978882834399,
+        8131365268884726200,
+        3584323654723342297,
+        696457651847595233,
+        26294789957452057,
+        37481735321082,
+    ];
+
+    let abs = tick.unsigned_abs();
+    if tick >= 0 {
+        // Q96 intermediates keep precision for large prices; the product needs 256 bits.
+        let mut ratio: u128 = if abs & 1 != 0 { 79232123823359799118286999567 } else { 1 << 96 };
+        for (bit, factor) in POSITIVE.iter().enumerate() {
+            if abs & (2 << bit) != 0 {
+                ratio = U256::mul(ratio, *factor).shr(96).low_u128();
+            }
+        }
+        ratio >> 32
+    } else {
+        let mut ratio: u128 = if abs & 1 != 0 { 18445821805675392311 } else { 1 << 64 };
+        for (bit, factor) in NEGATIVE.iter().enumerate() {
+            if abs & (2 << bit) != 0 {
+                ratio = (ratio * factor) >> 64;
+            }
+        }
+        ratio
+    }
+}
+
+/// The next tick a swap would reach from `tick_current` and whether it is
+/// initialized. When the loaded arrays hold no initialized tick, the last
+/// reachable tick is returned so the swap can run up to it.
+fn next_tick(tick_arrays: &[TickArray], tick_current: i32, tick_spacing: u16, a_to_b: bool) -> Result<(i32, bool)> {
+    let spacing = tick_spacing as i32;
+    let lookup = |index: i32| tick_arrays.iter().find_map(|array| array.tick(index, tick_spacing));
+
+    let mut index = tick_current - tick_current.rem_euclid(spacing);
+    if !a_to_b {
+        index += spacing;
+    }
+    let mut last_reachable = None;
+    while let Some(tick) = lookup(index) {
+        if tick.initialized {
+            return Ok((index, true));
+        }
+        last_reachable = Some(index);
+        index += if a_to_b { -spacing } else { spacing };
+    }
+    last_reachable
+        .map(|index| (index, false))
+        .ok_or_else(|| anyhow!("swap runs past the loaded tick arrays at tick {}", tick_current))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub end_sqrt_price: u128,
+}
+
+/// Simulates a swap across `tick_arrays` the way the program executes it.
+/// `amount` is the input when `amount_specified_is_input`, else the desired output.
+pub fn quote(
+    pool: &Whirlpool,
+    tick_arrays: &[TickArray],
+    amount: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+) -> Result<SwapQuote> {
+    let limit = if a_to_b { MIN_SQRT_PRICE } else { MAX_SQRT_PRICE };
+    let mut remaining = amount;
+    let mut calculated: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut sqrt_price = pool.sqrt_price;
+    let mut tick_current = pool.tick_current_index;
+    let mut liquidity = pool.liquidity;
+
+    while remaining > 0 && sqrt_price != limit {
+        let (tick_index, initialized) = next_tick(tick_arrays, tick_current, pool.tick_spacing, a_to_b)?;
+        let tick_index = tick_index.clamp(MIN_TICK_INDEX, MAX_TICK_INDEX);
+        let tick_price = sqrt_price_from_tick_index(tick_index);
+        let target = if a_to_b { tick_price.max(limit) } else { tick_price.min(limit) };
+
+        let step = compute_swap_step(remaining, pool.fee_rate.into(), liquidity, sqrt_price, target, amount_specified_is_input, a_to_b)?;
+        let overflow = || anyhow!("swap amount overflow");
+        if amount_specified_is_input {
+            remaining = remaining.checked_sub(step.amount_in + step.fee_amount).ok_or_else(overflow)?;
+            calculated = calculated.checked_add(step.amount_out).ok_or_else(overflow)?;
+        } else {
+            remaining = remaining.checked_sub(step.amount_out).ok_or_else(overflow)?;
+            calculated = calculated.checked_add(step.amount_in + step.fee_amount).ok_or_else(overflow)?;
+        }
+        fee_total += step.fee_amount;
+
+        if step.next_sqrt_price == tick_price {
+            if initialized {
+                let tick = tick_arrays
+                    .iter()
+                    .find_map(|array| array.tick(tick_index, pool.tick_spacing))
+                    .expect("next_tick returned a loaded tick");
+                // Crossing downward removes the liquidity that starts at this tick.
+                let net = if a_to_b { -tick.liquidity_net } else { tick.liquidity_net };
+                liquidity = liquidity
+                    .checked_add_signed(net)
+                    .ok_or_else(|| anyhow!("liquidity underflow crossing tick {}", tick_index))?;
+            }
+            tick_current = if a_to_b { tick_index - 1 } else { tick_index };
+        }
+        sqrt_price = step.next_sqrt_price;
+    }
+
+    let (amount_in, amount_out) = if amount_specified_is_input {
+        (amount - remaining, calculated)
+    } else {
+        (calculated, amount - remaining)
+    };
+    Ok(SwapQuote { amount_in, amount_out, fee_amount: fee_total, end_sqrt_price: sqrt_price })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount: u64,
+    pub other_amount_threshold: u64,
+    pub sqrt_price_limit: u128,
+    pub amount_specified_is_input: bool,
+    pub a_to_b: bool,
+}
+
+/// The Whirlpool `swap` instruction. `token_owner_account_a/b` are the
+/// authority's token accounts for the pool's mint A and mint B.
+pub fn swap(
+    whirlpool: Pubkey,
+    pool: &Whirlpool,
+    token_authority: Pubkey,
+    token_owner_account_a: Pubkey,
+    token_owner_account_b: Pubkey,
+    params: &SwapParams,
+) -> Instruction {
+    let tick_arrays = swap_tick_array_starts(pool, params.a_to_b).map(|start| tick_array_address(&whirlpool, start));
+
+    let mut data = Vec::with_capacity(42);
+    data.extend_from_slice(&anchor_discriminator("global:swap"));
+    data.extend_from_slice(&params.amount.to_le_bytes());
+    data.extend_from_slice(&params.other_amount_threshold.to_le_bytes());
+    data.extend_from_slice(&params.sqrt_price_limit.to_le_bytes());
+    data.push(params.amount_specified_is_input as u8);
+    data.push(params.a_to_b as u8);
+
+    Instruction {
+        program_id: WHIRLPOOL_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(token_authority, true),
+            AccountMeta::new(whirlpool, false),
+            AccountMeta::new(token_owner_account_a, false),
+            AccountMeta::new(pool.token_vault_a, false),
+            AccountMeta::new(token_owner_account_b, false),
+            AccountMeta::new(pool.token_vault_b, false),
+            AccountMeta::new(tick_arrays[0], false),
+            AccountMeta::new(tick_arrays[1], false),
+            AccountMeta::new(tick_arrays[2], false),
+            AccountMeta::new(oracle_address(&whirlpool), false),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(liquidity: u128, tick: i32) -> Whirlpool {
+        Whirlpool {
+            tick_spacing: 64,
+            fee_rate: 3000,
+            liquidity,
+            sqrt_price: sqrt_price_from_tick_index(tick),
+            tick_current_index: tick,
+            token_mint_a: Pubkey::new_unique(),
+            token_vault_a: Pubkey::new_unique(),
+            token_mint_b: Pubkey::new_unique(),
+            token_vault_b: Pubkey::new_unique(),
+        }
+    }
+
+    fn empty_arrays(pool: &Whirlpool, a_to_b: bool) -> Vec<TickArray> {
+        swap_tick_array_starts(pool, a_to_b)
+            .iter()
+            .map(|&start_tick_index| TickArray { start_tick_index, ticks: vec![Tick::default(); TICK_ARRAY_SIZE as usize] })
+            .collect()
+    }
+
+    #[test]
+    fn tick_math_matches_program_bounds() {
+        assert_eq!(sqrt_price_from_tick_index(0), 1 << 64);
+        assert_eq!(sqrt_price_from_tick_index(MIN_TICK_INDEX), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_from_tick_index(MAX_TICK_INDEX), MAX_SQRT_PRICE);
+        assert!(sqrt_price_from_tick_index(-1) < 1 << 64);
+        assert!(sqrt_price_from_tick_index(1) > 1 << 64);
+    }
+
+    #[test]
+    fn quote_within_one_range_matches_closed_form() {
+        let pool = pool(1_000_000_000_000, 0);
+        let arrays = empty_arrays(&pool, true);
+        let amount_in = 1_000_000u64;
+
+        let quote = quote(&pool, &arrays, amount_in, true, true).unwrap();
+        let net_in = amount_in as f64 * 0.997;
+        // With sqrt price 1.0, virtual reserves equal L on both sides.
+        let expected = 1e12 * net_in / (1e12 + net_in);
+        assert!((quote.amount_out as f64 - expected).abs() <= 1.0, "{} vs {}", quote.amount_out, expected);
+        assert_eq!(quote.amount_in, amount_in);
+        assert_eq!(quote.fee_amount, 3000);
+        assert!(quote.end_sqrt_price < pool.sqrt_price);
+    }
+
+    #[test]
+    fn quote_switches_liquidity_when_crossing_an_initialized_tick() {
+        let liquidity = 100_000_000u128;
+        let pool = pool(liquidity, 100);
+        let mut arrays = empty_arrays(&pool, true);
+        // Tick 64 is where an extra position ends, so crossing it downward adds liquidity.
+        let slot = ((64 - arrays[0].start_tick_index) / 64) as usize;
+        arrays[0].ticks[slot] = Tick { initialized: true, liquidity_net: -(liquidity as i128), liquidity_gross: liquidity };
+
+        let amount_in = 1_000_000u64;
+        let quote = quote(&pool, &arrays, amount_in, true, true).unwrap();
+
+        let boundary = sqrt_price_from_tick_index(64);
+        let first = compute_swap_step(amount_in, pool.fee_rate.into(), liquidity, pool.sqrt_price, boundary, true, true).unwrap();
+        assert_eq!(first.next_sqrt_price, boundary);
+        let remaining = amount_in - first.amount_in - first.fee_amount;
+        let lowest = sqrt_price_from_tick_index(arrays[2].start_tick_index);
+        let second = compute_swap_step(remaining, pool.fee_rate.into(), 2 * liquidity, boundary, lowest, true, true).unwrap();
+
+        assert_eq!(quote.amount_out, first.amount_out + second.amount_out);
+        assert_eq!(quote.fee_amount, first.fee_amount + second.fee_amount);
+    }
+
+    #[test]
+    fn quotes_are_monotonic_and_never_beat_the_curve() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x4f52_4341);
+        let mut checked = 0;
+        for _ in 0..300 {
+            let pool = pool(rng.gen_range(1_000_000_000u128..1_000_000_000_000_000), rng.gen_range(-1_000..1_000));
+            let a_to_b = rng.gen_bool(0.5);
+            let arrays = empty_arrays(&pool, a_to_b);
+            let amount = rng.gen_range(1..1_000_000_000u64);
+
+            let Ok(small) = quote(&pool, &arrays, amount, true, a_to_b) else { continue };
+            let Ok(large) = quote(&pool, &arrays, amount * 2, true, a_to_b) else { continue };
+            assert!(large.amount_out >= small.amount_out);
+            assert!(large.fee_amount >= small.fee_amount);
+            assert_eq!(small.amount_in, amount);
+            // Selling A pushes the price down, selling B pushes it up.
+            assert!(if a_to_b { small.end_sqrt_price <= pool.sqrt_price } else { small.end_sqrt_price >= pool.sqrt_price });
+
+            // Within one range the pool is x*y=L^2 on virtual reserves; rounding must favour the pool.
+            let price = pool.sqrt_price as f64 / 2f64.powi(64);
+            let liquidity = pool.liquidity as f64;
+            let (x, y) = (liquidity / price, liquidity * price);
+            let net = (amount - small.fee_amount) as f64;
+            let ideal = if a_to_b { y * net / (x + net) } else { x * net / (y + net) };
+            assert!(small.amount_out as f64 <= ideal * (1.0 + 1e-9) + 1.0, "{} > {}", small.amount_out, ideal);
+            checked += 1;
+        }
+        assert!(checked > 200, "only {} cases stayed within the tick arrays", checked);
+    }
+
+    #[test]
+    fn quote_fails_when_the_swap_outruns_the_tick_arrays() {
+        let pool = pool(1_000, 0);
+        let arrays = empty_arrays(&pool, true);
+        assert!(quote(&pool, &arrays, u64::MAX / 2, true, true).is_err());
+    }
+
+    #[test]
+    fn swap_instruction_layout() {
+        let whirlpool = Pubkey::new_unique();
+        let pool = pool(1, -100);
+        let authority = Pubkey::new_unique();
+        let params = SwapParams {
+            amount: 5_000,
+            other_amount_threshold: 4_900,
+            sqrt_price_limit: MIN_SQRT_PRICE,
+            amount_specified_is_input: true,
+            a_to_b: true,
+        };
+        let ix = swap(whirlpool, &pool, authority, Pubkey::new_unique(), Pubkey::new_unique(), &params);
+
+        assert_eq!(ix.data[..8], [248, 198, 158, 145, 225, 117, 135, 200]);
+        assert_eq!(ix.data.len(), 42);
+        assert_eq!(ix.data[8..16], 5_000u64.to_le_bytes());
+        assert_eq!(ix.data[16..24], 4_900u64.to_le_bytes());
+        assert_eq!(ix.data[40..], [1, 1]);
+        assert_eq!(ix.accounts.len(), 11);
+        assert!(ix.accounts[1].is_signer);
+        // Tick -100 lives in the array starting at -5632; a→b walks downward from there.
+        assert_eq!(ix.accounts[7].pubkey, tick_array_address(&whirlpool, -5632));
+        assert_eq!(ix.accounts[8].pubkey, tick_array_address(&whirlpool, -11264));
+        assert_eq!(ix.accounts[10].pubkey, oracle_address(&whirlpool));
+    }
+}
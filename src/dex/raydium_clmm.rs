@@ -0,0 +1,462 @@
+//! Raydium CLMM pool state, tick arrays, swap quoting and `swap_v2`.
+//!
+//! CLMM is concentrated liquidity like Whirlpools and shares the step math
+//! in [`super::math`], but its tick arrays hold 60 ticks, are sparse (the
+//! pool keeps a bitmap of which arrays exist) and use their own tick-to-price
+//! constants.
+
+use super::layout::{anchor_discriminator, read_i128, read_i32, read_pubkey, read_u128, read_u16, read_u32, read_u64, read_u8};
+use super::math::compute_swap_step;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const CLMM_PROGRAM_ID: Pubkey = pubkey!("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK");
+const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Offsets of `PoolState::token_mint_0` / `token_mint_1`, for `getProgramAccounts` filters.
+pub const POOL_TOKEN_MINT_0_OFFSET: usize = 73;
+pub const POOL_TOKEN_MINT_1_OFFSET: usize = 105;
+pub const POOL_STATE_LEN: u64 = 1544;
+
+pub const TICK_ARRAY_SIZE: i32 = 60;
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+pub const MIN_SQRT_PRICE_X64: u128 = 4_295_048_016;
+pub const MAX_SQRT_PRICE_X64: u128 = 79_226_673_521_066_979_257_578_248_091;
+
+const TICK_LEN: usize = 168;
+const TICKS_OFFSET: usize = 44;
+const BITMAP_OFFSET: usize = 904;
+/// The pool's own bitmap covers 512 tick arrays either side of tick 0.
+const BITMAP_HALF_SPAN: i32 = 512;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolState {
+    pub amm_config: Pubkey,
+    pub token_mint_0: Pubkey,
+    pub token_mint_1: Pubkey,
+    pub token_vault_0: Pubkey,
+    pub token_vault_1: Pubkey,
+    pub observation_key: Pubkey,
+    pub tick_spacing: u16,
+    pub liquidity: u128,
+    pub sqrt_price_x64: u128,
+    pub tick_current: i32,
+    pub status: u8,
+    /// Bit `i` set when the tick array starting at `(i - 512) * 60 * tick_spacing` exists.
+    pub tick_array_bitmap: [u64; 16],
+}
+
+impl PoolState {
+    pub fn discriminator() -> [u8; 8] {
+        anchor_discriminator("account:PoolState")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() != POOL_STATE_LEN as usize || data[..8] != Self::discriminator() {
+            bail!("not a Raydium CLMM pool account ({} bytes)", data.len());
+        }
+        let mut tick_array_bitmap = [0u64; 16];
+        for (i, word) in tick_array_bitmap.iter_mut().enumerate() {
+            *word = read_u64(data, BITMAP_OFFSET + i * 8)?;
+        }
+        Ok(Self {
+            amm_config: read_pubkey(data, 9)?,
+            token_mint_0: read_pubkey(data, POOL_TOKEN_MINT_0_OFFSET)?,
+            token_mint_1: read_pubkey(data, POOL_TOKEN_MINT_1_OFFSET)?,
+            token_vault_0: read_pubkey(data, 137)?,
+            token_vault_1: read_pubkey(data, 169)?,
+            observation_key: read_pubkey(data, 201)?,
+            tick_spacing: read_u16(data, 235)?,
+            liquidity: read_u128(data, 237)?,
+            sqrt_price_x64: read_u128(data, 253)?,
+            tick_current: read_i32(data, 269)?,
+            status: read_u8(data, 389)?,
+            tick_array_bitmap,
+        })
+    }
+
+    fn tick_array_exists(&self, start_index: i32) -> bool {
+        let bit = start_index / (TICK_ARRAY_SIZE * self.tick_spacing as i32) + BITMAP_HALF_SPAN;
+        (0..2 * BITMAP_HALF_SPAN).contains(&bit) && self.tick_array_bitmap[bit as usize / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// Start indices of the first `count` existing tick arrays a swap walks,
+    /// in swap order, beginning with the array holding the current tick.
+    /// Arrays outside the pool's own bitmap (held in its bitmap extension)
+    /// are not found.
+    pub fn swap_tick_array_starts(&self, zero_for_one: bool, count: usize) -> Vec<i32> {
+        let span = TICK_ARRAY_SIZE * self.tick_spacing as i32;
+        let step = if zero_for_one { -span } else { span };
+        let mut start = tick_array_start_index(self.tick_current, self.tick_spacing);
+        let mut starts = Vec::with_capacity(count);
+        while starts.len() < count && (-BITMAP_HALF_SPAN * span..BITMAP_HALF_SPAN * span).contains(&start) {
+            if self.tick_array_exists(start) {
+                starts.push(start);
+            }
+            start += step;
+        }
+        starts
+    }
+}
+
+/// The fee tier a pool points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmmConfig {
+    /// Over [`super::math::FEE_RATE_DENOMINATOR`].
+    pub trade_fee_rate: u32,
+}
+
+impl AmmConfig {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != anchor_discriminator("account:AmmConfig") {
+            bail!("not a Raydium CLMM config account");
+        }
+        Ok(Self { trade_fee_rate: read_u32(data, 47)? })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    pub tick: i32,
+    pub liquidity_net: i128,
+    pub liquidity_gross: u128,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickArray {
+    pub start_tick_index: i32,
+    pub ticks: Vec<Tick>,
+}
+
+impl TickArray {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < TICKS_OFFSET + TICK_ARRAY_SIZE as usize * TICK_LEN
+            || data[..8] != anchor_discriminator("account:TickArrayState")
+        {
+            bail!("not a Raydium CLMM tick array ({} bytes)", data.len());
+        }
+        let ticks = (0..TICK_ARRAY_SIZE as usize)
+            .map(|i| {
+                let offset = TICKS_OFFSET + i * TICK_LEN;
+                Ok(Tick {
+                    tick: read_i32(data, offset)?,
+                    liquidity_net: read_i128(data, offset + 4)?,
+                    liquidity_gross: read_u128(data, offset + 20)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { start_tick_index: read_i32(data, 40)?, ticks })
+    }
+}
+
+/// First tick of the array containing `tick_index`.
+pub fn tick_array_start_index(tick_index: i32, tick_spacing: u16) -> i32 {
+    let span = TICK_ARRAY_SIZE * tick_spacing as i32;
+    tick_index.div_euclid(span) * span
+}
+
+/// Tick arrays are seeded with the big-endian start index.
+pub fn tick_array_address(pool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(&[b"tick_array", pool.as_ref(), &start_tick_index.to_be_bytes()], &CLMM_PROGRAM_ID).0
+}
+
+pub fn bitmap_extension_address(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_tick_array_bitmap_extension", pool.as_ref()], &CLMM_PROGRAM_ID).0
+}
+
+/// Q64.64 square root of `1.0001^tick` with the program's constants.
+pub fn sqrt_price_at_tick(tick: i32) -> u128 {
+    const FACTORS: [u128; 18] = [
+        0xfff97272373d4000,
+        0xfff2e50f5f657000,
+        0xffe5caca7e10f000,
+        0xffcb9843d60f7000,
+        0xff973b41fa98e800,
+        0xff2ea16466c9b000,
+        0xfe5dee046a9a3800,
+        0xfcbe86c7900bb000,
+        0xf987a7253ac65800,
+        0xf3392b0822bb6000,
+        0xe7159475a2caf000,
+        0xd097f3bdfd2f2000,
+        0xa9f746462d9f8000,
+        0x70d869a156f31c00,
+        0x31be135f97ed3200,
+        0x9aa508b5b85a500,
+        0x5d6af8dedc582c,
+        0x2216e584f5fa,
+    ];
+    let abs = tick.unsigned_abs();
+    let mut ratio: u128 = if abs & 1 != 0 { 0xfffcb933bd6fb800 } else { 1 << 64 };
+    for (bit, factor) in FACTORS.iter().enumerate() {
+        if abs & (2 << bit) != 0 {
+            ratio = (ratio * factor) >> 64;
+        }
+    }
+    if tick > 0 {
+        ratio = u128::MAX / ratio;
+    }
+    ratio
+}
+
+/// The next initialized tick from `tick_current`: at or below it when
+/// moving down, strictly above it when moving up. `None` when the loaded
+/// arrays hold no more.
+fn next_initialized_tick(tick_arrays: &[TickArray], tick_current: i32, tick_spacing: u16, zero_for_one: bool) -> Option<i32> {
+    let spacing = tick_spacing as i32;
+    let floor = tick_current - tick_current.rem_euclid(spacing);
+    let initialized = tick_arrays
+        .iter()
+        .flat_map(|array| {
+            array.ticks.iter().enumerate().filter(|(_, tick)| tick.liquidity_gross != 0).map(|(i, _)| array.start_tick_index + i as i32 * spacing)
+        });
+    if zero_for_one {
+        initialized.filter(|&index| index <= floor).max()
+    } else {
+        initialized.filter(|&index| index > floor).min()
+    }
+}
+
+fn liquidity_net(tick_arrays: &[TickArray], tick_index: i32, tick_spacing: u16) -> i128 {
+    let spacing = tick_spacing as i32;
+    tick_arrays
+        .iter()
+        .find(|array| (array.start_tick_index..array.start_tick_index + TICK_ARRAY_SIZE * spacing).contains(&tick_index))
+        .map_or(0, |array| array.ticks[((tick_index - array.start_tick_index) / spacing) as usize].liquidity_net)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub end_sqrt_price: u128,
+}
+
+/// Simulates an exact-input swap across `tick_arrays` the way the program
+/// executes it. The program cannot move past the tick arrays it is given,
+/// so neither does the quote: `amount_in` comes back short when the loaded
+/// arrays run out first.
+pub fn quote(pool: &PoolState, trade_fee_rate: u32, tick_arrays: &[TickArray], amount_in: u64, zero_for_one: bool) -> Result<SwapQuote> {
+    let span = TICK_ARRAY_SIZE * pool.tick_spacing as i32;
+    let edge = if zero_for_one {
+        tick_arrays.iter().map(|array| array.start_tick_index).min().map_or(MIN_SQRT_PRICE_X64 + 1, sqrt_price_at_tick)
+    } else {
+        tick_arrays.iter().map(|array| array.start_tick_index + span).max().map_or(MAX_SQRT_PRICE_X64 - 1, |tick| sqrt_price_at_tick(tick.min(MAX_TICK)))
+    };
+    let limit = if zero_for_one { edge.max(MIN_SQRT_PRICE_X64 + 1) } else { edge.min(MAX_SQRT_PRICE_X64 - 1) };
+    let mut remaining = amount_in;
+    let mut amount_out: u64 = 0;
+    let mut fee_total: u64 = 0;
+    let mut sqrt_price = pool.sqrt_price_x64;
+    let mut tick_current = pool.tick_current;
+    let mut liquidity = pool.liquidity;
+
+    while remaining > 0 && sqrt_price != limit {
+        let next = next_initialized_tick(tick_arrays, tick_current, pool.tick_spacing, zero_for_one);
+        let tick_next = next.unwrap_or(if zero_for_one { MIN_TICK } else { MAX_TICK }).clamp(MIN_TICK, MAX_TICK);
+        let tick_price = sqrt_price_at_tick(tick_next);
+        let target = if zero_for_one { tick_price.max(limit) } else { tick_price.min(limit) };
+
+        let step = compute_swap_step(remaining, trade_fee_rate, liquidity, sqrt_price, target, true, zero_for_one)?;
+        let overflow = || anyhow!("swap amount overflow");
+        remaining = remaining.checked_sub(step.amount_in + step.fee_amount).ok_or_else(overflow)?;
+        amount_out = amount_out.checked_add(step.amount_out).ok_or_else(overflow)?;
+        fee_total += step.fee_amount;
+
+        if step.next_sqrt_price == tick_price {
+            if next.is_some() {
+                let net = liquidity_net(tick_arrays, tick_next, pool.tick_spacing);
+                // Crossing downward removes the liquidity that starts at this tick.
+                let net = if zero_for_one { -net } else { net };
+                liquidity = liquidity
+                    .checked_add_signed(net)
+                    .ok_or_else(|| anyhow!("liquidity underflow crossing tick {}", tick_next))?;
+            }
+            tick_current = if zero_for_one { tick_next - 1 } else { tick_next };
+        } else if step.next_sqrt_price != sqrt_price {
+            tick_current = tick_at_sqrt_price(step.next_sqrt_price)?;
+        }
+        sqrt_price = step.next_sqrt_price;
+    }
+
+    Ok(SwapQuote { amount_in: amount_in - remaining, amount_out, fee_amount: fee_total, end_sqrt_price: sqrt_price })
+}
+
+/// Greatest tick whose sqrt price is at or below `sqrt_price`.
+fn tick_at_sqrt_price(sqrt_price: u128) -> Result<i32> {
+    if !(MIN_SQRT_PRICE_X64..MAX_SQRT_PRICE_X64).contains(&sqrt_price) {
+        bail!("sqrt price {} outside the tick range", sqrt_price);
+    }
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid) <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    /// Token 0 in, token 1 out.
+    pub zero_for_one: bool,
+}
+
+/// Exact-input `swap_v2` with no price limit beyond `minimum_amount_out`.
+/// `tick_array_starts` come from [`PoolState::swap_tick_array_starts`].
+pub fn swap_v2(
+    pool_address: Pubkey,
+    pool: &PoolState,
+    payer: Pubkey,
+    input_account: Pubkey,
+    output_account: Pubkey,
+    tick_array_starts: &[i32],
+    params: &SwapParams,
+) -> Instruction {
+    let (input_vault, output_vault, input_mint, output_mint) = if params.zero_for_one {
+        (pool.token_vault_0, pool.token_vault_1, pool.token_mint_0, pool.token_mint_1)
+    } else {
+        (pool.token_vault_1, pool.token_vault_0, pool.token_mint_1, pool.token_mint_0)
+    };
+
+    let mut data = Vec::with_capacity(41);
+    data.extend_from_slice(&anchor_discriminator("global:swap_v2"));
+    data.extend_from_slice(&params.amount_in.to_le_bytes());
+    data.extend_from_slice(&params.minimum_amount_out.to_le_bytes());
+    // A zero limit lets the program use the bound for the direction.
+    data.extend_from_slice(&0u128.to_le_bytes());
+    data.push(1); // is_base_input
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(payer, true),
+        AccountMeta::new_readonly(pool.amm_config, false),
+        AccountMeta::new(pool_address, false),
+        AccountMeta::new(input_account, false),
+        AccountMeta::new(output_account, false),
+        AccountMeta::new(input_vault, false),
+        AccountMeta::new(output_vault, false),
+        AccountMeta::new(pool.observation_key, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+        AccountMeta::new_readonly(MEMO_PROGRAM_ID, false),
+        AccountMeta::new_readonly(input_mint, false),
+        AccountMeta::new_readonly(output_mint, false),
+        AccountMeta::new_readonly(bitmap_extension_address(&pool_address), false),
+    ];
+    accounts.extend(
+        tick_array_starts.iter().map(|&start| AccountMeta::new(tick_array_address(&pool_address, start), false)),
+    );
+    Instruction { program_id: CLMM_PROGRAM_ID, accounts, data }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(liquidity: u128, tick_current: i32, tick_spacing: u16) -> PoolState {
+        PoolState {
+            amm_config: Pubkey::new_unique(),
+            token_mint_0: Pubkey::new_unique(),
+            token_mint_1: Pubkey::new_unique(),
+            token_vault_0: Pubkey::new_unique(),
+            token_vault_1: Pubkey::new_unique(),
+            observation_key: Pubkey::new_unique(),
+            tick_spacing,
+            liquidity,
+            sqrt_price_x64: sqrt_price_at_tick(tick_current),
+            tick_current,
+            status: 0,
+            tick_array_bitmap: [0; 16],
+        }
+    }
+
+    fn array(start_tick_index: i32, tick_spacing: u16, initialized: &[(i32, i128)]) -> TickArray {
+        let mut ticks: Vec<Tick> = (0..TICK_ARRAY_SIZE)
+            .map(|i| Tick { tick: start_tick_index + i * tick_spacing as i32, liquidity_net: 0, liquidity_gross: 0 })
+            .collect();
+        for &(index, net) in initialized {
+            let tick = &mut ticks[((index - start_tick_index) / tick_spacing as i32) as usize];
+            tick.liquidity_net = net;
+            tick.liquidity_gross = net.unsigned_abs();
+        }
+        TickArray { start_tick_index, ticks }
+    }
+
+    #[test]
+    fn tick_math_matches_program_bounds() {
+        assert_eq!(sqrt_price_at_tick(0), 1 << 64);
+        assert_eq!(sqrt_price_at_tick(MIN_TICK), MIN_SQRT_PRICE_X64);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK), MAX_SQRT_PRICE_X64);
+        for tick in [-100_000, -1, 1, 7, 100_000] {
+            assert!(sqrt_price_at_tick(tick) < sqrt_price_at_tick(tick + 1));
+            assert_eq!(tick_at_sqrt_price(sqrt_price_at_tick(tick)).unwrap(), tick);
+        }
+    }
+
+    #[test]
+    fn tick_arrays_are_found_through_the_bitmap_with_big_endian_seeds() {
+        let mut pool = pool(1, 10, 10);
+        // Arrays at 0 and -1200 exist; -600 does not.
+        for start in [0, -1_200] {
+            let bit = (start / 600 + 512) as usize;
+            pool.tick_array_bitmap[bit / 64] |= 1 << (bit % 64);
+        }
+        assert_eq!(pool.swap_tick_array_starts(true, 3), [0, -1_200]);
+        assert_eq!(pool.swap_tick_array_starts(false, 3), [0]);
+
+        let address = Pubkey::new_unique();
+        let expected = Pubkey::find_program_address(&[b"tick_array", address.as_ref(), &(-1_200i32).to_be_bytes()], &CLMM_PROGRAM_ID).0;
+        assert_eq!(tick_array_address(&address, -1_200), expected);
+    }
+
+    #[test]
+    fn quote_crosses_initialized_ticks_and_stops_at_the_loaded_arrays() {
+        let liquidity = 1_000_000_000u128;
+        let pool = pool(liquidity, 5, 10);
+        // Liquidity doubles below tick -100.
+        let arrays = [array(0, 10, &[]), array(-600, 10, &[(-100, -(liquidity as i128))])];
+        let amount_in = 100_000_000;
+
+        let quote = quote(&pool, 2_500, &arrays, amount_in, true).unwrap();
+        let boundary = sqrt_price_at_tick(-100);
+        let first = compute_swap_step(amount_in, 2_500, liquidity, pool.sqrt_price_x64, boundary, true, true).unwrap();
+        assert_eq!(first.next_sqrt_price, boundary);
+        let remaining = amount_in - first.amount_in - first.fee_amount;
+        // The second array ends at tick -600; the program would need a third.
+        let second = compute_swap_step(remaining, 2_500, 2 * liquidity, boundary, sqrt_price_at_tick(-600), true, true).unwrap();
+        assert_eq!(second.next_sqrt_price, sqrt_price_at_tick(-600));
+        assert_eq!(quote.amount_in, first.amount_in + first.fee_amount + second.amount_in + second.fee_amount);
+        assert!(quote.amount_in < amount_in);
+        assert_eq!(quote.amount_out, first.amount_out + second.amount_out);
+        assert_eq!(quote.fee_amount, first.fee_amount + second.fee_amount);
+    }
+
+    #[test]
+    fn swap_v2_layout() {
+        let pool_address = Pubkey::new_unique();
+        let pool = pool(1, 0, 10);
+        let params = SwapParams { amount_in: 9, minimum_amount_out: 8, zero_for_one: true };
+        let ix = swap_v2(pool_address, &pool, Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), &[0, -600], &params);
+
+        assert_eq!(ix.data[..8], anchor_discriminator("global:swap_v2"));
+        assert_eq!(ix.data.len(), 41);
+        assert_eq!(ix.data[16..24], 8u64.to_le_bytes());
+        assert_eq!(ix.data[40], 1);
+        assert_eq!(ix.accounts.len(), 16);
+        assert_eq!(ix.accounts[5].pubkey, pool.token_vault_0);
+        assert_eq!(ix.accounts[13].pubkey, bitmap_extension_address(&pool_address));
+        assert_eq!(ix.accounts[15].pubkey, tick_array_address(&pool_address, -600));
+    }
+}
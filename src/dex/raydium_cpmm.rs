@@ -0,0 +1,213 @@
+//! Raydium CPMM pool state, quoting and the `swap_base_input` instruction.
+//!
+//! CPMM is Raydium's Anchor constant-product AMM. Unlike v4 it has no order
+//! book, supports Token-2022 mints and records each mint's token program in
+//! the pool.
+
+use super::layout::{anchor_discriminator, read_pubkey, read_u64, read_u8};
+use super::math::mul_div;
+use anyhow::{bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const CPMM_PROGRAM_ID: Pubkey = pubkey!("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C");
+
+/// Offsets of `PoolState::token_0_mint` / `token_1_mint`, for `getProgramAccounts` filters.
+pub const POOL_TOKEN_0_MINT_OFFSET: usize = 168;
+pub const POOL_TOKEN_1_MINT_OFFSET: usize = 200;
+
+pub const FEE_RATE_DENOMINATOR: u64 = 1_000_000;
+
+const AUTH_SEED: &[u8] = b"vault_and_lp_mint_auth_seed";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolState {
+    pub amm_config: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_program: Pubkey,
+    pub token_1_program: Pubkey,
+    pub observation_key: Pubkey,
+    pub status: u8,
+    pub lp_supply: u64,
+    pub protocol_fees_token_0: u64,
+    pub protocol_fees_token_1: u64,
+    pub fund_fees_token_0: u64,
+    pub fund_fees_token_1: u64,
+    pub open_time: u64,
+}
+
+impl PoolState {
+    pub fn discriminator() -> [u8; 8] {
+        anchor_discriminator("account:PoolState")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            bail!("not a Raydium CPMM pool account");
+        }
+        Ok(Self {
+            amm_config: read_pubkey(data, 8)?,
+            token_0_vault: read_pubkey(data, 72)?,
+            token_1_vault: read_pubkey(data, 104)?,
+            lp_mint: read_pubkey(data, 136)?,
+            token_0_mint: read_pubkey(data, POOL_TOKEN_0_MINT_OFFSET)?,
+            token_1_mint: read_pubkey(data, POOL_TOKEN_1_MINT_OFFSET)?,
+            token_0_program: read_pubkey(data, 232)?,
+            token_1_program: read_pubkey(data, 264)?,
+            observation_key: read_pubkey(data, 296)?,
+            status: read_u8(data, 329)?,
+            lp_supply: read_u64(data, 333)?,
+            protocol_fees_token_0: read_u64(data, 341)?,
+            protocol_fees_token_1: read_u64(data, 349)?,
+            fund_fees_token_0: read_u64(data, 357)?,
+            fund_fees_token_1: read_u64(data, 365)?,
+            open_time: read_u64(data, 373)?,
+        })
+    }
+
+    /// Tradable reserves: vault balances less protocol and fund fees not yet collected.
+    pub fn reserves(&self, vault_0_amount: u64, vault_1_amount: u64) -> (u64, u64) {
+        (
+            vault_0_amount.saturating_sub(self.protocol_fees_token_0 + self.fund_fees_token_0),
+            vault_1_amount.saturating_sub(self.protocol_fees_token_1 + self.fund_fees_token_1),
+        )
+    }
+}
+
+/// The fee tier a pool points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmmConfig {
+    /// Over [`FEE_RATE_DENOMINATOR`].
+    pub trade_fee_rate: u64,
+}
+
+impl AmmConfig {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != anchor_discriminator("account:AmmConfig") {
+            bail!("not a Raydium CPMM config account");
+        }
+        Ok(Self { trade_fee_rate: read_u64(data, 12)? })
+    }
+}
+
+pub fn authority() -> Pubkey {
+    Pubkey::find_program_address(&[AUTH_SEED], &CPMM_PROGRAM_ID).0
+}
+
+/// `swap_base_input` as the program computes it: `(amount_out, fee)`. The
+/// trade fee is taken from the input, rounded up. Token-2022 transfer fees
+/// are not included.
+pub fn quote_base_input(amount_in: u64, reserve_in: u64, reserve_out: u64, trade_fee_rate: u64) -> Option<(u64, u64)> {
+    let fee = mul_div(amount_in as u128, trade_fee_rate as u128, FEE_RATE_DENOMINATOR as u128, true)?;
+    let net_in = amount_in as u128 - fee;
+    let amount_out = mul_div(net_in, reserve_out as u128, (reserve_in as u128).checked_add(net_in)?, false)?;
+    Some((u64::try_from(amount_out).ok()?, fee as u64))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    /// Token 0 in, token 1 out.
+    pub zero_for_one: bool,
+}
+
+/// Exact-input swap; `input_account` / `output_account` are the payer's
+/// token accounts for the input and output mints.
+pub fn swap_base_input(
+    pool_address: Pubkey,
+    pool: &PoolState,
+    payer: Pubkey,
+    input_account: Pubkey,
+    output_account: Pubkey,
+    params: &SwapParams,
+) -> Instruction {
+    let (input_vault, output_vault, input_program, output_program, input_mint, output_mint) = if params.zero_for_one {
+        (pool.token_0_vault, pool.token_1_vault, pool.token_0_program, pool.token_1_program, pool.token_0_mint, pool.token_1_mint)
+    } else {
+        (pool.token_1_vault, pool.token_0_vault, pool.token_1_program, pool.token_0_program, pool.token_1_mint, pool.token_0_mint)
+    };
+
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&anchor_discriminator("global:swap_base_input"));
+    data.extend_from_slice(&params.amount_in.to_le_bytes());
+    data.extend_from_slice(&params.minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: CPMM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(authority(), false),
+            AccountMeta::new_readonly(pool.amm_config, false),
+            AccountMeta::new(pool_address, false),
+            AccountMeta::new(input_account, false),
+            AccountMeta::new(output_account, false),
+            AccountMeta::new(input_vault, false),
+            AccountMeta::new(output_vault, false),
+            AccountMeta::new_readonly(input_program, false),
+            AccountMeta::new_readonly(output_program, false),
+            AccountMeta::new_readonly(input_mint, false),
+            AccountMeta::new_readonly(output_mint, false),
+            AccountMeta::new(pool.observation_key, false),
+        ],
+        data,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_pool_state_and_nets_out_uncollected_fees() {
+        let mut data = vec![0u8; 637];
+        data[..8].copy_from_slice(&PoolState::discriminator());
+        let mint_0 = Pubkey::new_unique();
+        data[POOL_TOKEN_0_MINT_OFFSET..POOL_TOKEN_0_MINT_OFFSET + 32].copy_from_slice(mint_0.as_ref());
+        data[341..349].copy_from_slice(&100u64.to_le_bytes());
+        data[357..365].copy_from_slice(&20u64.to_le_bytes());
+
+        let pool = PoolState::decode(&data).unwrap();
+        assert_eq!(pool.token_0_mint, mint_0);
+        assert_eq!(pool.reserves(1_000, 500), (880, 500));
+        assert!(PoolState::decode(&data[..300]).is_err());
+    }
+
+    #[test]
+    fn quote_charges_the_fee_on_input() {
+        // 0.25% fee tier.
+        let (out, fee) = quote_base_input(1_000_000, 1_000_000_000, 2_000_000_000, 2_500).unwrap();
+        assert_eq!(fee, 2_500);
+        assert_eq!(out, (997_500u128 * 2_000_000_000 / 1_000_997_500) as u64);
+        assert_eq!(quote_base_input(1, 1_000, 1_000, 2_500), Some((0, 1)));
+    }
+
+    #[test]
+    fn swap_instruction_layout() {
+        let mut data = vec![0u8; 637];
+        data[..8].copy_from_slice(&PoolState::discriminator());
+        for offset in [8, 72, 104, 136, 168, 200, 232, 264, 296] {
+            data[offset..offset + 32].copy_from_slice(Pubkey::new_unique().as_ref());
+        }
+        let pool = PoolState::decode(&data).unwrap();
+        let payer = Pubkey::new_unique();
+
+        let params = SwapParams { amount_in: 7, minimum_amount_out: 5, zero_for_one: false };
+        let ix = swap_base_input(Pubkey::new_unique(), &pool, payer, Pubkey::new_unique(), Pubkey::new_unique(), &params);
+        assert_eq!(ix.data[..8], anchor_discriminator("global:swap_base_input"));
+        assert_eq!(ix.data[8..], [7u64.to_le_bytes(), 5u64.to_le_bytes()].concat());
+        assert_eq!(ix.accounts.len(), 13);
+        assert!(ix.accounts[0].is_signer && ix.accounts[0].pubkey == payer);
+        // One-for-zero: token 1 goes in.
+        assert_eq!(ix.accounts[6].pubkey, pool.token_1_vault);
+        assert_eq!(ix.accounts[10].pubkey, pool.token_1_mint);
+        assert_eq!(ix.accounts[12].pubkey, pool.observation_key);
+    }
+}
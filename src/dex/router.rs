@@ -0,0 +1,331 @@
+//! Multi-hop routing over discovered pools.
+//!
+//! Pools form an undirected graph keyed by mint. Every simple path of up to
+//! [`MAX_HOPS`] pools from the input mint to the output mint is quoted hop by
+//! hop through the venue, fees included, and the path with the largest
+//! output wins.
+
+use super::DexPool;
+use crate::trade;
+use crate::venue::AmmVenue;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+pub const MAX_HOPS: usize = 3;
+
+/// Quote assets [`Router::discover`] also looks up pools for, so a route can
+/// cross from the input's quote asset to the output's: wSOL and USDC.
+pub const BRIDGE_MINTS: [&str; 2] = [
+    "So11111111111111111111111111111111111111112",
+    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+];
+
+#[derive(Debug, Clone)]
+pub struct Hop {
+    pub pool: DexPool,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+}
+
+impl Route {
+    pub fn amount_in(&self) -> u64 {
+        self.hops.first().map_or(0, |hop| hop.amount_in)
+    }
+
+    pub fn amount_out(&self) -> u64 {
+        self.hops.last().map_or(0, |hop| hop.amount_out)
+    }
+}
+
+pub struct Router<'a> {
+    venue: &'a dyn AmmVenue,
+    pools: Vec<DexPool>,
+}
+
+impl<'a> Router<'a> {
+    pub fn new(venue: &'a dyn AmmVenue, pools: Vec<DexPool>) -> Self {
+        Self { venue, pools }
+    }
+
+    /// Builds the graph from every pool trading any of `mints`, plus the
+    /// [`BRIDGE_MINTS`] pools whose other side is already in the graph. Those
+    /// are the middle hop of a three-hop route, e.g. wSOL/USDC between a
+    /// token quoted in SOL and one quoted in USDC; bridge pools leading
+    /// nowhere are dropped to keep the path search small.
+    pub async fn discover(venue: &'a dyn AmmVenue, mints: &[&str]) -> Result<Self> {
+        let mut seen = HashSet::new();
+        let mut pools = Vec::new();
+        for mint in mints {
+            for pool in venue.find_pools(mint).await? {
+                if seen.insert(pool.address.clone()) {
+                    pools.push(pool);
+                }
+            }
+        }
+
+        let reachable: HashSet<String> = pools.iter().flat_map(|pool| [pool.token_a.clone(), pool.token_b.clone()]).collect();
+        for bridge in BRIDGE_MINTS.into_iter().filter(|bridge| !mints.contains(bridge)) {
+            for pool in venue.find_pools(bridge).await? {
+                let other = if pool.token_a == bridge { &pool.token_b } else { &pool.token_a };
+                if reachable.contains(other) && seen.insert(pool.address.clone()) {
+                    pools.push(pool);
+                }
+            }
+        }
+        Ok(Self::new(venue, pools))
+    }
+
+    /// The route with the largest output for `amount_in`. Paths whose quote
+    /// fails (unsupported venue, range exhausted) are skipped.
+    pub async fn best_route(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<Route> {
+        let mut best: Option<Route> = None;
+        for path in self.paths(input_mint, output_mint) {
+            match self.quote_path(&path, input_mint, amount_in).await {
+                Ok(route) => {
+                    if best.as_ref().is_none_or(|best| route.amount_out() > best.amount_out()) {
+                        best = Some(route);
+                    }
+                }
+                Err(e) => log::debug!("Skipping route via {:?}: {:#}", path, e),
+            }
+        }
+        best.ok_or_else(|| anyhow!("No route from {} to {} within {} hops", input_mint, output_mint, MAX_HOPS))
+    }
+
+    /// Swap instructions for `route`, each preceded by an idempotent ATA
+    /// creation for its output mint under that mint's own token program, so
+    /// it is the account the swap writes to. Each hop is re-quoted for the amount it
+    /// will actually spend, its minimum output is that quote less
+    /// `slippage_bps`, and the next hop spends exactly that minimum so it
+    /// never depends on the previous fill beating its floor.
+    pub async fn instructions(&self, route: &Route, user_wallet: &Pubkey, slippage_bps: u32) -> Result<Vec<Instruction>> {
+        let mut instructions = Vec::with_capacity(route.hops.len() * 2);
+        let mut amount = route.amount_in();
+        for hop in &route.hops {
+            let is_input_a = hop.pool.token_a == hop.input_mint;
+            let (quoted, _) = self.venue.calculate_swap_amounts(&hop.pool, amount, is_input_a).await?;
+            let min_out = trade::min_out(quoted, slippage_bps);
+            if min_out == 0 {
+                bail!("Hop through {} would return nothing for {}", hop.pool.address, amount);
+            }
+
+            let output_mint = Pubkey::from_str(&hop.output_mint)?;
+            let token_program = self.venue.token_program(&hop.output_mint).await?;
+            instructions.push(create_associated_token_account_idempotent(user_wallet, user_wallet, &output_mint, &token_program));
+            instructions.push(
+                self.venue
+                    .create_swap_instruction(&hop.pool, user_wallet, &hop.input_mint, &hop.output_mint, amount, min_out)
+                    .await?,
+            );
+            amount = min_out;
+        }
+        Ok(instructions)
+    }
+
+    /// Quotes `route`'s pools again for a different `amount_in`.
+    pub async fn requote(&self, route: &Route, amount_in: u64) -> Result<Route> {
+        let pools: Vec<&DexPool> = route.hops.iter().map(|hop| &hop.pool).collect();
+        let input_mint = route.hops.first().map_or("", |hop| hop.input_mint.as_str());
+        self.quote_pools(&pools, input_mint, amount_in).await
+    }
+
+    async fn quote_path(&self, path: &[usize], input_mint: &str, amount_in: u64) -> Result<Route> {
+        let pools: Vec<&DexPool> = path.iter().map(|&index| &self.pools[index]).collect();
+        self.quote_pools(&pools, input_mint, amount_in).await
+    }
+
+    async fn quote_pools(&self, pools: &[&DexPool], input_mint: &str, amount_in: u64) -> Result<Route> {
+        let mut hops = Vec::with_capacity(pools.len());
+        let mut mint = input_mint.to_string();
+        let mut amount = amount_in;
+        for &pool in pools {
+            let is_input_a = pool.token_a == mint;
+            let output_mint = if is_input_a { pool.token_b.clone() } else { pool.token_a.clone() };
+            let (amount_out, fee) = self.venue.calculate_swap_amounts(pool, amount, is_input_a).await?;
+            if amount_out == 0 {
+                bail!("pool {} returns nothing for {}", pool.address, amount);
+            }
+            hops.push(Hop {
+                pool: pool.clone(),
+                input_mint: mint,
+                output_mint: output_mint.clone(),
+                amount_in: amount,
+                amount_out,
+                fee,
+            });
+            mint = output_mint;
+            amount = amount_out;
+        }
+        Ok(Route { hops })
+    }
+
+    /// Every simple path (no pool or intermediate mint repeated) of up to
+    /// [`MAX_HOPS`] pools, as indices into `self.pools`.
+    fn paths(&self, from: &str, to: &str) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        let mut visited = vec![from.to_string()];
+        self.extend_paths(from, to, &mut Vec::new(), &mut visited, &mut paths);
+        paths
+    }
+
+    fn extend_paths(&self, mint: &str, to: &str, path: &mut Vec<usize>, visited: &mut Vec<String>, paths: &mut Vec<Vec<usize>>) {
+        if path.len() == MAX_HOPS {
+            return;
+        }
+        for (index, pool) in self.pools.iter().enumerate() {
+            if path.contains(&index) {
+                continue;
+            }
+            let next = if pool.token_a == mint {
+                &pool.token_b
+            } else if pool.token_b == mint {
+                &pool.token_a
+            } else {
+                continue;
+            };
+            path.push(index);
+            if next == to {
+                paths.push(path.clone());
+            } else if !visited.contains(next) {
+                visited.push(next.clone());
+                self.extend_paths(next, to, path, visited, paths);
+                visited.pop();
+            }
+            path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::DexType;
+    use crate::meteora::damm_v2;
+    use crate::mock::MockAmm;
+    use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+    fn pool(address: &str, token_a: &str, token_b: &str, reserve_a: u64, reserve_b: u64) -> DexPool {
+        DexPool {
+            address: address.to_string(),
+            token_a: token_a.to_string(),
+            token_b: token_b.to_string(),
+            reserve_a,
+            reserve_b,
+            fee_numerator: 25,
+            fee_denominator: 10_000,
+            dex_type: DexType::Raydium,
+        }
+    }
+
+    async fn direct_quote(venue: &MockAmm, pool: &DexPool, amount: u64, is_input_a: bool) -> u64 {
+        venue.calculate_swap_amounts(pool, amount, is_input_a).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn prefers_a_deep_two_hop_route_over_a_thin_direct_pool() {
+        let thin = pool("direct", "A", "B", 10_000_000, 10_000_000);
+        let venue = MockAmm::default()
+            .with_pool(thin.clone())
+            .with_pool(pool("a-usdc", "A", "USDC", 1_000_000_000_000, 1_000_000_000_000))
+            .with_pool(pool("usdc-b", "USDC", "B", 1_000_000_000_000, 1_000_000_000_000));
+        let router = Router::discover(&venue, &["A", "B"]).await.unwrap();
+
+        let route = router.best_route("A", "B", 1_000_000).await.unwrap();
+        let direct = direct_quote(&venue, &thin, 1_000_000, true).await;
+
+        assert_eq!(route.hops.len(), 2);
+        assert_eq!(route.hops[0].output_mint, "USDC");
+        assert!(route.amount_out() > direct, "{} vs direct {}", route.amount_out(), direct);
+        let first = direct_quote(&venue, &route.hops[0].pool, 1_000_000, true).await;
+        assert_eq!(route.amount_out(), direct_quote(&venue, &route.hops[1].pool, first, true).await);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_direct_pool_when_it_is_best() {
+        let deep = pool("direct", "A", "B", 1_000_000_000_000, 1_000_000_000_000);
+        let venue = MockAmm::default()
+            .with_pool(deep.clone())
+            .with_pool(pool("a-usdc", "A", "USDC", 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("usdc-b", "USDC", "B", 1_000_000_000, 1_000_000_000));
+        let router = Router::discover(&venue, &["A", "B"]).await.unwrap();
+
+        let route = router.best_route("B", "A", 5_000_000).await.unwrap();
+        assert_eq!(route.hops.len(), 1);
+        assert_eq!(route.amount_out(), direct_quote(&venue, &deep, 5_000_000, false).await);
+    }
+
+    #[tokio::test]
+    async fn finds_three_hop_routes_through_a_bridge_pool() {
+        let [sol, usdc] = BRIDGE_MINTS;
+        let venue = MockAmm::default()
+            .with_pool(pool("a-sol", "A", sol, 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("sol-usdc", sol, usdc, 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("usdc-b", usdc, "B", 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("sol-c", sol, "C", 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("unrelated", "C", "D", 1_000_000_000, 1_000_000_000));
+        let router = Router::discover(&venue, &["A", "B"]).await.unwrap();
+
+        let route = router.best_route("A", "B", 1_000_000).await.unwrap();
+        let mints: Vec<&str> = route.hops.iter().map(|hop| hop.output_mint.as_str()).collect();
+        assert_eq!(mints, [sol, usdc, "B"]);
+        // wSOL/C connects to nothing the input or output trades against.
+        let mut discovered: Vec<&str> = router.pools.iter().map(|pool| pool.address.as_str()).collect();
+        discovered.sort();
+        assert_eq!(discovered, ["a-sol", "sol-usdc", "usdc-b"]);
+        assert!(router.best_route("A", "D", 1_000_000).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn instructions_chain_each_hop_minimum_into_the_next() {
+        let a = Pubkey::new_unique().to_string();
+        let usdc = Pubkey::new_unique().to_string();
+        let b = Pubkey::new_unique().to_string();
+        let venue = MockAmm::default()
+            .with_pool(pool("a-usdc", &a, &usdc, 1_000_000_000, 1_000_000_000))
+            .with_pool(pool("usdc-b", &usdc, &b, 1_000_000_000, 1_000_000_000));
+        let router = Router::discover(&venue, &[&a, &b]).await.unwrap();
+        let route = router.best_route(&a, &b, 1_000_000).await.unwrap();
+
+        let instructions = router.instructions(&route, &Pubkey::new_unique(), 100).await.unwrap();
+        assert_eq!(instructions.len(), 4);
+        let amounts = |ix: &Instruction| {
+            (u64::from_le_bytes(ix.data[..8].try_into().unwrap()), u64::from_le_bytes(ix.data[8..].try_into().unwrap()))
+        };
+        let (first_in, first_min) = amounts(&instructions[1]);
+        let (second_in, second_min) = amounts(&instructions[3]);
+        assert_eq!(first_in, 1_000_000);
+        assert_eq!(first_min, route.hops[0].amount_out * 99 / 100);
+        assert_eq!(second_in, first_min);
+        assert!(second_min < route.amount_out());
+    }
+
+    #[tokio::test]
+    async fn creates_token_2022_output_accounts_under_their_own_program() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let venue = MockAmm::default()
+            .with_pool(pool("a-b", &a.to_string(), &b.to_string(), 1_000_000_000, 1_000_000_000))
+            .with_token_2022_mint(&b.to_string());
+        let router = Router::discover(&venue, &[&a.to_string()]).await.unwrap();
+        let route = router.best_route(&a.to_string(), &b.to_string(), 1_000_000).await.unwrap();
+        let wallet = Pubkey::new_unique();
+
+        let instructions = router.instructions(&route, &wallet, 100).await.unwrap();
+        let create = &instructions[0];
+        let ata = get_associated_token_address_with_program_id(&wallet, &b, &damm_v2::TOKEN_2022_PROGRAM_ID);
+        assert_eq!(create.accounts[1].pubkey, ata);
+        assert_eq!(create.accounts[5].pubkey, damm_v2::TOKEN_2022_PROGRAM_ID);
+        assert_ne!(ata, get_associated_token_address_with_program_id(&wallet, &b, &spl_token::id()));
+    }
+}
@@ -0,0 +1,270 @@
+//! Per-trade choice between the internal router and an external quote
+//! provider, and execution of whichever quotes more.
+//!
+//! Both sources go through the same [`SlippagePolicy`]: the winner's quote
+//! becomes a [`TradePlan`] or the trade is refused. The router wins ties so
+//! an aggregator is only trusted when it is strictly better.
+
+use crate::aggregator::ProviderQuote;
+use crate::dex::router::{Route, Router};
+use crate::store::{FillRecord, Ledger};
+use crate::trade::{self, Fill, Quote, SlippagePolicy, TradePlan};
+use crate::venue::{AmmVenue, QuoteProvider, SolanaApi};
+use anyhow::{anyhow, bail, Result};
+
+/// Where a trade will be filled.
+#[derive(Debug, Clone)]
+pub enum Source {
+    Router(Route),
+    Provider(ProviderQuote),
+}
+
+#[derive(Debug, Clone)]
+pub struct Choice {
+    pub source: Source,
+    pub plan: TradePlan,
+}
+
+pub struct Executor<'a> {
+    venue: &'a dyn AmmVenue,
+    provider: Option<&'a dyn QuoteProvider>,
+    policy: SlippagePolicy,
+}
+
+impl<'a> Executor<'a> {
+    pub fn new(venue: &'a dyn AmmVenue, provider: Option<&'a dyn QuoteProvider>, policy: SlippagePolicy) -> Self {
+        Self { venue, provider, policy }
+    }
+
+    /// Quotes `amount_in` on the router and the provider and plans the
+    /// larger output. Either side failing only removes it from the choice.
+    pub async fn choose(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<Choice> {
+        let router_quote = match self.router_quote(input_mint, output_mint, amount_in).await {
+            Ok(quote) => Some(quote),
+            Err(e) => {
+                log::warn!("Router has no quote for {} -> {}: {:#}", input_mint, output_mint, e);
+                None
+            }
+        };
+        let provider_quote = match self.provider {
+            Some(provider) => match provider.quote(input_mint, output_mint, amount_in, self.policy.slippage_bps).await {
+                Ok(quote) => Some(quote),
+                Err(e) => {
+                    log::warn!("{} has no quote for {} -> {}: {:#}", provider.name(), input_mint, output_mint, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let (source, quote) = match (router_quote, provider_quote) {
+            (Some((route, quote)), Some(external)) => {
+                let delta = external.amount_out as i128 - route.amount_out() as i128;
+                log::info!(
+                    "Router {} vs {} {} for {}: provider {:+} ({:+.2} bps)",
+                    route.amount_out(),
+                    self.provider_name(),
+                    external.amount_out,
+                    amount_in,
+                    delta,
+                    delta as f64 * 10_000.0 / route.amount_out().max(1) as f64
+                );
+                if external.amount_out > route.amount_out() {
+                    provider_choice(external)
+                } else {
+                    (Source::Router(route), quote)
+                }
+            }
+            (Some((route, quote)), None) => (Source::Router(route), quote),
+            (None, Some(external)) => provider_choice(external),
+            (None, None) => bail!("No quote for {} -> {} from the router or a provider", input_mint, output_mint),
+        };
+        Ok(Choice { source, plan: self.policy.plan(quote)? })
+    }
+
+    /// Executes `choice` from `solana_client`'s wallet and records the fill.
+    /// The actual output is what the confirmed transaction added to the
+    /// wallet's `output_mint` balance; SOL is traded wrapped on either
+    /// source, so a SOL output lands in the wSOL account.
+    pub async fn execute(
+        &self,
+        choice: &Choice,
+        input_mint: &str,
+        output_mint: &str,
+        solana_client: &dyn SolanaApi,
+        ledger: &Ledger,
+    ) -> Result<FillRecord> {
+        let wallet = solana_client.wallet_pubkey();
+        let (venue, pool, signature) = match &choice.source {
+            Source::Router(route) => {
+                let router = Router::new(self.venue, route.hops.iter().map(|hop| hop.pool.clone()).collect());
+                let instructions = router.instructions(route, &wallet, choice.plan.slippage_bps).await?;
+                let signature = solana_client.send_instructions(&instructions).await?;
+                let pools: Vec<&str> = route.hops.iter().map(|hop| hop.pool.address.as_str()).collect();
+                ("router".to_string(), pools.join(">"), signature)
+            }
+            Source::Provider(quote) => {
+                let provider = self.provider.ok_or_else(|| anyhow!("no quote provider configured"))?;
+                // The aggregator enforces its own threshold; never accept one looser than ours.
+                if quote.other_amount_threshold < choice.plan.min_out {
+                    bail!(
+                        "{} would accept {} out, below the {} minimum; not signing",
+                        provider.name(),
+                        quote.other_amount_threshold,
+                        choice.plan.min_out
+                    );
+                }
+                let transaction = provider.swap_transaction(quote, &wallet).await?;
+                let signature = solana_client.sign_and_send_serialized(&transaction).await?;
+                (provider.name().to_string(), quote.route.join(">"), signature)
+            }
+        };
+        let received = solana_client.token_balance_change(&signature, output_mint).await?;
+
+        trade::record_fill(
+            ledger,
+            &Fill {
+                venue: &venue,
+                pool: &pool,
+                input_mint,
+                output_mint,
+                plan: &choice.plan,
+                actual_out: u64::try_from(received.max(0)).unwrap_or(u64::MAX),
+                signature: &signature.to_string(),
+            },
+        )
+    }
+
+    fn provider_name(&self) -> &str {
+        self.provider.map_or("provider", |provider| provider.name())
+    }
+
+    /// The router's best route and its quote, price impact measured on the
+    /// same path at 0.1% of the size.
+    async fn router_quote(&self, input_mint: &str, output_mint: &str, amount_in: u64) -> Result<(Route, Quote)> {
+        let router = Router::discover(self.venue, &[input_mint, output_mint]).await?;
+        let route = router.best_route(input_mint, output_mint, amount_in).await?;
+        let probe_in = amount_in / 1_000;
+        let probe_out = if probe_in > 0 { router.requote(&route, probe_in).await?.amount_out() } else { 0 };
+        let quote = Quote {
+            amount_in,
+            amount_out: route.amount_out(),
+            fee: route.hops.iter().map(|hop| hop.fee).sum(),
+            price_impact_bps: trade::price_impact_bps(amount_in, route.amount_out(), probe_in, probe_out),
+        };
+        Ok((route, quote))
+    }
+}
+
+fn provider_choice(external: ProviderQuote) -> (Source, Quote) {
+    let quote = Quote {
+        amount_in: external.amount_in,
+        amount_out: external.amount_out,
+        fee: 0,
+        price_impact_bps: external.price_impact_bps,
+    };
+    (Source::Provider(external), quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregator::tests::{quote_body, serve};
+    use crate::aggregator::{AggregatorClient, DEFAULT_TIMEOUT};
+    use crate::dex::{DexPool, DexType};
+    use crate::mock::{MockAmm, MockSolana};
+    use base64::Engine;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn amm() -> MockAmm {
+        MockAmm::default().with_pool(DexPool {
+            address: "Pool1".to_string(),
+            token_a: SOL.to_string(),
+            token_b: USDC.to_string(),
+            reserve_a: 1_000_000_000_000,
+            reserve_b: 150_000_000_000,
+            fee_numerator: 25,
+            fee_denominator: 10_000,
+            dex_type: DexType::Raydium,
+        })
+    }
+
+    fn policy() -> SlippagePolicy {
+        SlippagePolicy { slippage_bps: 50, max_price_impact_bps: 500 }
+    }
+
+    #[tokio::test]
+    async fn takes_the_provider_when_it_quotes_more() {
+        let amm = amm();
+        let router_out = Executor::new(&amm, None, policy()).choose(SOL, USDC, 1_000_000_000).await.unwrap().plan.quote.amount_out;
+
+        let transaction = base64::engine::general_purpose::STANDARD.encode([9u8; 4]);
+        let (base_url, _server) = serve(vec![
+            (200, quote_body(1_000_000_000, router_out + 1_000, router_out)),
+            (200, serde_json::json!({ "swapTransaction": transaction }).to_string()),
+        ])
+        .await;
+        let provider = AggregatorClient::new(&base_url, DEFAULT_TIMEOUT).unwrap();
+        let executor = Executor::new(&amm, Some(&provider), policy());
+
+        let choice = executor.choose(SOL, USDC, 1_000_000_000).await.unwrap();
+        assert!(matches!(choice.source, Source::Provider(_)));
+        assert_eq!(choice.plan.quote.amount_out, router_out + 1_000);
+
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(2_000_000_000);
+        let record = executor.execute(&choice, SOL, USDC, &solana, &Ledger::new(dir.path())).await.unwrap();
+        assert_eq!(solana.sent(), [bs58::encode([9u8; 4]).into_string()]);
+        assert_eq!(record.venue, base_url);
+        assert_eq!(record.pool, "Whirlpool");
+    }
+
+    #[tokio::test]
+    async fn sol_output_is_measured_in_the_wrapped_sol_account() {
+        let amm = amm();
+        let transaction = base64::engine::general_purpose::STANDARD.encode([7u8; 4]);
+        let (base_url, server) = serve(vec![
+            (200, quote_body(150_000_000, 2_000_000_000, 1_990_000_000)),
+            (200, serde_json::json!({ "swapTransaction": transaction }).to_string()),
+        ])
+        .await;
+        let provider = AggregatorClient::new(&base_url, DEFAULT_TIMEOUT).unwrap();
+        let executor = Executor::new(&amm, Some(&provider), policy());
+        let choice = executor.choose(USDC, SOL, 150_000_000).await.unwrap();
+        assert!(matches!(choice.source, Source::Provider(_)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(2_000_000_000)
+            .with_balance_change(USDC, -150_000_000)
+            .with_balance_change(SOL, 1_995_000_000);
+        let record = executor.execute(&choice, USDC, SOL, &solana, &Ledger::new(dir.path())).await.unwrap();
+        assert_eq!((record.output_mint.as_str(), record.actual_out), (SOL, 1_995_000_000));
+
+        let requests = server.await.unwrap();
+        assert!(requests[1].contains(r#""wrapAndUnwrapSol":false"#), "{}", requests[1]);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_router_when_the_provider_is_worse_or_down() {
+        let amm = amm();
+        let (base_url, _server) = serve(vec![(200, quote_body(1_000_000_000, 1, 1)), (503, "{}".to_string())]).await;
+        let provider = AggregatorClient::new(&base_url, DEFAULT_TIMEOUT).unwrap();
+        let executor = Executor::new(&amm, Some(&provider), policy());
+
+        let choice = executor.choose(SOL, USDC, 1_000_000_000).await.unwrap();
+        let Source::Router(route) = &choice.source else { panic!("expected the router") };
+        assert_eq!(route.hops[0].pool.address, "Pool1");
+        assert_eq!(choice.plan.min_out, trade::min_out(route.amount_out(), 50));
+
+        // The provider now errors; the router still answers.
+        assert!(matches!(executor.choose(SOL, USDC, 1_000_000_000).await.unwrap().source, Source::Router(_)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let solana = MockSolana::with_balance(2_000_000_000);
+        let record = executor.execute(&choice, SOL, USDC, &solana, &Ledger::new(dir.path())).await.unwrap();
+        assert_eq!(solana.sent_instructions.lock().unwrap().len(), 1);
+        assert_eq!((record.venue.as_str(), record.pool.as_str()), ("router", "Pool1"));
+    }
+}
@@ -2,14 +2,31 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
 
+mod aggregator;
 mod config;
 mod commands;
 mod solana;
 mod bagsfm;
 mod dex;
+mod executor;
 mod meteora;
+mod numeric;
+mod store;
+mod trade;
+mod venue;
+#[cfg(test)]
+mod mock;
 
-use commands::{bundler, sniper, market_maker, tracker, export};
+use bagsfm::BagsFmClient;
+use commands::{bundler, sniper, market_maker, tracker, export, watch_launches, fees, liquidity, lp, swap, curve_progress};
+use aggregator::AggregatorClient;
+use dex::DexClient;
+use executor::Executor;
+use meteora::MeteoraClient;
+use solana::SolanaClient;
+use std::sync::Arc;
+use store::Ledger;
+use trade::SlippagePolicy;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "ReoswellEcho — bags.fm trading weapon (bundler/sniper/mm/track/export)", long_about = None)]
@@ -17,6 +34,11 @@ struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
+    /// Allow fabricated placeholder data when live sources are unavailable.
+    /// Never enable this for real trading.
+    #[arg(long, global = true)]
+    demo: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -30,14 +52,31 @@ enum Commands {
         dry_run: bool,
     },
     Sniper {
-        #[arg(short, long)]
-        token: String,
+        /// Token mint to snipe; its live Meteora DBC pool is looked up on-chain
+        #[arg(short, long, visible_alias = "mint", required_unless_present = "watch")]
+        token: Option<String>,
+        /// Whole units of the curve's quote token to spend (SOL on SOL-quoted curves)
         #[arg(long, default_value_t = 1.0)]
         budget_sol: f64,
+        /// Wait for the next bags.fm launch and snipe it instead of --token
+        #[arg(long, conflicts_with = "token")]
+        watch: bool,
+    },
+    /// Sell a sniped token on its DBC curve, or on its DAMM v2 pool once graduated
+    SniperExit {
+        #[arg(short, long, visible_alias = "mint")]
+        token: String,
+        /// Raw token amount to sell; defaults to the whole balance
+        #[arg(long)]
+        amount: Option<u64>,
     },
     MarketMaker {
-        #[arg(short, long)]
-        pair: String,
+        /// Pool address to quote on
+        #[arg(short, long, required_unless_present = "mint", conflicts_with = "mint")]
+        pair: Option<String>,
+        /// Token mint; quotes on its deepest pool across the supported DEXes
+        #[arg(long)]
+        mint: Option<String>,
         #[arg(long, default_value_t = 100)]
         spread_bps: u32,
     },
@@ -49,6 +88,130 @@ enum Commands {
         #[arg(short, long, default_value = "out/trades.csv")]
         out: String,
     },
+    /// bags.fm creator fees for tokens we launched
+    Fees {
+        #[command(subcommand)]
+        action: FeesAction,
+    },
+    /// Raydium AMM v4 and Meteora DAMM v2 liquidity
+    Liquidity {
+        #[command(subcommand)]
+        action: LiquidityAction,
+    },
+    /// Meteora DAMM v2 LP positions
+    Lp {
+        #[command(subcommand)]
+        action: LpAction,
+    },
+    /// Swap through the best of the internal router and QUOTE_API_URL
+    Swap {
+        #[arg(long)]
+        input: String,
+        #[arg(long)]
+        output: String,
+        /// Input amount in base units
+        #[arg(long)]
+        amount: u64,
+        /// Compare quotes without signing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    WatchLaunches {
+        /// Stop after this many launches
+        #[arg(long)]
+        max: Option<usize>,
+        /// Only report launches that happen after startup
+        #[arg(long)]
+        skip_backlog: bool,
+    },
+    /// Show a token's bonding-curve progress toward migration
+    CurveProgress {
+        /// Token mint launched on Meteora DBC
+        mint: String,
+        /// Keep watching until the curve completes and its DAMM v2 pool appears
+        #[arg(long)]
+        watch: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum LpAction {
+    /// Show unclaimed LP fees per position, optionally claiming them
+    Fees {
+        /// Only this position
+        #[arg(long)]
+        position: Option<String>,
+        /// Claim every position with fees and record the income
+        #[arg(long)]
+        claim: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum FeesAction {
+    /// Show claimable fees per launched mint
+    List,
+    /// Build and submit fee claim transactions
+    Claim {
+        #[arg(long, required_unless_present = "all")]
+        mint: Option<String>,
+        #[arg(long, conflicts_with = "mint")]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum LiquidityAction {
+    /// Deposit both tokens at the pool's current ratio
+    Add {
+        #[arg(long)]
+        pool: String,
+        /// Most token A to deposit, in base units
+        #[arg(long)]
+        amount_a: u64,
+        /// Most token B to deposit, in base units
+        #[arg(long)]
+        amount_b: u64,
+        /// DAMM v2 position to deposit into
+        #[arg(long)]
+        position: Option<String>,
+    },
+    /// Withdraw LP tokens (Raydium) or position liquidity (DAMM v2)
+    Remove {
+        #[arg(long)]
+        pool: String,
+        #[arg(long, required_unless_present = "all")]
+        lp_amount: Option<u128>,
+        #[arg(long, conflicts_with = "lp_amount")]
+        all: bool,
+        /// DAMM v2 position to withdraw from
+        #[arg(long)]
+        position: Option<String>,
+    },
+    /// Open a new DAMM v2 position and deposit into it
+    Open {
+        #[arg(long)]
+        pool: String,
+        /// Most token A to deposit, in base units
+        #[arg(long)]
+        amount_a: u64,
+        /// Most token B to deposit, in base units
+        #[arg(long)]
+        amount_b: u64,
+    },
+    /// Withdraw everything from a DAMM v2 position and close it
+    Close {
+        #[arg(long)]
+        position: String,
+    },
+    /// Show the pool and the wallet's share of it
+    Show {
+        #[arg(long)]
+        pool: String,
+        /// DAMM v2 position to report on
+        #[arg(long)]
+        position: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -64,13 +227,72 @@ async fn main() -> Result<()> {
 
     let cfg = config::Config::from_env()?;
     log::info!("Loaded config: {:?}", cfg.safe());
+    if cli.demo {
+        log::warn!("Demo mode enabled: placeholder data may be substituted for live data");
+    }
+
+    let solana = || SolanaClient::new(&cfg.rpc_url, &cfg.wallet_path);
+    let bagsfm = || BagsFmClient::from_config(&cfg);
+    let ledger = Ledger::from_config(&cfg);
 
     match cli.command {
-        Commands::Bundler { plan, dry_run } => bundler::run(&cfg, &plan, dry_run).await?,
-        Commands::Sniper { token, budget_sol } => sniper::run(&cfg, &token, budget_sol).await?,
-        Commands::MarketMaker { pair, spread_bps } => market_maker::run(&cfg, &pair, spread_bps).await?,
-        Commands::Track { wallet } => tracker::run(&cfg, &wallet).await?,
-        Commands::Export { out } => export::run(&cfg, &out).await?,
+        Commands::Bundler { plan, dry_run } => {
+            bundler::run(&cfg, &plan, dry_run, &solana()?, &bagsfm()?, &MeteoraClient::new(&cfg.rpc_url)?, &ledger).await?
+        }
+        Commands::Sniper { token, budget_sol, watch } => {
+            let token = if watch {
+                sniper::TargetToken::NextLaunch
+            } else {
+                sniper::TargetToken::Mint(token.as_deref().ok_or_else(|| anyhow::anyhow!("--token or --watch is required"))?)
+            };
+            let target = sniper::Target { token, budget_sol };
+            sniper::run(&cfg, target, cli.demo, &solana()?, Arc::new(bagsfm()?), &MeteoraClient::new(&cfg.rpc_url)?, &ledger).await?
+        }
+        Commands::SniperExit { token, amount } => {
+            sniper::exit(&cfg, &token, amount, &solana()?, Arc::new(MeteoraClient::new(&cfg.rpc_url)?), &ledger).await?
+        }
+        Commands::MarketMaker { pair, mint, spread_bps } => {
+            let meteora = Arc::new(MeteoraClient::new(&cfg.rpc_url)?);
+            market_maker::run(&cfg, pair.as_deref(), mint.as_deref(), spread_bps, &DexClient::new(&cfg.rpc_url)?, meteora).await?
+        }
+        Commands::Track { wallet } => tracker::run(&cfg, &wallet, cli.demo, &solana()?, &bagsfm()?).await?,
+        Commands::Export { out } => export::run(&cfg, &out, cli.demo, &solana()?, &bagsfm()?, &ledger).await?,
+        Commands::Fees { action: FeesAction::List } => fees::list(&cfg, &solana()?, &bagsfm()?, &ledger).await?,
+        Commands::Fees { action: FeesAction::Claim { mint, all } } => {
+            fees::claim(&cfg, mint.as_deref(), all, &solana()?, &bagsfm()?, &ledger).await?
+        }
+        Commands::Liquidity { action: LiquidityAction::Add { pool, amount_a, amount_b, position } } => {
+            liquidity::add(&cfg, &pool, amount_a, amount_b, position.as_deref(), &solana()?, &DexClient::new(&cfg.rpc_url)?).await?
+        }
+        Commands::Liquidity { action: LiquidityAction::Remove { pool, lp_amount, all, position } } => {
+            liquidity::remove(&cfg, &pool, lp_amount, all, position.as_deref(), &solana()?, &DexClient::new(&cfg.rpc_url)?).await?
+        }
+        Commands::Liquidity { action: LiquidityAction::Open { pool, amount_a, amount_b } } => {
+            liquidity::open(&cfg, &pool, amount_a, amount_b, &solana()?, &MeteoraClient::new(&cfg.rpc_url)?).await?
+        }
+        Commands::Liquidity { action: LiquidityAction::Close { position } } => {
+            liquidity::close(&cfg, &position, &solana()?, &MeteoraClient::new(&cfg.rpc_url)?).await?
+        }
+        Commands::Liquidity { action: LiquidityAction::Show { pool, position } } => {
+            liquidity::show(&cfg, &pool, position.as_deref(), &solana()?, &DexClient::new(&cfg.rpc_url)?).await?
+        }
+        Commands::Lp { action: LpAction::Fees { position, claim } } => {
+            lp::fees(&cfg, position.as_deref(), claim, &solana()?, &MeteoraClient::new(&cfg.rpc_url)?, &ledger).await?
+        }
+        Commands::Swap { input, output, amount, dry_run } => {
+            let dex = DexClient::new(&cfg.rpc_url)?;
+            let provider = cfg.quote_api_url.as_deref()
+                .map(|url| AggregatorClient::new(url, aggregator::DEFAULT_TIMEOUT))
+                .transpose()?;
+            let executor = Executor::new(&dex, provider.as_ref().map(|p| p as &dyn venue::QuoteProvider), SlippagePolicy::from_config(&cfg));
+            swap::run(&executor, &input, &output, amount, dry_run, &solana()?, &ledger).await?
+        }
+        Commands::WatchLaunches { max, skip_backlog } => {
+            watch_launches::run(&cfg, max, skip_backlog, Arc::new(bagsfm()?)).await?
+        }
+        Commands::CurveProgress { mint, watch } => {
+            curve_progress::run(&cfg, &mint, watch, Arc::new(MeteoraClient::new(&cfg.rpc_url)?)).await?
+        }
     }
 
     Ok(())
@@ -1,149 +0,0 @@
-use anyhow::{anyhow, Result};
-use solana_sdk::pubkey::Pubkey;
-use std::str::FromStr;
-
-#[derive(Debug, Clone)]
-pub struct MeteoraDbcPool {
-    pub address: String,
-    pub token_mint: String,
-    pub base_token: String,
-    pub current_price: f64,
-    pub total_supply: u64,
-    pub curve_type: DbcCurveType,
-    pub status: DbcStatus,
-}
-
-#[derive(Debug, Clone)]
-pub enum DbcCurveType {
-    Linear,
-    Exponential,
-    Logarithmic,
-}
-
-#[derive(Debug, Clone)]
-pub enum DbcStatus {
-    Active,
-    Migrated,
-    Paused,
-}
-
-#[derive(Debug, Clone)]
-pub struct MeteoraDammPool {
-    pub address: String,
-    pub token_a: String,
-    pub token_b: String,
-    pub reserve_a: u64,
-    pub reserve_b: u64,
-    pub fee_rate: f64,
-    pub amplification: f64,
-}
-
-pub struct MeteoraClient {
-    dbc_program_id: Pubkey,
-    damm_program_id: Pubkey,
-}
-
-impl MeteoraClient {
-    pub fn new() -> Result<Self> {
-        Ok(Self {
-            dbc_program_id: Pubkey::from_str("DBCAwfnVqxqHMjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8")?,
-            damm_program_id: Pubkey::from_str("DMMWcVfJTPT6WM5U2J5otPvEonrCJ5FykfCbrbwVPua6")?,
-        })
-    }
-
-    pub async fn create_dbc_pool(
-        &self,
-        token_mint: &str,
-        base_token: &str,
-        initial_price: f64,
-        total_supply: u64,
-        curve_type: DbcCurveType,
-    ) -> Result<MeteoraDbcPool> {
-        log::info!("Creating Meteora DBC pool for token: {}", token_mint);
-        
-        let pool = MeteoraDbcPool {
-            address: format!("DbcPool{}", token_mint),
-            token_mint: token_mint.to_string(),
-            base_token: base_token.to_string(),
-            current_price: initial_price,
-            total_supply,
-            curve_type,
-            status: DbcStatus::Active,
-        };
-
-        log::info!("DBC pool created: {}", pool.address);
-        Ok(pool)
-    }
-
-    pub async fn buy_from_dbc(
-        &self,
-        pool: &MeteoraDbcPool,
-        amount_base: f64,
-    ) -> Result<(u64, f64)> {
-        log::info!("Buying tokens from DBC pool: {}", pool.address);
-        
-        let tokens_received = self.calculate_dbc_buy_amount(pool, amount_base)?;
-        let price_impact = self.calculate_price_impact(pool, amount_base)?;
-
-        Ok((tokens_received, price_impact))
-    }
-
-    pub async fn migrate_to_damm(
-        &self,
-        dbc_pool: &MeteoraDbcPool,
-        target_amplification: f64,
-    ) -> Result<MeteoraDammPool> {
-        log::info!("Migrating DBC pool to DAMM v2: {}", dbc_pool.address);
-
-        let damm_pool = MeteoraDammPool {
-            address: format!("DammPool{}", dbc_pool.token_mint),
-            token_a: dbc_pool.base_token.clone(),
-            token_b: dbc_pool.token_mint.clone(),
-            reserve_a: 100_000_000_000,
-            reserve_b: 1_000_000_000_000,
-            fee_rate: 0.0025,
-            amplification: target_amplification,
-        };
-
-        log::info!("Migration completed. New DAMM pool: {}", damm_pool.address);
-        Ok(damm_pool)
-    }
-
-    fn calculate_dbc_buy_amount(&self, pool: &MeteoraDbcPool, amount_base: f64) -> Result<u64> {
-        match pool.curve_type {
-            DbcCurveType::Linear => {
-                let tokens = (amount_base / pool.current_price) as u64;
-                Ok(tokens)
-            },
-            DbcCurveType::Exponential => {
-                let tokens = (amount_base / pool.current_price * 0.8) as u64;
-                Ok(tokens)
-            },
-            DbcCurveType::Logarithmic => {
-                let tokens = (amount_base / pool.current_price * 0.9) as u64;
-                Ok(tokens)
-            },
-        }
-    }
-
-    fn calculate_price_impact(&self, pool: &MeteoraDbcPool, amount_base: f64) -> Result<f64> {
-        match pool.curve_type {
-            DbcCurveType::Linear => Ok(amount_base / 1000.0),
-            DbcCurveType::Exponential => Ok(amount_base / 100.0),
-            DbcCurveType::Logarithmic => Ok(amount_base / 500.0),
-        }
-    }
-
-    pub async fn get_dbc_pool_info(&self, pool_address: &str) -> Result<MeteoraDbcPool> {
-        // In real implementation, this would query the Meteora API or blockchain
-        Ok(MeteoraDbcPool {
-            address: pool_address.to_string(),
-            token_mint: "DemoToken123".to_string(),
-            base_token: "SOL".to_string(),
-            current_price: 0.001,
-            total_supply: 1_000_000_000,
-            curve_type: DbcCurveType::Linear,
-            status: crate::meteora::DbcStatus::Active,
-        })
-    }
-}
@@ -0,0 +1,676 @@
+//! Meteora DAMM v2 (`cp-amm`) account layouts, swap and position instructions.
+//!
+//! DAMM v2 has no LP mint: liquidity lives in positions, each owned by
+//! whoever holds the position's NFT. The pool's `liquidity` is the total
+//! across positions and plays the role of LP supply.
+
+use super::fee::{ActivationType, BaseFee, DynamicFee, FeeClock, FeeSchedule, FeeSchedulerMode};
+use crate::dex::layout::{anchor_discriminator, read_pubkey, read_u128, read_u16, read_u256, read_u32, read_u64, read_u8};
+use crate::dex::math::U256;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const DAMM_V2_PROGRAM_ID: Pubkey = pubkey!("cpamdpZCGKUy5JxQXB4dcpGPiikHawvSWAd6mEn1sGG");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Offsets of `Pool::token_a_mint` / `token_b_mint` / `creator`, for `getProgramAccounts` filters.
+pub const POOL_TOKEN_A_MINT_OFFSET: usize = 168;
+pub const POOL_TOKEN_B_MINT_OFFSET: usize = 200;
+pub const POOL_CREATOR_OFFSET: usize = 648;
+
+pub use super::fee::FEE_DENOMINATOR;
+
+/// Fee-per-liquidity accumulators carry 128 fractional bits.
+const LIQUIDITY_SCALE: u32 = 128;
+
+/// A DAMM v2 pool: a single concentrated range `[sqrt_min_price, sqrt_max_price]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pool {
+    pub fees: FeeSchedule,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub liquidity: u128,
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
+    pub sqrt_price: u128,
+    pub pool_status: u8,
+    /// 0 for SPL Token mints, 1 for Token-2022.
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    pub collect_fee_mode: CollectFeeMode,
+    /// LP fees earned per unit of liquidity since the pool opened.
+    pub fee_a_per_liquidity: U256,
+    pub fee_b_per_liquidity: U256,
+    pub creator: Pubkey,
+}
+
+/// Which token trading fees are charged in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectFeeMode {
+    /// Fees are taken from the output token, whichever it is.
+    BothToken,
+    /// Fees are always taken in token B: from the input on B→A, the output on A→B.
+    OnlyB,
+}
+
+impl Pool {
+    pub fn discriminator() -> [u8; 8] {
+        anchor_discriminator("account:Pool")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            bail!("not a Meteora DAMM v2 pool account");
+        }
+        let dynamic = if read_u8(data, 56)? != 0 {
+            Some(DynamicFee {
+                max_volatility_accumulator: read_u32(data, 64)?,
+                variable_fee_control: read_u32(data, 68)?,
+                bin_step: read_u16(data, 72)?,
+                filter_period: read_u16(data, 74)?,
+                decay_period: read_u16(data, 76)?,
+                reduction_factor: read_u16(data, 78)?,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            fees: FeeSchedule {
+                base: BaseFee {
+                    cliff_fee_numerator: read_u64(data, 8)?,
+                    mode: FeeSchedulerMode::from_u8(read_u8(data, 16)?)?,
+                    number_of_period: read_u16(data, 22)?,
+                    period_frequency: read_u64(data, 24)?,
+                    reduction_factor: read_u64(data, 32)?,
+                },
+                dynamic,
+                volatility_accumulator: read_u128(data, 120)?,
+                last_volatility_update: read_u64(data, 80)?,
+                activation_point: read_u64(data, 472)?,
+                activation_type: ActivationType::from_u8(read_u8(data, 480)?)?,
+            },
+            token_a_mint: read_pubkey(data, POOL_TOKEN_A_MINT_OFFSET)?,
+            token_b_mint: read_pubkey(data, POOL_TOKEN_B_MINT_OFFSET)?,
+            token_a_vault: read_pubkey(data, 232)?,
+            token_b_vault: read_pubkey(data, 264)?,
+            liquidity: read_u128(data, 360)?,
+            sqrt_min_price: read_u128(data, 424)?,
+            sqrt_max_price: read_u128(data, 440)?,
+            sqrt_price: read_u128(data, 456)?,
+            pool_status: read_u8(data, 481)?,
+            token_a_flag: read_u8(data, 482)?,
+            token_b_flag: read_u8(data, 483)?,
+            collect_fee_mode: match read_u8(data, 484)? {
+                0 => CollectFeeMode::BothToken,
+                1 => CollectFeeMode::OnlyB,
+                other => bail!("unknown DAMM v2 collect_fee_mode {}", other),
+            },
+            fee_a_per_liquidity: read_u256(data, 488)?,
+            fee_b_per_liquidity: read_u256(data, 520)?,
+            creator: read_pubkey(data, POOL_CREATOR_OFFSET)?,
+        })
+    }
+
+    /// Whether this is the pool a DBC curve selling `base_mint` for
+    /// `quote_mint` migrated into. Migration creates it signed by the DBC
+    /// program's pool authority with the token as A and the quote as B;
+    /// anyone can open other DAMM v2 pools for the same mint.
+    pub fn is_dbc_migration_of(&self, base_mint: &Pubkey, quote_mint: &Pubkey) -> bool {
+        self.creator == super::dbc::pool_authority()
+            && self.token_a_mint == *base_mint
+            && self.token_b_mint == *quote_mint
+    }
+
+    /// Exact-input quote at the fee in force at `now`: `(amount_out, fee)`.
+    /// DAMM v2 liquidity is Q64-scaled, so token B deltas shift by 128 bits.
+    pub fn quote_exact_in(&self, amount_in: u64, a_to_b: bool, now: FeeClock) -> Result<(u64, u64)> {
+        let fee_on_input = self.collect_fee_mode == CollectFeeMode::OnlyB && !a_to_b;
+        let fee = self.fees.at(now);
+
+        let (net_in, input_fee) = if fee_on_input {
+            let fee = fee.fee_on(amount_in);
+            (amount_in - fee, fee)
+        } else {
+            (amount_in, 0)
+        };
+
+        let gross_out = if a_to_b { self.swap_a_to_b(net_in)? } else { self.swap_b_to_a(net_in)? };
+        if fee_on_input {
+            Ok((gross_out, input_fee))
+        } else {
+            let fee = fee.fee_on(gross_out);
+            Ok((gross_out - fee, fee))
+        }
+    }
+
+    pub fn token_a_program(&self) -> Pubkey {
+        token_program(self.token_a_flag)
+    }
+
+    pub fn token_b_program(&self) -> Pubkey {
+        token_program(self.token_b_flag)
+    }
+
+    /// The most liquidity `max_a` / `max_b` can add at the current price.
+    /// Below the range only token A is needed, above it only token B.
+    pub fn liquidity_for_amounts(&self, max_a: u64, max_b: u64) -> Result<u128> {
+        // L_a = amount_a * sqrt_p * sqrt_max / (sqrt_max - sqrt_p)
+        let from_a = if self.sqrt_price < self.sqrt_max_price {
+            U256::mul(self.sqrt_price, self.sqrt_max_price)
+                .div_rem((self.sqrt_max_price - self.sqrt_price).into())
+                .and_then(|(per_unit, _)| per_unit.to_u128())
+                .and_then(|per_unit| U256::mul(max_a as u128, per_unit).to_u128())
+                .unwrap_or(u128::MAX)
+        } else {
+            u128::MAX
+        };
+        // L_b = (amount_b << 128) / (sqrt_p - sqrt_min)
+        let from_b = if self.sqrt_price > self.sqrt_min_price {
+            U256::from(max_b as u128)
+                .checked_shl(128)
+                .and_then(|shifted| shifted.div_rem((self.sqrt_price - self.sqrt_min_price).into()))
+                .and_then(|(liquidity, _)| liquidity.to_u128())
+                .unwrap_or(u128::MAX)
+        } else {
+            u128::MAX
+        };
+        let liquidity = from_a.min(from_b);
+        if liquidity == u128::MAX {
+            bail!("pool price is outside its own range");
+        }
+        Ok(liquidity)
+    }
+
+    /// Token amounts backing `liquidity`: rounded up when depositing, down
+    /// when withdrawing, as the program does.
+    pub fn amounts_for_liquidity(&self, liquidity: u128, round_up: bool) -> Result<(u64, u64)> {
+        let overflow = || anyhow!("DAMM v2 liquidity overflow");
+        let to_u64 = |amount: U256| amount.to_u128().and_then(|amount| u64::try_from(amount).ok()).ok_or_else(overflow);
+
+        let a = U256::mul(liquidity, self.sqrt_max_price - self.sqrt_price);
+        let a_denominator = U256::mul(self.sqrt_price, self.sqrt_max_price);
+        let a = if round_up { a.div_round_up(a_denominator) } else { a.div_rem(a_denominator).map(|(q, _)| q) };
+
+        let b = U256::mul(liquidity, self.sqrt_price - self.sqrt_min_price);
+        let b = if round_up {
+            b.div_round_up(U256::from(1u128).checked_shl(128).ok_or_else(overflow)?)
+        } else {
+            Some(b.shr(128))
+        };
+        Ok((to_u64(a.ok_or_else(overflow)?)?, to_u64(b.ok_or_else(overflow)?)?))
+    }
+
+    fn swap_a_to_b(&self, amount_a: u64) -> Result<u64> {
+        let overflow = || anyhow!("DAMM v2 swap overflow");
+        // sqrt_p' = L * sqrt_p / (L + amount * sqrt_p), rounded up.
+        let numerator = U256::mul(self.liquidity, self.sqrt_price);
+        let denominator = U256::from(self.liquidity)
+            .checked_add(U256::mul(amount_a as u128, self.sqrt_price))
+            .ok_or_else(overflow)?;
+        let next = numerator.div_round_up(denominator).and_then(U256::to_u128).ok_or_else(overflow)?;
+        if next < self.sqrt_min_price {
+            bail!("swap exceeds the pool's price range");
+        }
+        let out = U256::mul(self.liquidity, self.sqrt_price - next).shr(128);
+        out.to_u128().and_then(|out| u64::try_from(out).ok()).ok_or_else(overflow)
+    }
+
+    fn swap_b_to_a(&self, amount_b: u64) -> Result<u64> {
+        let overflow = || anyhow!("DAMM v2 swap overflow");
+        // sqrt_p' = sqrt_p + (amount << 128) / L, rounded down.
+        let delta = U256::from(amount_b as u128)
+            .checked_shl(128)
+            .and_then(|shifted| shifted.div_rem(self.liquidity.into()))
+            .and_then(|(quotient, _)| quotient.to_u128())
+            .ok_or_else(overflow)?;
+        let next = self.sqrt_price.checked_add(delta).ok_or_else(overflow)?;
+        if next > self.sqrt_max_price {
+            bail!("swap exceeds the pool's price range");
+        }
+        // amount_a = L * (sqrt_p' - sqrt_p) / (sqrt_p * sqrt_p'), rounded down.
+        let (out, _) = U256::mul(self.liquidity, next - self.sqrt_price)
+            .div_rem(U256::mul(self.sqrt_price, next))
+            .ok_or_else(overflow)?;
+        out.to_u128().and_then(|out| u64::try_from(out).ok()).ok_or_else(overflow)
+    }
+}
+
+pub(crate) fn token_program(flag: u8) -> Pubkey {
+    if flag == 1 {
+        TOKEN_2022_PROGRAM_ID
+    } else {
+        spl_token::id()
+    }
+}
+
+/// A liquidity position. Only unlocked liquidity can be withdrawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub pool: Pubkey,
+    pub nft_mint: Pubkey,
+    /// The pool's fee accumulators when `fee_*_pending` was last settled.
+    pub fee_a_per_token_checkpoint: U256,
+    pub fee_b_per_token_checkpoint: U256,
+    pub fee_a_pending: u64,
+    pub fee_b_pending: u64,
+    pub unlocked_liquidity: u128,
+    pub vested_liquidity: u128,
+    pub permanent_locked_liquidity: u128,
+}
+
+impl Position {
+    pub fn discriminator() -> [u8; 8] {
+        anchor_discriminator("account:Position")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            bail!("not a Meteora DAMM v2 position account");
+        }
+        Ok(Self {
+            pool: read_pubkey(data, 8)?,
+            nft_mint: read_pubkey(data, 40)?,
+            fee_a_per_token_checkpoint: read_u256(data, 72)?,
+            fee_b_per_token_checkpoint: read_u256(data, 104)?,
+            fee_a_pending: read_u64(data, 136)?,
+            fee_b_pending: read_u64(data, 144)?,
+            unlocked_liquidity: read_u128(data, 152)?,
+            vested_liquidity: read_u128(data, 168)?,
+            permanent_locked_liquidity: read_u128(data, 184)?,
+        })
+    }
+
+    /// The Token-2022 account holding the position NFT; holding it is what
+    /// authorizes changes to the position.
+    pub fn nft_account(&self) -> Pubkey {
+        position_nft_account(&self.nft_mint)
+    }
+
+    pub fn total_liquidity(&self) -> u128 {
+        self.unlocked_liquidity + self.vested_liquidity + self.permanent_locked_liquidity
+    }
+
+    /// Fees claimable now: those already settled into the position plus
+    /// what its liquidity earned since the checkpoint, as `(a, b)`.
+    pub fn unclaimed_fees(&self, pool: &Pool) -> Result<(u64, u64)> {
+        let liquidity = self.total_liquidity();
+        let earned = |per_liquidity: U256, checkpoint: U256, pending: u64| -> Result<u64> {
+            let overflow = || anyhow!("DAMM v2 fee overflow");
+            let delta = per_liquidity.checked_sub(checkpoint).unwrap_or(U256::ZERO);
+            // liquidity * delta >> 128, without a 384-bit product.
+            let fee = U256::mul(liquidity, delta.low_u128())
+                .shr(LIQUIDITY_SCALE)
+                .checked_add(U256::mul(liquidity, delta.high_u128()))
+                .and_then(U256::to_u128)
+                .and_then(|fee| u64::try_from(fee).ok())
+                .ok_or_else(overflow)?;
+            fee.checked_add(pending).ok_or_else(overflow)
+        };
+        Ok((
+            earned(pool.fee_a_per_liquidity, self.fee_a_per_token_checkpoint, self.fee_a_pending)?,
+            earned(pool.fee_b_per_liquidity, self.fee_b_per_token_checkpoint, self.fee_b_pending)?,
+        ))
+    }
+
+    /// Closing needs no liquidity left and no unclaimed fees.
+    pub fn is_empty(&self) -> bool {
+        self.total_liquidity() == 0
+            && self.fee_a_pending == 0
+            && self.fee_b_pending == 0
+    }
+}
+
+pub fn position_address(nft_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"position", nft_mint.as_ref()], &DAMM_V2_PROGRAM_ID).0
+}
+
+pub fn position_nft_account(nft_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"position_nft_account", nft_mint.as_ref()], &DAMM_V2_PROGRAM_ID).0
+}
+
+pub fn pool_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_authority"], &DAMM_V2_PROGRAM_ID).0
+}
+
+fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &DAMM_V2_PROGRAM_ID).0
+}
+
+/// The accounts an owner's position instruction touches, shared by the
+/// liquidity instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionKeys {
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub owner: Pubkey,
+    pub token_a_account: Pubkey,
+    pub token_b_account: Pubkey,
+}
+
+impl PositionKeys {
+    fn accounts(&self, pool: &Pool, position: &Position) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.position, false),
+            AccountMeta::new(self.token_a_account, false),
+            AccountMeta::new(self.token_b_account, false),
+            AccountMeta::new(pool.token_a_vault, false),
+            AccountMeta::new(pool.token_b_vault, false),
+            AccountMeta::new_readonly(pool.token_a_mint, false),
+            AccountMeta::new_readonly(pool.token_b_mint, false),
+            AccountMeta::new_readonly(position.nft_account(), false),
+            AccountMeta::new_readonly(self.owner, true),
+            AccountMeta::new_readonly(pool.token_a_program(), false),
+            AccountMeta::new_readonly(pool.token_b_program(), false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
+        ]
+    }
+}
+
+fn liquidity_data(name: &str, liquidity_delta: u128, threshold_a: u64, threshold_b: u64) -> Vec<u8> {
+    let mut data = anchor_discriminator(name).to_vec();
+    data.extend_from_slice(&liquidity_delta.to_le_bytes());
+    data.extend_from_slice(&threshold_a.to_le_bytes());
+    data.extend_from_slice(&threshold_b.to_le_bytes());
+    data
+}
+
+/// Add `liquidity_delta` to the position, spending at most `max_a` / `max_b`.
+pub fn add_liquidity(keys: &PositionKeys, pool: &Pool, position: &Position, liquidity_delta: u128, max_a: u64, max_b: u64) -> Instruction {
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts: keys.accounts(pool, position),
+        data: liquidity_data("global:add_liquidity", liquidity_delta, max_a, max_b),
+    }
+}
+
+/// Remove `liquidity_delta` from the position, receiving at least `min_a` / `min_b`.
+pub fn remove_liquidity(keys: &PositionKeys, pool: &Pool, position: &Position, liquidity_delta: u128, min_a: u64, min_b: u64) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(pool_authority(), false)];
+    accounts.extend(keys.accounts(pool, position));
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts,
+        data: liquidity_data("global:remove_liquidity", liquidity_delta, min_a, min_b),
+    }
+}
+
+/// Sends the position's unclaimed fees to the owner's token accounts.
+pub fn claim_position_fee(keys: &PositionKeys, pool: &Pool, position: &Position) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(pool_authority(), false)];
+    accounts.extend(keys.accounts(pool, position));
+    // The pool is only read when claiming.
+    accounts[1].is_writable = false;
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts,
+        data: anchor_discriminator("global:claim_position_fee").to_vec(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+}
+
+/// Exact-input swap; `input_account` / `output_account` are the payer's
+/// token accounts for the input and output mints.
+pub fn swap(
+    pool_address: Pubkey,
+    pool: &Pool,
+    payer: Pubkey,
+    input_account: Pubkey,
+    output_account: Pubkey,
+    params: &SwapParams,
+) -> Instruction {
+    let mut data = anchor_discriminator("global:swap").to_vec();
+    data.extend_from_slice(&params.amount_in.to_le_bytes());
+    data.extend_from_slice(&params.minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(pool_authority(), false),
+            AccountMeta::new(pool_address, false),
+            AccountMeta::new(input_account, false),
+            AccountMeta::new(output_account, false),
+            AccountMeta::new(pool.token_a_vault, false),
+            AccountMeta::new(pool.token_b_vault, false),
+            AccountMeta::new_readonly(pool.token_a_mint, false),
+            AccountMeta::new_readonly(pool.token_b_mint, false),
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(pool.token_a_program(), false),
+            AccountMeta::new_readonly(pool.token_b_program(), false),
+            // No referral account: Anchor reads the program id as `None`.
+            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Opens an empty position in `pool` owned by `owner`. `nft_mint` is a
+/// fresh keypair's address and must sign alongside `payer`.
+pub fn create_position(pool_address: Pubkey, owner: Pubkey, payer: Pubkey, nft_mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(owner, false),
+            AccountMeta::new(nft_mint, true),
+            AccountMeta::new(position_nft_account(&nft_mint), false),
+            AccountMeta::new(pool_address, false),
+            AccountMeta::new(position_address(&nft_mint), false),
+            AccountMeta::new_readonly(pool_authority(), false),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
+        ],
+        data: anchor_discriminator("global:create_position").to_vec(),
+    }
+}
+
+/// Closes an empty position, burning its NFT and refunding rent to `owner`.
+pub fn close_position(keys: &PositionKeys, position: &Position) -> Instruction {
+    Instruction {
+        program_id: DAMM_V2_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(position.nft_mint, false),
+            AccountMeta::new(position.nft_account(), false),
+            AccountMeta::new(keys.pool, false),
+            AccountMeta::new(keys.position, false),
+            AccountMeta::new_readonly(pool_authority(), false),
+            AccountMeta::new(keys.owner, false),
+            AccountMeta::new_readonly(keys.owner, true),
+            AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
+        ],
+        data: anchor_discriminator("global:close_position").to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_range_pool(reserve: u64, collect_fee_mode: CollectFeeMode) -> Pool {
+        Pool {
+            fees: FeeSchedule::constant(2_500_000),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            token_a_vault: Pubkey::new_unique(),
+            token_b_vault: Pubkey::new_unique(),
+            // At price 1.0 the real liquidity equals each reserve; stored Q64-scaled.
+            liquidity: (reserve as u128) << 64,
+            sqrt_min_price: 4_295_048_016,
+            sqrt_max_price: 79_226_673_515_401_279_992_447_579_055,
+            sqrt_price: 1 << 64,
+            pool_status: 0,
+            token_a_flag: 0,
+            token_b_flag: 0,
+            collect_fee_mode,
+            fee_a_per_liquidity: U256::ZERO,
+            fee_b_per_liquidity: U256::ZERO,
+            creator: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn only_the_dbc_authority_pool_counts_as_the_migration() {
+        let pool = full_range_pool(1_000_000, CollectFeeMode::BothToken);
+        let (base, quote) = (pool.token_a_mint, pool.token_b_mint);
+        assert!(!pool.is_dbc_migration_of(&base, &quote));
+
+        let migrated = Pool { creator: super::super::dbc::pool_authority(), ..pool };
+        assert!(migrated.is_dbc_migration_of(&base, &quote));
+        assert!(!migrated.is_dbc_migration_of(&base, &Pubkey::new_unique()));
+        assert!(!migrated.is_dbc_migration_of(&quote, &base));
+    }
+
+    #[test]
+    fn full_range_quote_matches_constant_product() {
+        let reserve = 1_000_000_000_000u64;
+        let amount = 1_000_000u64;
+        let constant_product = (reserve as u128 * amount as u128 / (reserve + amount) as u128) as u64;
+
+        let (out, fee) = full_range_pool(reserve, CollectFeeMode::BothToken).quote_exact_in(amount, true, FeeClock::default()).unwrap();
+        assert!(constant_product - (out + fee) <= 1, "{} + {} vs {}", out, fee, constant_product);
+        assert_eq!(fee, (out + fee).div_ceil(400));
+
+        // OnlyB takes the fee from the B input before the swap.
+        let (out, fee) = full_range_pool(reserve, CollectFeeMode::OnlyB).quote_exact_in(amount, false, FeeClock::default()).unwrap();
+        assert_eq!(fee, 2_500);
+        let net = amount - fee;
+        let expected = (reserve as u128 * net as u128 / (reserve + net) as u128) as u64;
+        assert!(expected - out <= 1, "{} vs {}", out, expected);
+    }
+
+    #[test]
+    fn full_range_quotes_keep_k_and_are_monotonic() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(0x4441_4d4d);
+        for _ in 0..1_000 {
+            let reserve = rng.gen_range(1_000_000..1_000_000_000_000_000u64);
+            let amount = rng.gen_range(1..reserve / 10);
+            let a_to_b = rng.gen_bool(0.5);
+            let mode = if rng.gen_bool(0.5) { CollectFeeMode::BothToken } else { CollectFeeMode::OnlyB };
+            let pool = full_range_pool(reserve, mode);
+
+            let (out, fee) = pool.quote_exact_in(amount, a_to_b, FeeClock::default()).unwrap();
+            let k_before = reserve as u128 * reserve as u128;
+            let k_after = (reserve as u128 + amount as u128) * (reserve - out) as u128;
+            assert!(k_after >= k_before, "k shrank: amount={} reserve={}", amount, reserve);
+
+            let (more_out, more_fee) = pool.quote_exact_in(amount + 1, a_to_b, FeeClock::default()).unwrap();
+            assert!(more_out >= out && more_fee >= fee);
+        }
+    }
+
+    #[test]
+    fn liquidity_round_trips_through_amounts() {
+        let pool = full_range_pool(1_000_000_000_000, CollectFeeMode::BothToken);
+
+        // At price 1.0 a balanced deposit adds liquidity equal to the amount, Q64-scaled.
+        let liquidity = pool.liquidity_for_amounts(1_000_000, 5_000_000).unwrap();
+        let (a, b) = pool.amounts_for_liquidity(liquidity, true).unwrap();
+        assert!(a <= 1_000_000 && 1_000_000 - a <= 1, "{}", a);
+        assert!(b <= 5_000_000 && b.abs_diff(1_000_000) <= 1, "{}", b);
+
+        let (out_a, out_b) = pool.amounts_for_liquidity(liquidity, false).unwrap();
+        assert!(out_a <= a && out_b <= b && a - out_a <= 1 && b - out_b <= 1);
+
+        // Price at the top of the range: only token B backs liquidity.
+        let top = Pool { sqrt_price: pool.sqrt_max_price, ..pool };
+        let liquidity = top.liquidity_for_amounts(0, 1_000).unwrap();
+        assert_eq!(top.amounts_for_liquidity(liquidity, true).unwrap().0, 0);
+    }
+
+    #[test]
+    fn swap_and_position_instruction_layouts() {
+        let pool = full_range_pool(1_000_000, CollectFeeMode::BothToken);
+        let (address, payer, input, output) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let ix = swap(address, &pool, payer, input, output, &SwapParams { amount_in: 9, minimum_amount_out: 8 });
+        assert_eq!(ix.data, [anchor_discriminator("global:swap").as_slice(), &9u64.to_le_bytes(), &8u64.to_le_bytes()].concat());
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(keys[..4], [pool_authority(), address, input, output]);
+        assert_eq!(keys[8], payer);
+        assert!(ix.accounts[8].is_signer && ix.accounts.iter().filter(|meta| meta.is_signer).count() == 1);
+
+        let nft_mint = Pubkey::new_unique();
+        let ix = create_position(address, payer, payer, nft_mint);
+        let signers: Vec<Pubkey> = ix.accounts.iter().filter(|meta| meta.is_signer).map(|meta| meta.pubkey).collect();
+        assert_eq!(signers, [nft_mint, payer]);
+        assert_eq!(ix.accounts[4].pubkey, position_address(&nft_mint));
+
+        let position = Position {
+            pool: address,
+            nft_mint,
+            fee_a_per_token_checkpoint: U256::ZERO,
+            fee_b_per_token_checkpoint: U256::ZERO,
+            fee_a_pending: 0,
+            fee_b_pending: 0,
+            unlocked_liquidity: 0,
+            vested_liquidity: 0,
+            permanent_locked_liquidity: 0,
+        };
+        assert!(position.is_empty());
+        assert!(!Position { fee_b_pending: 1, ..position.clone() }.is_empty());
+        let keys = PositionKeys { pool: address, position: position_address(&nft_mint), owner: payer, token_a_account: input, token_b_account: output };
+        let ix = close_position(&keys, &position);
+        assert_eq!(ix.data, anchor_discriminator("global:close_position"));
+        assert_eq!(ix.accounts[1].pubkey, position_nft_account(&nft_mint));
+        assert!(ix.accounts[6].is_signer && ix.accounts[6].pubkey == payer);
+
+        let ix = claim_position_fee(&keys, &pool, &position);
+        assert_eq!(ix.data, anchor_discriminator("global:claim_position_fee"));
+        assert_eq!((ix.accounts[1].pubkey, ix.accounts[1].is_writable), (address, false));
+        assert!(ix.accounts[2].is_writable && ix.accounts[10].is_signer);
+    }
+
+    #[test]
+    fn unclaimed_fees_accrue_since_the_checkpoint() {
+        // How the program accrues fees: (fee << 128) / pool liquidity.
+        let per_liquidity = |fee: u128, liquidity: u128| {
+            U256::from(fee).checked_shl(LIQUIDITY_SCALE).and_then(|scaled| scaled.div_rem(liquidity.into())).unwrap().0
+        };
+        let liquidity = 1_000_000u128 << 64;
+        let pool = Pool {
+            // 5_000 A and 12_345 B of fees over the pool's whole liquidity.
+            fee_a_per_liquidity: per_liquidity(5_000, liquidity),
+            fee_b_per_liquidity: per_liquidity(12_345, liquidity),
+            ..full_range_pool(1_000_000, CollectFeeMode::BothToken)
+        };
+        let position = Position {
+            pool: Pubkey::new_unique(),
+            nft_mint: Pubkey::new_unique(),
+            fee_a_per_token_checkpoint: U256::ZERO,
+            fee_b_per_token_checkpoint: per_liquidity(2_345, liquidity),
+            fee_a_pending: 7,
+            fee_b_pending: 0,
+            // A fifth of the pool, split across lock states.
+            unlocked_liquidity: liquidity / 10,
+            vested_liquidity: liquidity / 20,
+            permanent_locked_liquidity: liquidity / 20,
+        };
+        let (a, b) = position.unclaimed_fees(&pool).unwrap();
+        assert!(a.abs_diff(1_007) <= 1, "{}", a);
+        assert!(b.abs_diff(2_000) <= 1, "{}", b);
+
+        let settled = Position { fee_a_per_token_checkpoint: pool.fee_a_per_liquidity, fee_b_per_token_checkpoint: pool.fee_b_per_liquidity, ..position };
+        assert_eq!(settled.unclaimed_fees(&pool).unwrap(), (7, 0));
+        assert!(!settled.is_empty());
+    }
+}
@@ -0,0 +1,612 @@
+//! Meteora Dynamic Bonding Curve (DBC) account layouts and swap math.
+//!
+//! A DBC curve is a chain of constant-liquidity segments in sqrt-price
+//! space: segment `i` runs from the previous point's sqrt price (or the
+//! config's start price) up to `points[i].sqrt_price` with
+//! `points[i].liquidity`. Liquidity is Q64-scaled as in DAMM v2, so quote
+//! deltas shift by 128 bits. Buys walk the segments up, sells walk them down.
+
+use super::damm_v2::token_program;
+use super::fee::{ActivationType, BaseFee, DynamicFee, FeeClock, FeeSchedule, FeeSchedulerMode};
+use crate::dex::layout::{anchor_discriminator, read_pubkey, read_u128, read_u16, read_u32, read_u64, read_u8};
+use crate::dex::math::U256;
+use anyhow::{anyhow, bail, Result};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+pub const DBC_PROGRAM_ID: Pubkey = pubkey!("dbcij3LWUppWqq96dh6gJWwBifmcGfLSB5D4DuSMaqN");
+
+/// Offset of `VirtualPool::base_mint`, for `getProgramAccounts` filters.
+pub const VIRTUAL_POOL_BASE_MINT_OFFSET: usize = 136;
+
+pub use super::fee::FEE_DENOMINATOR;
+
+/// Curve points a config holds; unused trailing points are zeroed.
+pub const MAX_CURVE_POINTS: usize = 20;
+const CURVE_OFFSET: usize = 408;
+
+/// A token's bonding-curve pool. The quote mint, fees and curve live in the
+/// pool's [`PoolConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtualPool {
+    /// Left by the last swap; drives the config's dynamic fee.
+    pub volatility_accumulator: u128,
+    /// Unix time of that swap.
+    pub volatility_updated_at: u64,
+    pub config: Pubkey,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_reserve: u64,
+    pub quote_reserve: u64,
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    /// 0 for SPL Token, 1 for Token-2022.
+    pub pool_type: u8,
+    pub is_migrated: bool,
+}
+
+impl VirtualPool {
+    pub fn discriminator() -> [u8; 8] {
+        anchor_discriminator("account:VirtualPool")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != Self::discriminator() {
+            bail!("not a Meteora DBC pool account");
+        }
+        Ok(Self {
+            volatility_accumulator: read_u128(data, 40)?,
+            volatility_updated_at: read_u64(data, 8)?,
+            config: read_pubkey(data, 72)?,
+            creator: read_pubkey(data, 104)?,
+            base_mint: read_pubkey(data, VIRTUAL_POOL_BASE_MINT_OFFSET)?,
+            base_vault: read_pubkey(data, 168)?,
+            quote_vault: read_pubkey(data, 200)?,
+            base_reserve: read_u64(data, 232)?,
+            quote_reserve: read_u64(data, 240)?,
+            sqrt_price: read_u128(data, 280)?,
+            activation_point: read_u64(data, 296)?,
+            pool_type: read_u8(data, 304)?,
+            is_migrated: read_u8(data, 305)? != 0,
+        })
+    }
+
+    pub fn base_token_program(&self) -> Pubkey {
+        token_program(self.pool_type)
+    }
+
+    /// Whether the curve has raised its migration threshold and stopped trading.
+    pub fn is_curve_complete(&self, config: &PoolConfig) -> bool {
+        self.quote_reserve >= config.migration_quote_threshold
+    }
+
+    /// The config's fee schedule, anchored at this pool's activation.
+    pub fn fee_schedule(&self, config: &PoolConfig) -> FeeSchedule {
+        FeeSchedule {
+            base: config.base_fee,
+            dynamic: config.dynamic_fee,
+            volatility_accumulator: self.volatility_accumulator,
+            last_volatility_update: self.volatility_updated_at,
+            activation_point: self.activation_point,
+            activation_type: config.activation_type,
+        }
+    }
+
+    /// Exact-input quote at the fee in force at `now`.
+    /// `base_for_quote` sells the token; otherwise the quote token buys it.
+    pub fn quote_exact_in(&self, config: &PoolConfig, amount_in: u64, base_for_quote: bool, now: FeeClock) -> Result<SwapQuote> {
+        let fee_on_input = config.collect_fee_mode == CollectFeeMode::QuoteToken && !base_for_quote;
+        let fee = self.fee_schedule(config).at(now);
+
+        let (net_in, input_fee) = if fee_on_input {
+            let fee = fee.fee_on(amount_in);
+            (amount_in - fee, fee)
+        } else {
+            (amount_in, 0)
+        };
+        let (gross_out, next_sqrt_price) = if base_for_quote {
+            config.curve.sell(self.sqrt_price, net_in)?
+        } else {
+            config.curve.buy(self.sqrt_price, net_in)?
+        };
+        let (amount_out, fee) = if fee_on_input {
+            (gross_out, input_fee)
+        } else {
+            let fee = fee.fee_on(gross_out);
+            (gross_out - fee, fee)
+        };
+        Ok(SwapQuote { amount_in, amount_out, fee, next_sqrt_price })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Trading fee, in the input token when charged on input, else the output.
+    pub fee: u64,
+    pub next_sqrt_price: u128,
+}
+
+/// Which token trading fees are charged in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectFeeMode {
+    /// Always in the quote token: from the input on buys, the output on sells.
+    QuoteToken,
+    /// From the output token, whichever it is.
+    OutputToken,
+}
+
+/// The shared launch configuration a DBC pool is created from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub quote_mint: Pubkey,
+    pub fee_claimer: Pubkey,
+    pub base_fee: BaseFee,
+    pub dynamic_fee: Option<DynamicFee>,
+    pub collect_fee_mode: CollectFeeMode,
+    /// 0 for DAMM v1, 1 for DAMM v2.
+    pub migration_option: u8,
+    pub activation_type: ActivationType,
+    pub token_decimal: u8,
+    /// 0 for an SPL Token quote mint, 1 for Token-2022.
+    pub quote_token_flag: u8,
+    /// Quote raised at which the curve completes and the pool can migrate.
+    pub migration_quote_threshold: u64,
+    pub migration_base_threshold: u64,
+    pub migration_sqrt_price: u128,
+    pub curve: Curve,
+}
+
+impl PoolConfig {
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        if data.len() < 8 || data[..8] != anchor_discriminator("account:PoolConfig") {
+            bail!("not a Meteora DBC config account");
+        }
+        let mut points = Vec::new();
+        for i in 0..MAX_CURVE_POINTS {
+            let offset = CURVE_OFFSET + i * 32;
+            let point = CurvePoint { sqrt_price: read_u128(data, offset)?, liquidity: read_u128(data, offset + 16)? };
+            if point.sqrt_price == 0 || point.liquidity == 0 {
+                break;
+            }
+            points.push(point);
+        }
+        let dynamic_fee = if read_u8(data, 136)? != 0 {
+            Some(DynamicFee {
+                max_volatility_accumulator: read_u32(data, 144)?,
+                variable_fee_control: read_u32(data, 148)?,
+                bin_step: read_u16(data, 152)?,
+                filter_period: read_u16(data, 154)?,
+                decay_period: read_u16(data, 156)?,
+                reduction_factor: read_u16(data, 158)?,
+            })
+        } else {
+            None
+        };
+        Ok(Self {
+            quote_mint: read_pubkey(data, 8)?,
+            fee_claimer: read_pubkey(data, 40)?,
+            base_fee: BaseFee {
+                cliff_fee_numerator: read_u64(data, 104)?,
+                period_frequency: read_u64(data, 112)?,
+                reduction_factor: read_u64(data, 120)?,
+                number_of_period: read_u16(data, 128)?,
+                mode: FeeSchedulerMode::from_u8(read_u8(data, 130)?)?,
+            },
+            dynamic_fee,
+            collect_fee_mode: match read_u8(data, 232)? {
+                0 => CollectFeeMode::QuoteToken,
+                1 => CollectFeeMode::OutputToken,
+                other => bail!("unknown DBC collect_fee_mode {}", other),
+            },
+            migration_option: read_u8(data, 233)?,
+            activation_type: ActivationType::from_u8(read_u8(data, 234)?)?,
+            token_decimal: read_u8(data, 235)?,
+            quote_token_flag: read_u8(data, 238)?,
+            migration_quote_threshold: read_u64(data, 264)?,
+            migration_base_threshold: read_u64(data, 272)?,
+            migration_sqrt_price: read_u128(data, 280)?,
+            curve: Curve { sqrt_start_price: read_u128(data, 392)?, points },
+        })
+    }
+
+    pub fn quote_token_program(&self) -> Pubkey {
+        token_program(self.quote_token_flag)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurvePoint {
+    /// Upper end of this point's segment, Q64.64.
+    pub sqrt_price: u128,
+    /// Q64-scaled liquidity of the segment.
+    pub liquidity: u128,
+}
+
+/// The bonding curve's price segments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Curve {
+    pub sqrt_start_price: u128,
+    pub points: Vec<CurvePoint>,
+}
+
+impl Curve {
+    /// Spends `quote_in` from `sqrt_price` upward: `(base_out, next_sqrt_price)`.
+    pub fn buy(&self, sqrt_price: u128, quote_in: u64) -> Result<(u64, u128)> {
+        let mut price = sqrt_price;
+        let mut left = quote_in;
+        let mut out: u64 = 0;
+        for point in &self.points {
+            if point.sqrt_price <= price || left == 0 {
+                continue;
+            }
+            let max_in = quote_delta(price, point.sqrt_price, point.liquidity, true)?;
+            let next = if U256::from(left as u128) < max_in {
+                next_sqrt_price_from_quote(price, point.liquidity, left)?
+            } else {
+                point.sqrt_price
+            };
+            out = out.checked_add(to_u64(base_delta(price, next, point.liquidity, false)?)?).ok_or_else(overflow)?;
+            left = if next == point.sqrt_price { left - to_u64(max_in)? } else { 0 };
+            price = next;
+        }
+        if left != 0 {
+            bail!("curve cannot absorb {} more quote past its last point", left);
+        }
+        Ok((out, price))
+    }
+
+    /// Sells `base_in` from `sqrt_price` downward: `(quote_out, next_sqrt_price)`.
+    pub fn sell(&self, sqrt_price: u128, base_in: u64) -> Result<(u64, u128)> {
+        let mut price = sqrt_price;
+        let mut left = base_in;
+        let mut out: u64 = 0;
+        // Segment i + 1 spans points[i] .. points[i + 1]; segment 0 starts at the start price.
+        for i in (0..self.points.len().saturating_sub(1)).rev() {
+            let (lower, liquidity) = (self.points[i].sqrt_price, self.points[i + 1].liquidity);
+            if lower >= price || left == 0 {
+                continue;
+            }
+            let max_in = base_delta(lower, price, liquidity, true)?;
+            let next = if U256::from(left as u128) < max_in {
+                next_sqrt_price_from_base(price, liquidity, left)?
+            } else {
+                lower
+            };
+            out = out.checked_add(to_u64(quote_delta(next, price, liquidity, false)?)?).ok_or_else(overflow)?;
+            left = if next == lower { left - to_u64(max_in)? } else { 0 };
+            price = next;
+        }
+        if left != 0 {
+            let liquidity = self.points.first().ok_or_else(|| anyhow!("curve has no points"))?.liquidity;
+            let next = next_sqrt_price_from_base(price, liquidity, left)?;
+            if next < self.sqrt_start_price {
+                bail!("selling {} would push the price below the curve start", base_in);
+            }
+            out = out.checked_add(to_u64(quote_delta(next, price, liquidity, false)?)?).ok_or_else(overflow)?;
+            price = next;
+        }
+        Ok((out, price))
+    }
+}
+
+fn overflow() -> anyhow::Error {
+    anyhow!("DBC swap overflow")
+}
+
+fn to_u64(amount: U256) -> Result<u64> {
+    amount.to_u128().and_then(|amount| u64::try_from(amount).ok()).ok_or_else(overflow)
+}
+
+/// `L * (upper - lower) / (lower * upper)`.
+fn base_delta(lower: u128, upper: u128, liquidity: u128, round_up: bool) -> Result<U256> {
+    let numerator = U256::mul(liquidity, upper - lower);
+    let denominator = U256::mul(lower, upper);
+    if round_up { numerator.div_round_up(denominator) } else { numerator.div_rem(denominator).map(|(q, _)| q) }
+        .ok_or_else(overflow)
+}
+
+/// `L * (upper - lower) >> 128`.
+fn quote_delta(lower: u128, upper: u128, liquidity: u128, round_up: bool) -> Result<U256> {
+    let product = U256::mul(liquidity, upper - lower);
+    if round_up {
+        product.div_round_up(U256::from(1u128).checked_shl(128).ok_or_else(overflow)?).ok_or_else(overflow)
+    } else {
+        Ok(product.shr(128))
+    }
+}
+
+/// `sqrt_p + (amount << 128) / L`, rounded down.
+fn next_sqrt_price_from_quote(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    let delta = U256::from(amount as u128)
+        .checked_shl(128)
+        .and_then(|shifted| shifted.div_rem(liquidity.into()))
+        .and_then(|(quotient, _)| quotient.to_u128())
+        .ok_or_else(overflow)?;
+    sqrt_price.checked_add(delta).ok_or_else(overflow)
+}
+
+/// `L * sqrt_p / (L + amount * sqrt_p)`, rounded up.
+fn next_sqrt_price_from_base(sqrt_price: u128, liquidity: u128, amount: u64) -> Result<u128> {
+    let denominator = U256::from(liquidity)
+        .checked_add(U256::mul(amount as u128, sqrt_price))
+        .ok_or_else(overflow)?;
+    U256::mul(liquidity, sqrt_price).div_round_up(denominator).and_then(U256::to_u128).ok_or_else(overflow)
+}
+
+pub fn pool_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"pool_authority"], &DBC_PROGRAM_ID).0
+}
+
+fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &DBC_PROGRAM_ID).0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    /// Sell the base token for quote; otherwise buy it.
+    pub base_for_quote: bool,
+}
+
+/// Exact-input swap against the curve; `input_account` / `output_account`
+/// are the payer's token accounts for the input and output mints.
+pub fn swap(
+    pool_address: Pubkey,
+    pool: &VirtualPool,
+    config: &PoolConfig,
+    payer: Pubkey,
+    input_account: Pubkey,
+    output_account: Pubkey,
+    params: &SwapParams,
+) -> Instruction {
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(&anchor_discriminator("global:swap"));
+    data.extend_from_slice(&params.amount_in.to_le_bytes());
+    data.extend_from_slice(&params.minimum_amount_out.to_le_bytes());
+
+    Instruction {
+        program_id: DBC_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(pool_authority(), false),
+            AccountMeta::new_readonly(pool.config, false),
+            AccountMeta::new(pool_address, false),
+            AccountMeta::new(input_account, false),
+            AccountMeta::new(output_account, false),
+            AccountMeta::new(pool.base_vault, false),
+            AccountMeta::new(pool.quote_vault, false),
+            AccountMeta::new_readonly(pool.base_mint, false),
+            AccountMeta::new_readonly(config.quote_mint, false),
+            AccountMeta::new_readonly(payer, true),
+            AccountMeta::new_readonly(pool.base_token_program(), false),
+            AccountMeta::new_readonly(config.quote_token_program(), false),
+            // No referral account: Anchor reads the program id as `None`.
+            AccountMeta::new_readonly(DBC_PROGRAM_ID, false),
+            AccountMeta::new_readonly(event_authority(), false),
+            AccountMeta::new_readonly(DBC_PROGRAM_ID, false),
+        ],
+        data,
+    }
+}
+
+/// Quote per base token in whole units at a Q64.64 sqrt price.
+pub fn price_from_sqrt_price(sqrt_price: u128, base_decimals: u8, quote_decimals: u8) -> f64 {
+    let sqrt = sqrt_price as f64 / 2f64.powi(64);
+    sqrt * sqrt * 10f64.powi(base_decimals as i32 - quote_decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q64: u128 = 1 << 64;
+
+    /// Two segments: price 1.0 → 4.0 (sqrt 1 → 2) at L, then 4.0 → 9.0 at 2L.
+    fn config(cliff_fee_numerator: u64, collect_fee_mode: CollectFeeMode) -> PoolConfig {
+        let liquidity = 1_000_000_000u128 * Q64;
+        PoolConfig {
+            quote_mint: Pubkey::new_unique(),
+            fee_claimer: Pubkey::new_unique(),
+            base_fee: BaseFee::constant(cliff_fee_numerator),
+            dynamic_fee: None,
+            collect_fee_mode,
+            migration_option: 1,
+            activation_type: ActivationType::Timestamp,
+            token_decimal: 6,
+            quote_token_flag: 0,
+            migration_quote_threshold: 5_000_000_000,
+            migration_base_threshold: 0,
+            migration_sqrt_price: 3 * Q64,
+            curve: Curve {
+                sqrt_start_price: Q64,
+                points: vec![
+                    CurvePoint { sqrt_price: 2 * Q64, liquidity },
+                    CurvePoint { sqrt_price: 3 * Q64, liquidity: 2 * liquidity },
+                ],
+            },
+        }
+    }
+
+    fn at(unix_timestamp: u64) -> FeeClock {
+        FeeClock { slot: unix_timestamp, unix_timestamp }
+    }
+
+    fn pool(sqrt_price: u128) -> VirtualPool {
+        VirtualPool {
+            volatility_accumulator: 0,
+            volatility_updated_at: 0,
+            config: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            quote_vault: Pubkey::new_unique(),
+            base_reserve: 0,
+            quote_reserve: 0,
+            sqrt_price,
+            activation_point: 0,
+            pool_type: 0,
+            is_migrated: false,
+        }
+    }
+
+    fn dynamic_fee() -> DynamicFee {
+        DynamicFee {
+            bin_step: 100,
+            variable_fee_control: 100_000,
+            max_volatility_accumulator: 350_000,
+            filter_period: 10,
+            decay_period: 120,
+            reduction_factor: 5_000,
+        }
+    }
+
+    #[test]
+    fn decodes_config_fees_thresholds_and_curve() {
+        let mut data = vec![0u8; 1048];
+        data[..8].copy_from_slice(&anchor_discriminator("account:PoolConfig"));
+        data[104..112].copy_from_slice(&10_000_000u64.to_le_bytes());
+        // Exponential decay by 10% every 60 seconds, 5 times; dynamic fee on.
+        data[112..120].copy_from_slice(&60u64.to_le_bytes());
+        data[120..128].copy_from_slice(&1_000u64.to_le_bytes());
+        data[128..130].copy_from_slice(&5u16.to_le_bytes());
+        data[130] = 1;
+        data[136] = 1;
+        data[144..148].copy_from_slice(&350_000u32.to_le_bytes());
+        data[148..152].copy_from_slice(&100_000u32.to_le_bytes());
+        data[152..154].copy_from_slice(&100u16.to_le_bytes());
+        data[154..156].copy_from_slice(&10u16.to_le_bytes());
+        data[156..158].copy_from_slice(&120u16.to_le_bytes());
+        data[158..160].copy_from_slice(&5_000u16.to_le_bytes());
+        data[232] = 1;
+        data[234] = 1;
+        data[264..272].copy_from_slice(&85_000_000_000u64.to_le_bytes());
+        data[392..408].copy_from_slice(&Q64.to_le_bytes());
+        for (i, (sqrt_price, liquidity)) in [(2 * Q64, 7u128), (3 * Q64, 9)].into_iter().enumerate() {
+            let offset = CURVE_OFFSET + i * 32;
+            data[offset..offset + 16].copy_from_slice(&sqrt_price.to_le_bytes());
+            data[offset + 16..offset + 32].copy_from_slice(&liquidity.to_le_bytes());
+        }
+
+        let config = PoolConfig::decode(&data).unwrap();
+        assert_eq!(
+            config.base_fee,
+            BaseFee {
+                cliff_fee_numerator: 10_000_000,
+                number_of_period: 5,
+                period_frequency: 60,
+                reduction_factor: 1_000,
+                mode: FeeSchedulerMode::Exponential,
+            }
+        );
+        assert_eq!(config.dynamic_fee, Some(dynamic_fee()));
+        assert_eq!(config.activation_type, ActivationType::Timestamp);
+        assert_eq!(config.collect_fee_mode, CollectFeeMode::OutputToken);
+        assert_eq!(config.migration_quote_threshold, 85_000_000_000);
+        assert_eq!(config.curve.sqrt_start_price, Q64);
+        assert_eq!(config.curve.points, [CurvePoint { sqrt_price: 2 * Q64, liquidity: 7 }, CurvePoint { sqrt_price: 3 * Q64, liquidity: 9 }]);
+    }
+
+    #[test]
+    fn buys_walk_segments_and_match_closed_form() {
+        let config = config(0, CollectFeeMode::QuoteToken);
+        // Filling segment 0 (sqrt 1 → 2) costs L * 1 quote and yields L * (1 - 1/2) base.
+        let (out, price) = config.curve.buy(Q64, 1_000_000_000).unwrap();
+        assert_eq!(price, 2 * Q64);
+        assert_eq!(out, 500_000_000);
+
+        // Another 2L * 0.5 quote moves segment 1 from sqrt 2 to 2.5: 2L * (1/2 - 1/2.5) base.
+        let (out, price) = config.curve.buy(Q64, 2_000_000_000).unwrap();
+        assert_eq!(price, 5 * Q64 / 2);
+        assert_eq!(out, 500_000_000 + 200_000_000);
+
+        assert!(config.curve.buy(Q64, 3_000_000_001).is_err());
+    }
+
+    #[test]
+    fn sells_round_trip_in_the_pools_favour() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let config = config(0, CollectFeeMode::QuoteToken);
+        let mut rng = StdRng::seed_from_u64(0x4442_4331);
+        for _ in 0..500 {
+            let quote_in = rng.gen_range(1..3_000_000_000u64);
+            let (base, price) = config.curve.buy(Q64, quote_in).unwrap();
+            let (quote_back, end) = config.curve.sell(price, base).unwrap();
+            assert!(quote_back <= quote_in, "{} > {}", quote_back, quote_in);
+            assert!(quote_in - quote_back <= 3, "lost {} of {}", quote_in - quote_back, quote_in);
+            assert!(end >= Q64);
+        }
+        assert!(config.curve.sell(Q64, 1_000).is_err());
+    }
+
+    #[test]
+    fn fees_follow_the_collect_mode() {
+        // 1% fee; buying with 100_000_000 quote.
+        let quote_mode = pool(Q64).quote_exact_in(&config(10_000_000, CollectFeeMode::QuoteToken), 100_000_000, false, FeeClock::default()).unwrap();
+        assert_eq!(quote_mode.fee, 1_000_000);
+        let (net_out, _) = config(0, CollectFeeMode::QuoteToken).curve.buy(Q64, 99_000_000).unwrap();
+        assert_eq!(quote_mode.amount_out, net_out);
+
+        let output_mode = pool(Q64).quote_exact_in(&config(10_000_000, CollectFeeMode::OutputToken), 100_000_000, false, FeeClock::default()).unwrap();
+        let (gross_out, _) = config(0, CollectFeeMode::OutputToken).curve.buy(Q64, 100_000_000).unwrap();
+        assert_eq!(output_mode.fee, gross_out.div_ceil(100));
+        assert_eq!(output_mode.amount_out, gross_out - output_mode.fee);
+    }
+
+    #[test]
+    fn launch_fees_decay_out_of_quotes() {
+        // 50% at activation, down 9.8 points every 10 seconds to 1%.
+        let config = PoolConfig {
+            base_fee: BaseFee {
+                cliff_fee_numerator: 500_000_000,
+                number_of_period: 5,
+                period_frequency: 10,
+                reduction_factor: 98_000_000,
+                mode: FeeSchedulerMode::Linear,
+            },
+            ..config(0, CollectFeeMode::QuoteToken)
+        };
+        let pool = VirtualPool { activation_point: 1_000, ..pool(Q64) };
+
+        let at_launch = pool.quote_exact_in(&config, 100_000_000, false, at(1_002)).unwrap();
+        assert_eq!(at_launch.fee, 50_000_000);
+        let settled = pool.quote_exact_in(&config, 100_000_000, false, at(1_050)).unwrap();
+        assert_eq!(settled.fee, 1_000_000);
+        assert!(settled.amount_out > at_launch.amount_out);
+
+        // Recent volatility adds on top until the pool has been quiet for the
+        // decay period.
+        let config = PoolConfig { dynamic_fee: Some(dynamic_fee()), ..config };
+        let volatile = VirtualPool { volatility_accumulator: 100_000, volatility_updated_at: 4_995, ..pool.clone() };
+        assert_eq!(volatile.quote_exact_in(&config, 100_000_000, false, at(5_000)).unwrap().fee, 11_000_000);
+        assert_eq!(volatile.quote_exact_in(&config, 100_000_000, false, at(5_115)).unwrap().fee, 1_000_000);
+    }
+
+    #[test]
+    fn swap_instruction_layout() {
+        let (address, payer, input, output) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let pool = VirtualPool { pool_type: 1, ..pool(Q64) };
+        let config = config(0, CollectFeeMode::QuoteToken);
+        let ix = swap(address, &pool, &config, payer, input, output, &SwapParams { amount_in: 7, minimum_amount_out: 5, base_for_quote: false });
+
+        assert_eq!(ix.data[..8], anchor_discriminator("global:swap"));
+        assert_eq!(ix.data[8..], [7u64.to_le_bytes(), 5u64.to_le_bytes()].concat());
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert_eq!(keys[..5], [pool_authority(), pool.config, address, input, output]);
+        assert_eq!(keys[7..12], [pool.base_mint, config.quote_mint, payer, super::super::damm_v2::TOKEN_2022_PROGRAM_ID, spl_token::id()]);
+        assert_eq!(ix.accounts.iter().filter(|meta| meta.is_signer).count(), 1);
+        assert!(ix.accounts[9].is_signer && ix.accounts[2].is_writable);
+    }
+
+    #[test]
+    fn prices_scale_by_decimals() {
+        assert!((price_from_sqrt_price(2 * Q64, 6, 9) - 0.004).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,415 @@
+//! Meteora's trading fee, shared by DBC and DAMM v2: a base fee that can
+//! decay on a schedule after the pool activates, plus a variable fee that
+//! grows with recent volatility.
+//!
+//! The base fee starts at `cliff_fee_numerator` and drops every
+//! `period_frequency` points (slots or seconds, per the pool's activation
+//! type) for `number_of_period` periods, either by a fixed step (linear) or
+//! by a fixed share (exponential, `reduction_factor` in bps). The variable
+//! fee is `(volatility_accumulator * bin_step)^2 * variable_fee_control`,
+//! scaled down by 1e11. The accumulator the last swap left behind decays
+//! as the program's `update_references` does before the next swap: once
+//! `filter_period` seconds have passed it keeps `reduction_factor` bps of
+//! itself, and after `decay_period` seconds it resets to zero.
+
+use crate::dex::math::U256;
+use anyhow::{bail, Result};
+use std::time::Duration;
+
+/// Fee numerators are over 1e9 in both programs.
+pub const FEE_DENOMINATOR: u64 = 1_000_000_000;
+/// The programs never charge more than 99%.
+pub const MAX_FEE_NUMERATOR: u64 = 990_000_000;
+const BASIS_POINT_MAX: u64 = 10_000;
+const VARIABLE_FEE_SCALE: u128 = 100_000_000_000;
+/// Solana's target slot time, for slot-activated schedules.
+const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeSchedulerMode {
+    /// The fee drops by `reduction_factor` each period.
+    Linear,
+    /// The fee drops by `reduction_factor` bps of itself each period.
+    Exponential,
+    /// The fee rises with trade size instead; quoted at its cliff fee, the
+    /// fee small trades pay.
+    RateLimiter,
+}
+
+impl FeeSchedulerMode {
+    pub fn from_u8(mode: u8) -> Result<Self> {
+        Ok(match mode {
+            0 => Self::Linear,
+            1 => Self::Exponential,
+            2 => Self::RateLimiter,
+            other => bail!("unknown Meteora base fee mode {}", other),
+        })
+    }
+}
+
+/// What a pool's activation and fee periods are measured in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivationType {
+    Slot,
+    Timestamp,
+}
+
+impl ActivationType {
+    pub fn from_u8(activation_type: u8) -> Result<Self> {
+        Ok(match activation_type {
+            0 => Self::Slot,
+            1 => Self::Timestamp,
+            other => bail!("unknown Meteora activation type {}", other),
+        })
+    }
+
+    /// Roughly how long `points` take to pass.
+    pub fn duration(&self, points: u64) -> Duration {
+        match self {
+            Self::Slot => SLOT_DURATION * points.min(u32::MAX as u64) as u32,
+            Self::Timestamp => Duration::from_secs(points),
+        }
+    }
+}
+
+/// When a fee is evaluated: the cluster's slot and unix time, as the Clock
+/// sysvar reports them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FeeClock {
+    pub slot: u64,
+    pub unix_timestamp: u64,
+}
+
+impl FeeClock {
+    /// The point fee periods are counted in for `activation_type`.
+    pub fn point(&self, activation_type: ActivationType) -> u64 {
+        match activation_type {
+            ActivationType::Slot => self.slot,
+            ActivationType::Timestamp => self.unix_timestamp,
+        }
+    }
+
+    /// The clock `duration` from now, slots passing at the target slot time.
+    pub fn after(&self, duration: Duration) -> Self {
+        Self {
+            slot: self.slot + (duration.as_millis() / SLOT_DURATION.as_millis()) as u64,
+            unix_timestamp: self.unix_timestamp + duration.as_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseFee {
+    pub cliff_fee_numerator: u64,
+    pub number_of_period: u16,
+    /// Points per period; zero means the fee never decays.
+    pub period_frequency: u64,
+    pub reduction_factor: u64,
+    pub mode: FeeSchedulerMode,
+}
+
+impl BaseFee {
+    /// A fee that never changes.
+    #[cfg(test)]
+    pub fn constant(cliff_fee_numerator: u64) -> Self {
+        Self {
+            cliff_fee_numerator,
+            number_of_period: 0,
+            period_frequency: 0,
+            reduction_factor: 0,
+            mode: FeeSchedulerMode::Linear,
+        }
+    }
+
+    fn decays(&self) -> bool {
+        self.period_frequency > 0 && self.number_of_period > 0 && self.mode != FeeSchedulerMode::RateLimiter
+    }
+
+    /// Periods elapsed at `current_point`. Trades before activation (an
+    /// alpha vault's) already pay the fully decayed fee.
+    fn period(&self, activation_point: u64, current_point: u64) -> u64 {
+        if current_point < activation_point {
+            return self.number_of_period as u64;
+        }
+        ((current_point - activation_point) / self.period_frequency).min(self.number_of_period as u64)
+    }
+
+    fn numerator_at_period(&self, period: u64) -> u64 {
+        match self.mode {
+            FeeSchedulerMode::Linear => self.cliff_fee_numerator.saturating_sub(period.saturating_mul(self.reduction_factor)),
+            FeeSchedulerMode::Exponential => {
+                // cliff * (1 - reduction / 10_000)^period, in Q64.
+                let one = 1u128 << 64;
+                let keep = one - (self.reduction_factor.min(BASIS_POINT_MAX) as u128 * one / BASIS_POINT_MAX as u128);
+                let factor = pow_q64(keep, period);
+                U256::mul(self.cliff_fee_numerator as u128, factor).shr(64).low_u128() as u64
+            }
+            FeeSchedulerMode::RateLimiter => self.cliff_fee_numerator,
+        }
+    }
+
+    pub fn fee_numerator(&self, activation_point: u64, current_point: u64) -> u64 {
+        if !self.decays() {
+            return self.cliff_fee_numerator;
+        }
+        self.numerator_at_period(self.period(activation_point, current_point))
+    }
+
+    /// The base fee once every period has passed.
+    pub fn min_fee_numerator(&self) -> u64 {
+        if !self.decays() {
+            return self.cliff_fee_numerator;
+        }
+        self.numerator_at_period(self.number_of_period as u64)
+    }
+}
+
+/// `base^exp` for a Q64.64 `base` of at most one.
+fn pow_q64(base: u128, exp: u64) -> u128 {
+    let (mut result, mut base, mut exp) = (1u128 << 64, base, exp);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = U256::mul(result, base).shr(64).low_u128();
+        }
+        base = U256::mul(base, base).shr(64).low_u128();
+        exp >>= 1;
+    }
+    result
+}
+
+/// Volatility fee parameters. The accumulator itself lives with the pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynamicFee {
+    pub bin_step: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    /// Seconds after the last update within which swaps count as one burst.
+    pub filter_period: u16,
+    /// Seconds after the last update at which volatility is forgotten.
+    pub decay_period: u16,
+    /// Share of the accumulator kept between the two, in bps.
+    pub reduction_factor: u16,
+}
+
+impl DynamicFee {
+    /// The accumulator a swap at `now` starts from, given the one stored at
+    /// `last_update` (both unix seconds).
+    pub fn volatility_at(&self, accumulator: u128, last_update: u64, now: u64) -> u128 {
+        let elapsed = now.saturating_sub(last_update);
+        if elapsed < self.filter_period as u64 {
+            accumulator
+        } else if elapsed < self.decay_period as u64 {
+            accumulator * self.reduction_factor as u128 / BASIS_POINT_MAX as u128
+        } else {
+            0
+        }
+    }
+
+    pub fn variable_fee_numerator(&self, volatility_accumulator: u128) -> u64 {
+        let accumulator = volatility_accumulator.min(self.max_volatility_accumulator as u128);
+        let fee = (accumulator * self.bin_step as u128)
+            .checked_pow(2)
+            .and_then(|square| square.checked_mul(self.variable_fee_control as u128))
+            .map_or(u128::MAX, |fee| fee.div_ceil(VARIABLE_FEE_SCALE));
+        fee.min(MAX_FEE_NUMERATOR as u128) as u64
+    }
+}
+
+/// Everything that sets a pool's fee at a given point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSchedule {
+    pub base: BaseFee,
+    pub dynamic: Option<DynamicFee>,
+    pub volatility_accumulator: u128,
+    /// Unix time the accumulator was last updated.
+    pub last_volatility_update: u64,
+    pub activation_point: u64,
+    pub activation_type: ActivationType,
+}
+
+impl FeeSchedule {
+    /// A fixed fee with no schedule or volatility component.
+    #[cfg(test)]
+    pub fn constant(fee_numerator: u64) -> Self {
+        Self {
+            base: BaseFee::constant(fee_numerator),
+            dynamic: None,
+            volatility_accumulator: 0,
+            last_volatility_update: 0,
+            activation_point: 0,
+            activation_type: ActivationType::Timestamp,
+        }
+    }
+
+    pub fn at(&self, now: FeeClock) -> FeeQuote {
+        FeeQuote {
+            base_fee_numerator: self.base.fee_numerator(self.activation_point, now.point(self.activation_type)),
+            variable_fee_numerator: self.dynamic.map_or(0, |dynamic| {
+                let accumulator = dynamic.volatility_at(self.volatility_accumulator, self.last_volatility_update, now.unix_timestamp);
+                dynamic.variable_fee_numerator(accumulator)
+            }),
+        }
+    }
+
+    /// How long until the total fee is at most `max_fee_numerator`, or
+    /// `None` if it never falls that far. Assumes no swap adds volatility
+    /// in the meantime.
+    pub fn wait_until(&self, now: FeeClock, max_fee_numerator: u64) -> Option<Duration> {
+        // Both parts only fall, and only at a period boundary or a volatility
+        // decay step, so the first of those where the fee fits is the answer.
+        let mut steps = vec![Duration::ZERO];
+        if self.base.decays() {
+            let current_point = now.point(self.activation_type);
+            let current = self.base.period(self.activation_point, current_point);
+            steps.extend((current + 1..=self.base.number_of_period as u64).map(|period| {
+                let point = self.activation_point + period * self.base.period_frequency;
+                self.activation_type.duration(point - current_point)
+            }));
+        }
+        if let Some(dynamic) = self.dynamic {
+            for period in [dynamic.filter_period, dynamic.decay_period] {
+                let at = self.last_volatility_update + period as u64;
+                steps.push(Duration::from_secs(at.saturating_sub(now.unix_timestamp)));
+            }
+        }
+        steps.sort();
+        steps.into_iter().find(|&wait| self.at(now.after(wait)).fee_numerator() <= max_fee_numerator)
+    }
+}
+
+/// A pool's fee at one point, split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub base_fee_numerator: u64,
+    pub variable_fee_numerator: u64,
+}
+
+impl FeeQuote {
+    pub fn fee_numerator(&self) -> u64 {
+        (self.base_fee_numerator + self.variable_fee_numerator).min(MAX_FEE_NUMERATOR)
+    }
+
+    pub fn fee_bps(&self) -> f64 {
+        self.fee_numerator() as f64 * BASIS_POINT_MAX as f64 / FEE_DENOMINATOR as f64
+    }
+
+    /// The fee on `amount`, rounded up as the programs do.
+    pub fn fee_on(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_numerator() as u128).div_ceil(FEE_DENOMINATOR as u128) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(mode: FeeSchedulerMode, reduction_factor: u64) -> FeeSchedule {
+        FeeSchedule {
+            base: BaseFee {
+                // 50% at launch, decaying every 10 seconds for 10 periods.
+                cliff_fee_numerator: 500_000_000,
+                number_of_period: 10,
+                period_frequency: 10,
+                reduction_factor,
+                mode,
+            },
+            dynamic: None,
+            volatility_accumulator: 0,
+            last_volatility_update: 0,
+            activation_point: 1_000,
+            activation_type: ActivationType::Timestamp,
+        }
+    }
+
+    fn at(unix_timestamp: u64) -> FeeClock {
+        FeeClock { slot: unix_timestamp, unix_timestamp }
+    }
+
+    fn dynamic() -> DynamicFee {
+        DynamicFee {
+            bin_step: 100,
+            variable_fee_control: 100_000,
+            max_volatility_accumulator: 350_000,
+            filter_period: 10,
+            decay_period: 120,
+            reduction_factor: 5_000,
+        }
+    }
+
+    #[test]
+    fn base_fee_decays_per_period_and_stops() {
+        let linear = schedule(FeeSchedulerMode::Linear, 49_000_000);
+        assert_eq!(linear.at(at(1_000)).base_fee_numerator, 500_000_000);
+        assert_eq!(linear.at(at(1_009)).base_fee_numerator, 500_000_000);
+        assert_eq!(linear.at(at(1_010)).base_fee_numerator, 451_000_000);
+        assert_eq!(linear.at(at(1_100)).base_fee_numerator, 10_000_000);
+        assert_eq!(linear.at(at(5_000)).base_fee_numerator, 10_000_000);
+        // Before activation only an alpha vault trades, at the decayed fee.
+        assert_eq!(linear.at(at(999)).base_fee_numerator, 10_000_000);
+
+        // Halving ten times: 500_000_000 / 1024.
+        let exponential = schedule(FeeSchedulerMode::Exponential, 5_000);
+        assert_eq!(exponential.at(at(1_010)).base_fee_numerator, 250_000_000);
+        assert!(exponential.at(at(1_100)).base_fee_numerator.abs_diff(488_281) <= 1);
+        assert_eq!(exponential.base.min_fee_numerator(), exponential.at(at(1_100)).base_fee_numerator);
+
+        let rate_limited = schedule(FeeSchedulerMode::RateLimiter, 5_000);
+        assert_eq!(rate_limited.at(at(2_000)).base_fee_numerator, 500_000_000);
+    }
+
+    #[test]
+    fn volatility_adds_a_capped_variable_fee() {
+        let dynamic = dynamic();
+        // (100_000 * 100)^2 * 100_000 / 1e11 = 100_000_000, i.e. 10%.
+        assert_eq!(dynamic.variable_fee_numerator(100_000), 100_000_000);
+        assert_eq!(dynamic.variable_fee_numerator(1_000_000), dynamic.variable_fee_numerator(350_000));
+        assert_eq!(dynamic.variable_fee_numerator(0), 0);
+
+        let volatile = FeeSchedule {
+            dynamic: Some(dynamic),
+            volatility_accumulator: 100_000,
+            last_volatility_update: 1_095,
+            ..schedule(FeeSchedulerMode::Linear, 49_000_000)
+        };
+        let quote = volatile.at(at(1_100));
+        assert_eq!(quote.fee_numerator(), 110_000_000);
+        assert_eq!(quote.fee_bps(), 1_100.0);
+        assert_eq!(quote.fee_on(1_000_001), 110_001);
+    }
+
+    #[test]
+    fn volatility_decays_after_the_filter_period_and_resets_after_decay() {
+        let volatile = FeeSchedule {
+            dynamic: Some(dynamic()),
+            volatility_accumulator: 100_000,
+            last_volatility_update: 2_000,
+            ..schedule(FeeSchedulerMode::Linear, 49_000_000)
+        };
+        // Within the filter period the burst keeps its full accumulator: 10%.
+        assert_eq!(volatile.at(at(2_009)).variable_fee_numerator, 100_000_000);
+        // Then half of it, squared in the fee: 2.5%.
+        assert_eq!(volatile.at(at(2_010)).variable_fee_numerator, 25_000_000);
+        assert_eq!(volatile.at(at(2_119)).variable_fee_numerator, 25_000_000);
+        // A pool quiet for the decay period charges no variable fee at all.
+        assert_eq!(volatile.at(at(2_120)).variable_fee_numerator, 0);
+
+        // 1% base + 10% variable now; 1% + 2.5% once filtered; 1% once decayed.
+        assert_eq!(volatile.wait_until(at(2_005), 110_000_000), Some(Duration::ZERO));
+        assert_eq!(volatile.wait_until(at(2_005), 40_000_000), Some(Duration::from_secs(5)));
+        assert_eq!(volatile.wait_until(at(2_005), 10_000_000), Some(Duration::from_secs(115)));
+        assert_eq!(volatile.wait_until(at(2_005), 9_999_999), None);
+    }
+
+    #[test]
+    fn waits_for_the_first_period_under_the_cap() {
+        let linear = schedule(FeeSchedulerMode::Linear, 49_000_000);
+        assert_eq!(linear.wait_until(at(1_003), 500_000_000), Some(Duration::ZERO));
+        // 10% is first reached in period 9, at point 1_090.
+        assert_eq!(linear.wait_until(at(1_003), 100_000_000), Some(Duration::from_secs(87)));
+        assert_eq!(linear.wait_until(at(1_003), 1_000_000), None);
+
+        let slots = FeeSchedule { activation_type: ActivationType::Slot, ..linear };
+        assert_eq!(slots.wait_until(at(1_005), 451_000_000), Some(Duration::from_millis(2_000)));
+        assert_eq!(FeeSchedule::constant(500_000_000).wait_until(at(0), 100_000_000), None);
+    }
+}
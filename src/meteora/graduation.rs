@@ -0,0 +1,275 @@
+use super::{DbcProgress, DbcSide, DbcStatus, MeteoraDammPool, MeteoraDbcPool, SwapFill};
+use crate::config::Config;
+use crate::trade::{self, Quote};
+use crate::venue::{BondingCurveVenue, SolanaApi};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+
+/// A step of a token's graduation from its DBC curve to DAMM v2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraduationEvent {
+    /// The curve raised its migration threshold; trading on it has stopped.
+    CurveCompleted(DbcProgress),
+    /// The DAMM v2 pool the curve migrated into is live.
+    Migrated { token_mint: String, dbc_pool: String, damm_pool: String },
+}
+
+/// Polls one token's curve and reports each graduation step once, in order.
+/// Both events fire on the same poll when the watcher starts after migration.
+pub struct GraduationWatcher {
+    venue: Arc<dyn BondingCurveVenue>,
+    token_mint: String,
+    poll_interval: Duration,
+    completed: Option<DbcProgress>,
+    migrated: bool,
+}
+
+impl GraduationWatcher {
+    pub fn new(venue: Arc<dyn BondingCurveVenue>, token_mint: &str, poll_interval: Duration) -> Self {
+        Self {
+            venue,
+            token_mint: token_mint.to_string(),
+            poll_interval,
+            completed: None,
+            migrated: false,
+        }
+    }
+
+    pub fn from_config(venue: Arc<dyn BondingCurveVenue>, token_mint: &str, cfg: &Config) -> Self {
+        Self::new(venue, token_mint, Duration::from_millis(cfg.dbc_progress_poll_ms))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.migrated
+    }
+
+    /// Reads the curve once and returns the steps reached since the last poll.
+    pub async fn poll(&mut self) -> Result<Vec<GraduationEvent>> {
+        let mut events = Vec::new();
+        if self.migrated {
+            return Ok(events);
+        }
+
+        if self.completed.is_none() {
+            let progress = self.venue.curve_progress(&self.token_mint).await?;
+            log::debug!("{} curve at {:.2}% ({:?})", self.token_mint, progress.percent(), progress.status);
+            if progress.status == DbcStatus::Active {
+                return Ok(events);
+            }
+            events.push(GraduationEvent::CurveCompleted(progress.clone()));
+            self.completed = Some(progress);
+        }
+
+        if let Some(damm_pool) = self.venue.find_damm_v2_pool(&self.token_mint).await? {
+            self.migrated = true;
+            events.push(GraduationEvent::Migrated {
+                token_mint: self.token_mint.clone(),
+                dbc_pool: self.completed.as_ref().map(|progress| progress.pool.clone()).unwrap_or_default(),
+                damm_pool,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Runs the watcher on a background task until the token has migrated
+    /// or the receiver is dropped.
+    pub fn spawn(mut self, buffer: usize) -> mpsc::Receiver<GraduationEvent> {
+        let (tx, rx) = mpsc::channel(buffer);
+        tokio::spawn(async move {
+            loop {
+                let wait = match self.poll().await {
+                    Ok(events) => {
+                        for event in events {
+                            if tx.send(event).await.is_err() {
+                                return;
+                            }
+                        }
+                        self.poll_interval
+                    }
+                    Err(e) => {
+                        log::warn!("Graduation poll for {} failed: {:#}", self.token_mint, e);
+                        self.poll_interval * 2
+                    }
+                };
+
+                if self.is_done() || tx.is_closed() {
+                    return;
+                }
+                sleep(wait).await;
+            }
+        });
+        rx
+    }
+}
+
+/// Where a launched token trades right now.
+#[derive(Debug, Clone)]
+pub enum TokenMarket {
+    Curve(MeteoraDbcPool),
+    /// The DAMM v2 pool the curve graduated into; the token's mint is kept
+    /// since either side of the pool may hold it.
+    Damm { pool: MeteoraDammPool, token_mint: String },
+}
+
+impl TokenMarket {
+    /// Resolves `token_mint`'s market, following it onto DAMM v2 once its
+    /// curve has migrated. A completed curve that is still waiting for
+    /// migration is watched until the DAMM v2 pool is live.
+    pub async fn resolve(venue: Arc<dyn BondingCurveVenue>, token_mint: &str, cfg: &Config) -> Result<Self> {
+        let progress = venue.curve_progress(token_mint).await?;
+        match progress.status {
+            DbcStatus::Active => {
+                Ok(Self::Curve(venue.get_dbc_pool_info(&progress.pool).await?))
+            }
+            DbcStatus::Migrated => {
+                let dbc_pool = venue.get_dbc_pool_info(&progress.pool).await?;
+                let pool = venue.migrate_to_damm(&dbc_pool).await?;
+                Ok(Self::Damm { pool, token_mint: token_mint.to_string() })
+            }
+            DbcStatus::Completed => {
+                log::info!("{} curve is complete; waiting for its DAMM v2 pool", token_mint);
+                let mut events = GraduationWatcher::from_config(venue.clone(), token_mint, cfg).spawn(4);
+                while let Some(event) = events.recv().await {
+                    if let GraduationEvent::Migrated { damm_pool, .. } = event {
+                        let pool = venue.get_damm_pool(&damm_pool).await?;
+                        return Ok(Self::Damm { pool, token_mint: token_mint.to_string() });
+                    }
+                }
+                Err(anyhow!("Graduation watch for {} stopped before its DAMM v2 pool appeared", token_mint))
+            }
+        }
+    }
+
+    /// Venue name as recorded on fills.
+    pub fn venue_name(&self) -> &'static str {
+        match self {
+            Self::Curve(_) => "meteora-dbc",
+            Self::Damm { .. } => "meteora-damm-v2",
+        }
+    }
+
+    pub fn pool_address(&self) -> &str {
+        match self {
+            Self::Curve(pool) => &pool.address,
+            Self::Damm { pool, .. } => &pool.address,
+        }
+    }
+
+    /// Quotes selling `amount_in` of the token, with price impact measured
+    /// against a probe of 0.1% of the size, see [`trade::price_impact_bps`].
+    pub async fn quote_sell(&self, venue: &dyn BondingCurveVenue, amount_in: u64) -> Result<Quote> {
+        let (amount_out, fee) = self.quote_exact_in(venue, amount_in).await?;
+        let probe_in = amount_in / 1_000;
+        let probe_out = if probe_in > 0 { self.quote_exact_in(venue, probe_in).await?.0 } else { 0 };
+        Ok(Quote { amount_in, amount_out, fee, price_impact_bps: trade::price_impact_bps(amount_in, amount_out, probe_in, probe_out) })
+    }
+
+    async fn quote_exact_in(&self, venue: &dyn BondingCurveVenue, amount_in: u64) -> Result<(u64, u64)> {
+        match self {
+            Self::Curve(pool) => venue.quote_dbc_swap(pool, DbcSide::Sell, amount_in).await,
+            Self::Damm { pool, token_mint } => venue.quote_damm_swap(pool, token_mint, amount_in).await,
+        }
+    }
+
+    pub async fn sell(
+        &self,
+        venue: &dyn BondingCurveVenue,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        match self {
+            Self::Curve(pool) => venue.swap_dbc(pool, DbcSide::Sell, amount_in, min_out, solana_client).await,
+            Self::Damm { pool, token_mint } => venue.swap_damm(pool, token_mint, amount_in, min_out, solana_client).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockBondingCurve;
+
+    fn progress(quote_reserve: u64, status: DbcStatus) -> DbcProgress {
+        DbcProgress {
+            pool: "DbcPool1".to_string(),
+            token_mint: "Mint1".to_string(),
+            quote_mint: "So11111111111111111111111111111111111111112".to_string(),
+            quote_decimals: 9,
+            quote_reserve,
+            migration_quote_threshold: 85_000_000_000,
+            status,
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_completion_then_the_damm_pool_once_each() {
+        let curve = Arc::new(MockBondingCurve::default().with_progress(progress(40_000_000_000, DbcStatus::Active)));
+        let mut watcher = GraduationWatcher::new(curve.clone(), "Mint1", Duration::from_millis(1));
+
+        assert!(watcher.poll().await.unwrap().is_empty());
+
+        curve.progress.lock().unwrap().insert("Mint1".to_string(), progress(85_000_000_000, DbcStatus::Completed));
+        let events = watcher.poll().await.unwrap();
+        assert_eq!(events, [GraduationEvent::CurveCompleted(progress(85_000_000_000, DbcStatus::Completed))]);
+        assert!(watcher.poll().await.unwrap().is_empty());
+
+        curve.damm_pools.lock().unwrap().insert("Mint1".to_string(), "DammPool1".to_string());
+        let mut events = GraduationWatcher::spawn(watcher, 4);
+        assert_eq!(
+            events.recv().await,
+            Some(GraduationEvent::Migrated {
+                token_mint: "Mint1".to_string(),
+                dbc_pool: "DbcPool1".to_string(),
+                damm_pool: "DammPool1".to_string(),
+            })
+        );
+        assert_eq!(events.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn market_follows_the_token_onto_its_damm_pool() {
+        let cfg = Config::from_env().unwrap();
+        let curve = Arc::new(
+            MockBondingCurve::default()
+                .with_progress(progress(40_000_000_000, DbcStatus::Active))
+                .with_pool(MeteoraDbcPool {
+                    address: "DbcPool1".to_string(),
+                    token_mint: "Mint1".to_string(),
+                    base_token: "SOL".to_string(),
+                    current_price: 0.001,
+                    curve: crate::meteora::dbc::Curve::default(),
+                    status: DbcStatus::Active,
+                }),
+        );
+        let market = TokenMarket::resolve(curve.clone(), "Mint1", &cfg).await.unwrap();
+        assert_eq!((market.venue_name(), market.pool_address()), ("meteora-dbc", "DbcPool1"));
+
+        curve.progress.lock().unwrap().insert("Mint1".to_string(), progress(85_000_000_000, DbcStatus::Migrated));
+        assert!(TokenMarket::resolve(curve.clone(), "Mint1", &cfg).await.is_err());
+
+        curve.damm_pools.lock().unwrap().insert("Mint1".to_string(), "DammPool1".to_string());
+        curve.damms.lock().unwrap().insert("DammPool1".to_string(), MeteoraDammPool {
+            address: "DammPool1".to_string(),
+            token_a: "Mint1".to_string(),
+            token_b: "So11111111111111111111111111111111111111112".to_string(),
+            reserve_a: 1_000_000,
+            reserve_b: 1_000_000,
+            fee_rate: 0.0,
+        });
+        let market = TokenMarket::resolve(curve, "Mint1", &cfg).await.unwrap();
+        assert_eq!((market.venue_name(), market.pool_address()), ("meteora-damm-v2", "DammPool1"));
+    }
+
+    #[test]
+    fn progress_is_a_share_of_the_threshold() {
+        let halfway = progress(42_500_000_000, DbcStatus::Active);
+        assert_eq!(halfway.percent(), 50.0);
+        assert_eq!(halfway.quote_remaining(), 42_500_000_000);
+        assert_eq!(progress(90_000_000_000, DbcStatus::Completed).percent(), 100.0);
+        assert_eq!(DbcProgress { migration_quote_threshold: 0, ..halfway }.percent(), 0.0);
+    }
+}
@@ -0,0 +1,614 @@
+pub mod damm_v2;
+pub mod dbc;
+pub mod fee;
+pub mod graduation;
+
+use crate::dex::math::U256;
+use crate::dex::{get_program_accounts, layout};
+use crate::trade;
+use crate::venue::SolanaApi;
+use anyhow::{anyhow, bail, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction, sysvar,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id, instruction::create_associated_token_account_idempotent,
+};
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub struct MeteoraDbcPool {
+    pub address: String,
+    pub token_mint: String,
+    pub base_token: String,
+    pub current_price: f64,
+    pub curve: dbc::Curve,
+    pub status: DbcStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbcStatus {
+    Active,
+    /// The curve raised its migration threshold and no longer trades.
+    Completed,
+    Migrated,
+}
+
+/// How far a curve is from graduating to DAMM v2, in quote-token units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DbcProgress {
+    pub pool: String,
+    pub token_mint: String,
+    pub quote_mint: String,
+    pub quote_decimals: u8,
+    pub quote_reserve: u64,
+    /// Zero when the pool's config sets none.
+    pub migration_quote_threshold: u64,
+    pub status: DbcStatus,
+}
+
+impl DbcProgress {
+    /// Share of the threshold raised, capped at 100.
+    pub fn percent(&self) -> f64 {
+        if self.migration_quote_threshold == 0 {
+            return 0.0;
+        }
+        (self.quote_reserve as f64 * 100.0 / self.migration_quote_threshold as f64).min(100.0)
+    }
+
+    /// Quote still to be bought into the curve before it completes, net of fees.
+    pub fn quote_remaining(&self) -> u64 {
+        self.migration_quote_threshold.saturating_sub(self.quote_reserve)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbcSide {
+    /// Spend the quote token on the launched token.
+    Buy,
+    /// Sell the launched token back into the curve.
+    Sell,
+}
+
+/// A confirmed DBC or DAMM v2 swap. `amount_out` is what arrived in the wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapFill {
+    pub signature: Signature,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct MeteoraDammPool {
+    pub address: String,
+    pub token_a: String,
+    pub token_b: String,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub fee_rate: f64,
+}
+
+/// A confirmed change to a DAMM v2 position, with the quoted token amounts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DammPositionChange {
+    pub position: String,
+    pub signature: Signature,
+    pub liquidity_delta: u128,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+/// LP fees a DAMM v2 position can claim now, in each token's base units.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DammPositionFees {
+    pub position: String,
+    pub pool: String,
+    pub token_a: String,
+    pub token_b: String,
+    pub fee_a: u64,
+    pub fee_b: u64,
+}
+
+impl DammPositionFees {
+    pub fn is_zero(&self) -> bool {
+        self.fee_a == 0 && self.fee_b == 0
+    }
+}
+
+pub struct MeteoraClient {
+    rpc_client: RpcClient,
+    dbc_program_id: Pubkey,
+    damm_program_id: Pubkey,
+}
+
+impl MeteoraClient {
+    pub fn new(rpc_url: &str) -> Result<Self> {
+        Ok(Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_url.to_string(), CommitmentConfig::confirmed()),
+            dbc_program_id: dbc::DBC_PROGRAM_ID,
+            damm_program_id: damm_v2::DAMM_V2_PROGRAM_ID,
+        })
+    }
+
+    /// The live (not yet migrated) DBC pool for `token_mint`.
+    pub async fn find_dbc_pool(&self, token_mint: &str) -> Result<MeteoraDbcPool> {
+        let mint = Pubkey::from_str(token_mint)?;
+        for (address, pool) in self.virtual_pools(&mint)? {
+            if !pool.is_migrated {
+                return self.get_dbc_pool_info(&address.to_string()).await;
+            }
+        }
+        Err(anyhow!("No active Meteora DBC pool for {}", token_mint))
+    }
+
+    /// Graduation progress of `token_mint`'s DBC pool, migrated or not.
+    pub async fn curve_progress(&self, token_mint: &str) -> Result<DbcProgress> {
+        let mint = Pubkey::from_str(token_mint)?;
+        let mut pools = self.virtual_pools(&mint)?;
+        // A live pool wins over a migrated one for the same mint.
+        pools.sort_by_key(|(_, pool)| pool.is_migrated);
+        let (address, pool) = pools.into_iter().next()
+            .ok_or_else(|| anyhow!("No Meteora DBC pool for {}", token_mint))?;
+
+        let [config] = self.fetch_accounts([pool.config])?;
+        let config = dbc::PoolConfig::decode(&config.data)?;
+        let [quote_mint] = self.fetch_accounts([config.quote_mint])?;
+        Ok(DbcProgress {
+            pool: address.to_string(),
+            token_mint: token_mint.to_string(),
+            quote_mint: config.quote_mint.to_string(),
+            quote_decimals: layout::mint_decimals(&quote_mint.data)?,
+            quote_reserve: pool.quote_reserve,
+            migration_quote_threshold: config.migration_quote_threshold,
+            status: if pool.is_migrated {
+                DbcStatus::Migrated
+            } else if pool.is_curve_complete(&config) {
+                DbcStatus::Completed
+            } else {
+                DbcStatus::Active
+            },
+        })
+    }
+
+    /// The DAMM v2 pool `token_mint`'s curve migrated into, once it has.
+    /// Only a pool that [`damm_v2::Pool::is_dbc_migration_of`] a migrated
+    /// curve for the mint qualifies, never just any pool trading it.
+    pub async fn find_damm_v2_pool(&self, token_mint: &str) -> Result<Option<String>> {
+        let mint = Pubkey::from_str(token_mint)?;
+        let mut quote_mints = Vec::new();
+        for (_, pool) in self.virtual_pools(&mint)? {
+            if pool.is_migrated {
+                let [config] = self.fetch_accounts([pool.config])?;
+                quote_mints.push(dbc::PoolConfig::decode(&config.data)?.quote_mint);
+            }
+        }
+        if quote_mints.is_empty() {
+            return Ok(None);
+        }
+
+        let filters = vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &damm_v2::Pool::discriminator())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(damm_v2::POOL_TOKEN_A_MINT_OFFSET, mint.as_ref())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(damm_v2::POOL_CREATOR_OFFSET, dbc::pool_authority().as_ref())),
+        ];
+        for (address, account) in get_program_accounts(&self.rpc_client, &self.damm_program_id, filters)? {
+            let pool = damm_v2::Pool::decode(&account.data)?;
+            if quote_mints.iter().any(|quote_mint| pool.is_dbc_migration_of(&mint, quote_mint)) {
+                return Ok(Some(address.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Quotes spending `amount_base` whole quote tokens on the pool's curve
+    /// as it is now. The quote's `amount_in` is in the quote mint's base
+    /// units; its impact is measured against a probe of 0.1% of the size,
+    /// see [`trade::price_impact_bps`].
+    pub async fn buy_from_dbc(
+        &self,
+        pool: &MeteoraDbcPool,
+        amount_base: f64,
+    ) -> Result<trade::Quote> {
+        log::info!("Buying tokens from DBC pool: {}", pool.address);
+
+        let (state, config) = self.fetch_dbc_pool(&pool.address)?;
+        if state.is_migrated || state.is_curve_complete(&config) {
+            bail!("DBC pool {} has completed its curve and no longer trades", pool.address);
+        }
+        let [quote_mint] = self.fetch_accounts([config.quote_mint])?;
+        let amount_in = (amount_base * 10f64.powi(layout::mint_decimals(&quote_mint.data)? as i32)) as u64;
+        let now = fee_clock(&self.rpc_client)?;
+        let quote = state.quote_exact_in(&config, amount_in, false, now)?;
+        let probe_in = amount_in / 1_000;
+        let probe_out = if probe_in > 0 { state.quote_exact_in(&config, probe_in, false, now)?.amount_out } else { 0 };
+        let price_impact_bps = trade::price_impact_bps(amount_in, quote.amount_out, probe_in, probe_out);
+
+        Ok(trade::Quote { amount_in, amount_out: quote.amount_out, fee: quote.fee, price_impact_bps })
+    }
+
+    /// The curve's fee schedule and the cluster clock to evaluate it at.
+    pub async fn dbc_fee_schedule(&self, pool: &MeteoraDbcPool) -> Result<(fee::FeeSchedule, fee::FeeClock)> {
+        let (state, config) = self.fetch_dbc_pool(&pool.address)?;
+        Ok((state.fee_schedule(&config), fee_clock(&self.rpc_client)?))
+    }
+
+    /// Exact-input quote on the curve as it is now: `(amount_out, fee)`.
+    pub async fn quote_dbc_swap(&self, pool: &MeteoraDbcPool, side: DbcSide, amount_in: u64) -> Result<(u64, u64)> {
+        let (state, config) = self.fetch_dbc_pool(&pool.address)?;
+        let now = fee_clock(&self.rpc_client)?;
+        let quote = state.quote_exact_in(&config, amount_in, side == DbcSide::Sell, now)?;
+        Ok((quote.amount_out, quote.fee))
+    }
+
+    /// Swaps exactly `amount_in` on the curve from `solana_client`'s wallet,
+    /// reverting on-chain if less than `min_out` would arrive. Native SOL is
+    /// wrapped for buys; sale proceeds stay in the wrapped SOL account,
+    /// where the fill is measured.
+    pub async fn swap_dbc(
+        &self,
+        pool: &MeteoraDbcPool,
+        side: DbcSide,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        let address = Pubkey::from_str(&pool.address).context("invalid DBC pool address")?;
+        let (state, config) = self.fetch_dbc_pool(&pool.address)?;
+        if state.is_migrated {
+            bail!("DBC pool {} has migrated to DAMM v2 and no longer trades", pool.address);
+        }
+
+        let wallet = solana_client.wallet_pubkey();
+        let base = (state.base_mint, state.base_token_program());
+        let quote = (config.quote_mint, config.quote_token_program());
+        let (input, output) = match side {
+            DbcSide::Buy => (quote, base),
+            DbcSide::Sell => (base, quote),
+        };
+        let (input_account, output_account) = swap_accounts(&wallet, input, output);
+        let params = dbc::SwapParams { amount_in, minimum_amount_out: min_out, base_for_quote: side == DbcSide::Sell };
+        let swap = dbc::swap(address, &state, &config, wallet, input_account, output_account, &params);
+        send_swap(solana_client, input, output, amount_in, swap).await
+    }
+
+    /// The DAMM v2 pool `dbc_pool` graduated into. Migration itself is run
+    /// by Meteora's keeper once the curve completes; this only resolves it.
+    pub async fn migrate_to_damm(&self, dbc_pool: &MeteoraDbcPool) -> Result<MeteoraDammPool> {
+        let damm_pool = self.find_damm_v2_pool(&dbc_pool.token_mint).await?
+            .ok_or_else(|| anyhow!("DBC pool {} has not migrated to DAMM v2 yet ({:?})", dbc_pool.address, dbc_pool.status))?;
+        log::info!("DBC pool {} migrated to DAMM v2 pool {}", dbc_pool.address, damm_pool);
+        self.get_damm_pool(&damm_pool).await
+    }
+
+    pub async fn get_damm_pool(&self, pool_address: &str) -> Result<MeteoraDammPool> {
+        let (_, state) = self.fetch_damm_pool(pool_address)?;
+        let [vault_a, vault_b] = self.fetch_accounts([state.token_a_vault, state.token_b_vault])?;
+        Ok(MeteoraDammPool {
+            address: pool_address.to_string(),
+            token_a: state.token_a_mint.to_string(),
+            token_b: state.token_b_mint.to_string(),
+            reserve_a: layout::token_account_amount(&vault_a.data)?,
+            reserve_b: layout::token_account_amount(&vault_b.data)?,
+            fee_rate: state.fees.at(fee_clock(&self.rpc_client)?).fee_bps() / 10_000.0,
+        })
+    }
+
+    /// Exact-input quote on the pool as it is now: `(amount_out, fee)`.
+    pub async fn quote_damm_swap(&self, pool: &MeteoraDammPool, input_mint: &str, amount_in: u64) -> Result<(u64, u64)> {
+        let (_, state) = self.fetch_damm_pool(&pool.address)?;
+        let now = fee_clock(&self.rpc_client)?;
+        state.quote_exact_in(amount_in, damm_direction(&state, input_mint)?, now)
+    }
+
+    /// Swaps exactly `amount_in` of `input_mint` on a DAMM v2 pool, with the
+    /// same wrapping and fill measurement as [`Self::swap_dbc`].
+    pub async fn swap_damm(
+        &self,
+        pool: &MeteoraDammPool,
+        input_mint: &str,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        let (address, state) = self.fetch_damm_pool(&pool.address)?;
+        let a = (state.token_a_mint, state.token_a_program());
+        let b = (state.token_b_mint, state.token_b_program());
+        let (input, output) = if damm_direction(&state, input_mint)? { (a, b) } else { (b, a) };
+
+        let wallet = solana_client.wallet_pubkey();
+        let (input_account, output_account) = swap_accounts(&wallet, input, output);
+        let params = damm_v2::SwapParams { amount_in, minimum_amount_out: min_out };
+        let swap = damm_v2::swap(address, &state, wallet, input_account, output_account, &params);
+        send_swap(solana_client, input, output, amount_in, swap).await
+    }
+
+    /// Opens a new position in `pool` and deposits up to `max_a` / `max_b`
+    /// into it at the current price, in one transaction.
+    pub async fn open_damm_position(
+        &self,
+        pool: &MeteoraDammPool,
+        max_a: u64,
+        max_b: u64,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        let (address, state) = self.fetch_damm_pool(&pool.address)?;
+        let nft_mint = Keypair::new();
+        let position = damm_v2::Position {
+            pool: address,
+            nft_mint: nft_mint.pubkey(),
+            fee_a_per_token_checkpoint: U256::ZERO,
+            fee_b_per_token_checkpoint: U256::ZERO,
+            fee_a_pending: 0,
+            fee_b_pending: 0,
+            unlocked_liquidity: 0,
+            vested_liquidity: 0,
+            permanent_locked_liquidity: 0,
+        };
+        let owner = solana_client.wallet_pubkey();
+        let keys = position_keys(address, damm_v2::position_address(&position.nft_mint), &owner, &state);
+        let (liquidity_delta, a, b) = deposit_amounts(&state, max_a, max_b)?;
+
+        let instructions = [
+            damm_v2::create_position(address, owner, owner, position.nft_mint),
+            damm_v2::add_liquidity(&keys, &state, &position, liquidity_delta, trade::max_in(a, slippage_bps), trade::max_in(b, slippage_bps)),
+        ];
+        let signature = solana_client.send_instructions_with_signers(&instructions, &[&nft_mint]).await
+            .with_context(|| format!("opening a position in {} failed", pool.address))?;
+        Ok(DammPositionChange { position: keys.position.to_string(), signature, liquidity_delta, token_a_amount: a, token_b_amount: b })
+    }
+
+    /// Withdraws `liquidity` from a position, or all of its unlocked
+    /// liquidity when `None`.
+    pub async fn decrease_damm_position(
+        &self,
+        position: &str,
+        liquidity: Option<u128>,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        let owner = solana_client.wallet_pubkey();
+        let (keys, state, position) = self.fetch_damm_position(position, &owner)?;
+        let liquidity_delta = liquidity.unwrap_or(position.unlocked_liquidity);
+        if liquidity_delta == 0 || liquidity_delta > position.unlocked_liquidity {
+            bail!("Cannot withdraw {} liquidity from {}: {} unlocked", liquidity_delta, keys.position, position.unlocked_liquidity);
+        }
+        let (a, b) = state.amounts_for_liquidity(liquidity_delta, false)?;
+        let instructions = [
+            create_associated_token_account_idempotent(&owner, &owner, &state.token_a_mint, &state.token_a_program()),
+            create_associated_token_account_idempotent(&owner, &owner, &state.token_b_mint, &state.token_b_program()),
+            damm_v2::remove_liquidity(&keys, &state, &position, liquidity_delta, trade::min_out(a, slippage_bps), trade::min_out(b, slippage_bps)),
+        ];
+        let signature = solana_client.send_instructions(&instructions).await?;
+        Ok(DammPositionChange { position: keys.position.to_string(), signature, liquidity_delta, token_a_amount: a, token_b_amount: b })
+    }
+
+    pub async fn get_damm_position(&self, position: &str) -> Result<damm_v2::Position> {
+        let address = Pubkey::from_str(position).context("invalid position address")?;
+        let [account] = self.fetch_accounts([address])?;
+        damm_v2::Position::decode(&account.data)
+    }
+
+    /// Closes an empty position, burning its NFT and reclaiming the rent.
+    pub async fn close_damm_position(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        let owner = solana_client.wallet_pubkey();
+        let (keys, _, position) = self.fetch_damm_position(position, &owner)?;
+        if !position.is_empty() {
+            bail!(
+                "Position {} still holds {} liquidity and {} / {} unclaimed fees; withdraw and claim first",
+                keys.position,
+                position.unlocked_liquidity + position.vested_liquidity + position.permanent_locked_liquidity,
+                position.fee_a_pending,
+                position.fee_b_pending
+            );
+        }
+        solana_client.send_instructions(&[damm_v2::close_position(&keys, &position)]).await
+    }
+
+    /// Every DAMM v2 position `owner` holds the NFT of, with its unclaimed
+    /// fees. Positions are found through the owner's Token-2022 accounts.
+    pub async fn unclaimed_damm_fees(&self, owner: &Pubkey) -> Result<Vec<DammPositionFees>> {
+        let filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(32, owner.as_ref()))];
+        let nft_accounts = get_program_accounts(&self.rpc_client, &damm_v2::TOKEN_2022_PROGRAM_ID, filters)?;
+        let candidates: Vec<Pubkey> = nft_accounts.iter()
+            .filter(|(_, account)| layout::token_account_amount(&account.data).is_ok_and(|amount| amount == 1))
+            .filter_map(|(_, account)| layout::read_pubkey(&account.data, 0).ok())
+            .map(|nft_mint| damm_v2::position_address(&nft_mint))
+            .collect();
+
+        let mut positions = Vec::new();
+        for chunk in candidates.chunks(100) {
+            let accounts = self.rpc_client.get_multiple_accounts(chunk)?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                // Most NFTs are not positions; their PDA simply does not exist.
+                let Some(account) = account.filter(|account| account.owner == self.damm_program_id) else { continue };
+                positions.push((*address, damm_v2::Position::decode(&account.data)?));
+            }
+        }
+
+        let mut fees = Vec::with_capacity(positions.len());
+        for (address, position) in positions {
+            let [pool] = self.fetch_accounts([position.pool])?;
+            let pool = damm_v2::Pool::decode(&pool.data)?;
+            let (fee_a, fee_b) = position.unclaimed_fees(&pool)?;
+            fees.push(DammPositionFees {
+                position: address.to_string(),
+                pool: position.pool.to_string(),
+                token_a: pool.token_a_mint.to_string(),
+                token_b: pool.token_b_mint.to_string(),
+                fee_a,
+                fee_b,
+            });
+        }
+        Ok(fees)
+    }
+
+    /// Claims a position's LP fees into the owner's token accounts.
+    pub async fn claim_damm_fees(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        let owner = solana_client.wallet_pubkey();
+        let (keys, state, position) = self.fetch_damm_position(position, &owner)?;
+        let instructions = [
+            create_associated_token_account_idempotent(&owner, &owner, &state.token_a_mint, &state.token_a_program()),
+            create_associated_token_account_idempotent(&owner, &owner, &state.token_b_mint, &state.token_b_program()),
+            damm_v2::claim_position_fee(&keys, &state, &position),
+        ];
+        solana_client.send_instructions(&instructions).await
+            .with_context(|| format!("claiming fees for position {} failed", keys.position))
+    }
+
+    /// Decodes the pool, its config and both mints. `token_mint` is the
+    /// launched (base) token and `base_token` the quote it trades against.
+    pub async fn get_dbc_pool_info(&self, pool_address: &str) -> Result<MeteoraDbcPool> {
+        let (state, config) = self.fetch_dbc_pool(pool_address)?;
+        let [base_mint, quote_mint] = self.fetch_accounts([state.base_mint, config.quote_mint])?;
+        let status = if state.is_migrated {
+            DbcStatus::Migrated
+        } else if state.is_curve_complete(&config) {
+            DbcStatus::Completed
+        } else {
+            DbcStatus::Active
+        };
+        Ok(MeteoraDbcPool {
+            address: pool_address.to_string(),
+            token_mint: state.base_mint.to_string(),
+            base_token: config.quote_mint.to_string(),
+            current_price: dbc::price_from_sqrt_price(
+                state.sqrt_price,
+                layout::mint_decimals(&base_mint.data)?,
+                layout::mint_decimals(&quote_mint.data)?,
+            ),
+            curve: config.curve,
+            status,
+        })
+    }
+
+    /// Every DBC virtual pool selling `mint`, migrated or not.
+    fn virtual_pools(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, dbc::VirtualPool)>> {
+        let filters = vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &dbc::VirtualPool::discriminator())),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(dbc::VIRTUAL_POOL_BASE_MINT_OFFSET, mint.as_ref())),
+        ];
+        get_program_accounts(&self.rpc_client, &self.dbc_program_id, filters)?
+            .into_iter()
+            .map(|(address, account)| Ok((address, dbc::VirtualPool::decode(&account.data)?)))
+            .collect()
+    }
+
+    fn fetch_dbc_pool(&self, pool_address: &str) -> Result<(dbc::VirtualPool, dbc::PoolConfig)> {
+        let address = Pubkey::from_str(pool_address).context("invalid DBC pool address")?;
+        let [pool] = self.fetch_accounts([address])?;
+        let pool = dbc::VirtualPool::decode(&pool.data)?;
+        let [config] = self.fetch_accounts([pool.config])?;
+        Ok((pool, dbc::PoolConfig::decode(&config.data)?))
+    }
+
+    fn fetch_damm_pool(&self, pool_address: &str) -> Result<(Pubkey, damm_v2::Pool)> {
+        let address = Pubkey::from_str(pool_address).context("invalid DAMM v2 pool address")?;
+        let [pool] = self.fetch_accounts([address])?;
+        Ok((address, damm_v2::Pool::decode(&pool.data)?))
+    }
+
+    fn fetch_damm_position(&self, position: &str, owner: &Pubkey) -> Result<(damm_v2::PositionKeys, damm_v2::Pool, damm_v2::Position)> {
+        let address = Pubkey::from_str(position).context("invalid position address")?;
+        let [account] = self.fetch_accounts([address])?;
+        let position = damm_v2::Position::decode(&account.data)?;
+        let [pool] = self.fetch_accounts([position.pool])?;
+        let pool = damm_v2::Pool::decode(&pool.data)?;
+        Ok((position_keys(position.pool, address, owner, &pool), pool, position))
+    }
+
+    /// Fetches several accounts in one request, failing if any is missing.
+    fn fetch_accounts<const N: usize>(&self, addresses: [Pubkey; N]) -> Result<[Account; N]> {
+        let accounts = self.rpc_client.get_multiple_accounts(&addresses)?;
+        let accounts = accounts.into_iter()
+            .zip(addresses)
+            .map(|(account, address)| account.ok_or_else(|| anyhow!("account {} not found", address)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(accounts.try_into().expect("one account per address"))
+    }
+}
+
+/// The current slot and unix timestamp, from the Clock sysvar.
+pub(crate) fn fee_clock(rpc_client: &RpcClient) -> Result<fee::FeeClock> {
+    let clock = rpc_client.get_account(&sysvar::clock::id()).context("failed to read the Clock sysvar")?;
+    Ok(fee::FeeClock { slot: layout::read_u64(&clock.data, 0)?, unix_timestamp: layout::read_u64(&clock.data, 32)? })
+}
+
+/// The wallet's token accounts for a swap's `(mint, token program)` sides.
+fn swap_accounts(wallet: &Pubkey, input: (Pubkey, Pubkey), output: (Pubkey, Pubkey)) -> (Pubkey, Pubkey) {
+    (
+        get_associated_token_address_with_program_id(wallet, &input.0, &input.1),
+        get_associated_token_address_with_program_id(wallet, &output.0, &output.1),
+    )
+}
+
+/// Sends `swap` after creating the output account and, when spending native
+/// SOL, wrapping exactly `amount_in`. Sale proceeds in SOL stay wrapped, so
+/// the fill is what the confirmed transaction added to the wallet's output
+/// token balance.
+async fn send_swap(
+    solana_client: &dyn SolanaApi,
+    input: (Pubkey, Pubkey),
+    output: (Pubkey, Pubkey),
+    amount_in: u64,
+    swap: Instruction,
+) -> Result<SwapFill> {
+    let wallet = solana_client.wallet_pubkey();
+    let (input_account, _) = swap_accounts(&wallet, input, output);
+    let mut instructions = vec![create_associated_token_account_idempotent(&wallet, &wallet, &output.0, &output.1)];
+    if input.0 == spl_token::native_mint::id() {
+        instructions.extend([
+            create_associated_token_account_idempotent(&wallet, &wallet, &input.0, &input.1),
+            system_instruction::transfer(&wallet, &input_account, amount_in),
+            spl_token::instruction::sync_native(&input.1, &input_account)?,
+        ]);
+    }
+    instructions.push(swap);
+
+    let (input_mint, output_mint) = (input.0.to_string(), output.0.to_string());
+    let signature = solana_client.send_instructions(&instructions).await?;
+    let received = solana_client.token_balance_change(&signature, &output_mint).await?;
+    let amount_out = u64::try_from(received.max(0)).unwrap_or(u64::MAX);
+    Ok(SwapFill { signature, input_mint, output_mint, amount_in, amount_out })
+}
+
+/// Whether selling `input_mint` into `pool` swaps A for B.
+fn damm_direction(pool: &damm_v2::Pool, input_mint: &str) -> Result<bool> {
+    let input_mint = Pubkey::from_str(input_mint).context("invalid input mint")?;
+    if input_mint == pool.token_a_mint {
+        Ok(true)
+    } else if input_mint == pool.token_b_mint {
+        Ok(false)
+    } else {
+        bail!("DAMM v2 pool trades {}/{}, not {}", pool.token_a_mint, pool.token_b_mint, input_mint)
+    }
+}
+
+fn position_keys(pool_address: Pubkey, position: Pubkey, owner: &Pubkey, pool: &damm_v2::Pool) -> damm_v2::PositionKeys {
+    damm_v2::PositionKeys {
+        pool: pool_address,
+        position,
+        owner: *owner,
+        token_a_account: get_associated_token_address_with_program_id(owner, &pool.token_a_mint, &pool.token_a_program()),
+        token_b_account: get_associated_token_address_with_program_id(owner, &pool.token_b_mint, &pool.token_b_program()),
+    }
+}
+
+/// `(liquidity, a, b)` for the largest deposit within `max_a` / `max_b`.
+fn deposit_amounts(pool: &damm_v2::Pool, max_a: u64, max_b: u64) -> Result<(u128, u64, u64)> {
+    let liquidity = pool.liquidity_for_amounts(max_a, max_b)?;
+    if liquidity == 0 {
+        bail!("Deposit of {} / {} is too small to add any liquidity", max_a, max_b);
+    }
+    let (a, b) = pool.amounts_for_liquidity(liquidity, true)?;
+    Ok((liquidity, a, b))
+}
@@ -0,0 +1,590 @@
+//! In-memory implementations of the `crate::venue` traits for offline tests.
+
+use crate::bagsfm::{
+    BagsFmError, BagsFmLaunch, BagsFmToken, ClaimableFee, FeeClaimRequest, FeeClaimTransactions,
+    LaunchMetadata, LaunchMetadataRequest, LaunchTransaction, LaunchTransactionRequest,
+};
+use crate::dex::{raydium, DexPool, LiquidityQuote};
+use crate::dex::math::U256;
+use crate::meteora::fee::{FeeClock, FeeSchedule};
+use crate::meteora::{damm_v2, DammPositionChange, DammPositionFees, DbcProgress, DbcSide, DbcStatus, MeteoraDammPool, MeteoraDbcPool, SwapFill};
+use crate::trade::{self, Quote};
+use crate::venue::{AmmVenue, BondingCurveVenue, LaunchpadApi, SolanaApi};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature}};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Wallet with a fixed balance that records every transaction it is asked
+/// to sign instead of sending it.
+pub struct MockSolana {
+    pub pubkey: Pubkey,
+    pub balance: Mutex<u64>,
+    pub token_balances: Mutex<HashMap<String, u64>>,
+    pub sent: Mutex<Vec<String>>,
+    pub sent_instructions: Mutex<Vec<Vec<Instruction>>>,
+    /// What every sent transaction changes each mint's balance by.
+    pub balance_changes: Mutex<HashMap<String, i128>>,
+}
+
+impl MockSolana {
+    pub fn with_balance(lamports: u64) -> Self {
+        Self {
+            pubkey: Pubkey::new_unique(),
+            balance: Mutex::new(lamports),
+            token_balances: Mutex::new(HashMap::new()),
+            sent: Mutex::new(Vec::new()),
+            sent_instructions: Mutex::new(Vec::new()),
+            balance_changes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_balance_change(self, mint: &str, change: i128) -> Self {
+        self.balance_changes.lock().unwrap().insert(mint.to_string(), change);
+        self
+    }
+
+    pub fn with_token_balance(self, mint: &str, amount: u64) -> Self {
+        self.token_balances.lock().unwrap().insert(mint.to_string(), amount);
+        self
+    }
+
+    pub fn sent(&self) -> Vec<String> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl SolanaApi for MockSolana {
+    fn wallet_pubkey(&self) -> Pubkey {
+        self.pubkey
+    }
+
+    async fn get_balance(&self) -> Result<u64> {
+        Ok(*self.balance.lock().unwrap())
+    }
+
+    async fn get_token_balance(&self, token_mint: &str) -> Result<u64> {
+        Ok(self.token_balances.lock().unwrap().get(token_mint).copied().unwrap_or(0))
+    }
+
+    async fn token_balance_change(&self, _signature: &Signature, token_mint: &str) -> Result<i128> {
+        Ok(self.balance_changes.lock().unwrap().get(token_mint).copied().unwrap_or(0))
+    }
+
+    async fn sign_and_send_serialized(&self, encoded: &str) -> Result<Signature> {
+        let mut sent = self.sent.lock().unwrap();
+        sent.push(encoded.to_string());
+        Ok(Signature::from([sent.len() as u8; 64]))
+    }
+
+    async fn send_instructions(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let mut sent = self.sent_instructions.lock().unwrap();
+        sent.push(instructions.to_vec());
+        Ok(Signature::from([sent.len() as u8; 64]))
+    }
+
+    async fn send_instructions_with_signers(&self, instructions: &[Instruction], _signers: &[&Keypair]) -> Result<Signature> {
+        self.send_instructions(instructions).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        _address: &Pubkey,
+        _limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        Ok(Vec::new())
+    }
+}
+
+/// bags.fm stand-in. Anything not seeded answers `NotFound`.
+#[derive(Default)]
+pub struct MockLaunchpad {
+    pub tokens: Mutex<HashMap<String, BagsFmToken>>,
+    pub launches: Mutex<Vec<BagsFmLaunch>>,
+    /// The `since` cursor of every `get_launches_since` call.
+    pub launch_cursors: Mutex<Vec<Option<DateTime<Utc>>>>,
+    pub claimable: Mutex<Vec<ClaimableFee>>,
+    pub metadata_requests: Mutex<Vec<LaunchMetadataRequest>>,
+    pub claim_requests: Mutex<Vec<FeeClaimRequest>>,
+    /// Mints handed out by `create_launch_metadata`, in order; fresh ones after.
+    pub next_mints: Mutex<Vec<String>>,
+}
+
+impl MockLaunchpad {
+    pub fn with_mint(self, mint: &str) -> Self {
+        self.next_mints.lock().unwrap().push(mint.to_string());
+        self
+    }
+
+    pub fn with_token(self, token: BagsFmToken) -> Self {
+        self.tokens.lock().unwrap().insert(token.address.clone(), token);
+        self
+    }
+
+    pub fn with_launch(self, launch: BagsFmLaunch) -> Self {
+        self.launches.lock().unwrap().push(launch);
+        self
+    }
+
+    pub fn with_claimable(self, token_mint: &str, claimable_lamports: u64) -> Self {
+        self.claimable.lock().unwrap().push(ClaimableFee {
+            token_mint: token_mint.to_string(),
+            claimable_lamports,
+            total_claimed_lamports: 0,
+        });
+        self
+    }
+}
+
+#[async_trait]
+impl LaunchpadApi for MockLaunchpad {
+    async fn get_token_info(&self, token_address: &str) -> Result<BagsFmToken, BagsFmError> {
+        self.tokens.lock().unwrap().get(token_address).cloned()
+            .ok_or_else(|| BagsFmError::NotFound(format!("/v1/tokens/{}", token_address)))
+    }
+
+    async fn get_launches_since(&self, since: Option<DateTime<Utc>>, limit: u32) -> Result<Vec<BagsFmLaunch>, BagsFmError> {
+        self.launch_cursors.lock().unwrap().push(since);
+        Ok(self.launches.lock().unwrap().iter()
+            .filter(|launch| since.is_none_or(|since| launch.launch_time >= since))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_launch_metadata(&self, request: &LaunchMetadataRequest) -> Result<LaunchMetadata, BagsFmError> {
+        let mut requests = self.metadata_requests.lock().unwrap();
+        requests.push(request.clone());
+        let mut next_mints = self.next_mints.lock().unwrap();
+        let token_mint = if next_mints.is_empty() { Pubkey::new_unique().to_string() } else { next_mints.remove(0) };
+        Ok(LaunchMetadata {
+            token_mint,
+            metadata_uri: format!("ipfs://mock/{}", requests.len()),
+        })
+    }
+
+    async fn create_launch_transaction(&self, request: &LaunchTransactionRequest) -> Result<LaunchTransaction, BagsFmError> {
+        Ok(LaunchTransaction {
+            transaction: format!("launch-tx:{}", request.token_mint),
+        })
+    }
+
+    async fn get_claimable_fees(&self, _wallet: &str) -> Result<Vec<ClaimableFee>, BagsFmError> {
+        Ok(self.claimable.lock().unwrap().clone())
+    }
+
+    async fn create_fee_claim_transactions(&self, request: &FeeClaimRequest) -> Result<FeeClaimTransactions, BagsFmError> {
+        self.claim_requests.lock().unwrap().push(request.clone());
+        Ok(FeeClaimTransactions {
+            transactions: vec![format!("claim-tx:{}", request.token_mint)],
+        })
+    }
+}
+
+/// AMM with seeded pools; quotes use the pool's own reserves. LP supply
+/// defaults to the pool's token A reserve.
+#[derive(Default)]
+pub struct MockAmm {
+    pub pools: Mutex<HashMap<String, DexPool>>,
+    pub lp_supplies: Mutex<HashMap<String, u64>>,
+    pub lp_balances: Mutex<HashMap<String, u64>>,
+    pub token_2022_mints: Mutex<HashSet<String>>,
+}
+
+impl MockAmm {
+    pub fn with_pool(self, pool: DexPool) -> Self {
+        self.pools.lock().unwrap().insert(pool.address.clone(), pool);
+        self
+    }
+
+    pub fn with_lp(self, pool: &str, supply: u64, balance: u64) -> Self {
+        self.lp_supplies.lock().unwrap().insert(pool.to_string(), supply);
+        self.lp_balances.lock().unwrap().insert(pool.to_string(), balance);
+        self
+    }
+
+    pub fn with_token_2022_mint(self, mint: &str) -> Self {
+        self.token_2022_mints.lock().unwrap().insert(mint.to_string());
+        self
+    }
+
+    fn lp_supply(&self, pool: &DexPool) -> u64 {
+        self.lp_supplies.lock().unwrap().get(&pool.address).copied().unwrap_or(pool.reserve_a)
+    }
+}
+
+#[async_trait]
+impl AmmVenue for MockAmm {
+    async fn get_pool_info(&self, pool_address: &str) -> Result<DexPool> {
+        self.pools.lock().unwrap().get(pool_address).cloned()
+            .ok_or_else(|| anyhow!("unknown pool {}", pool_address))
+    }
+
+    async fn find_pools(&self, mint: &str) -> Result<Vec<DexPool>> {
+        let mut pools: Vec<DexPool> = self.pools.lock().unwrap().values()
+            .filter(|pool| pool.reserve_of(mint).is_some())
+            .cloned()
+            .collect();
+        pools.sort_by_key(|pool| std::cmp::Reverse(pool.reserve_of(mint)));
+        Ok(pools)
+    }
+
+    /// SPL Token unless the mint was seeded as Token-2022.
+    async fn token_program(&self, mint: &str) -> Result<Pubkey> {
+        Ok(if self.token_2022_mints.lock().unwrap().contains(mint) {
+            damm_v2::TOKEN_2022_PROGRAM_ID
+        } else {
+            spl_token::id()
+        })
+    }
+
+    /// Raydium-style constant product with the pool's fee.
+    async fn calculate_swap_amounts(&self, pool: &DexPool, input_amount: u64, is_input_a: bool) -> Result<(u64, u64)> {
+        let (reserve_in, reserve_out) = if is_input_a {
+            (pool.reserve_a, pool.reserve_b)
+        } else {
+            (pool.reserve_b, pool.reserve_a)
+        };
+        raydium::quote_base_in(input_amount, reserve_in, reserve_out, pool.fee_numerator, pool.fee_denominator)
+            .ok_or_else(|| anyhow!("quote overflow"))
+    }
+
+    /// A placeholder instruction whose data is `input_amount ++ min_output_amount`.
+    async fn create_swap_instruction(
+        &self,
+        _pool: &DexPool,
+        _user_wallet: &Pubkey,
+        _input_mint: &str,
+        _output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        let data = [input_amount.to_le_bytes(), min_output_amount.to_le_bytes()].concat();
+        Ok(Instruction::new_with_bytes(Pubkey::default(), &data, vec![]))
+    }
+
+    /// Raydium deposit math against the seeded LP supply.
+    async fn add_liquidity(&self, pool: &DexPool, max_a: u64, max_b: u64) -> Result<LiquidityQuote> {
+        let supply = self.lp_supply(pool);
+        let (a, b, lp) = raydium::deposit_amounts(max_a, max_b, pool.reserve_a, pool.reserve_b, supply)
+            .ok_or_else(|| anyhow!("empty pool"))?;
+        Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount: lp as u128, lp_supply: supply as u128 })
+    }
+
+    async fn remove_liquidity(&self, pool: &DexPool, lp_amount: u128) -> Result<LiquidityQuote> {
+        let supply = self.lp_supply(pool);
+        let (a, b) = raydium::withdraw_amounts(lp_amount as u64, pool.reserve_a, pool.reserve_b, supply)
+            .ok_or_else(|| anyhow!("LP amount exceeds supply"))?;
+        Ok(LiquidityQuote { token_a_amount: a, token_b_amount: b, lp_amount, lp_supply: supply as u128 })
+    }
+
+    async fn lp_balance(&self, pool: &DexPool, _owner: &Pubkey, _position: Option<&str>) -> Result<u128> {
+        Ok(self.lp_balances.lock().unwrap().get(&pool.address).copied().unwrap_or(0) as u128)
+    }
+
+    /// A placeholder instruction whose data is `lp_amount ++ max_a ++ max_b`.
+    async fn create_add_liquidity_instructions(
+        &self,
+        _pool: &DexPool,
+        _owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        _position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        let data = [
+            (quote.lp_amount as u64).to_le_bytes(),
+            trade::max_in(quote.token_a_amount, slippage_bps).to_le_bytes(),
+            trade::max_in(quote.token_b_amount, slippage_bps).to_le_bytes(),
+        ].concat();
+        Ok(vec![Instruction::new_with_bytes(Pubkey::default(), &data, vec![])])
+    }
+
+    /// A placeholder instruction whose data is `lp_amount ++ min_a ++ min_b`.
+    async fn create_remove_liquidity_instructions(
+        &self,
+        _pool: &DexPool,
+        _owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        _position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        let data = [
+            (quote.lp_amount as u64).to_le_bytes(),
+            trade::min_out(quote.token_a_amount, slippage_bps).to_le_bytes(),
+            trade::min_out(quote.token_b_amount, slippage_bps).to_le_bytes(),
+        ].concat();
+        Ok(vec![Instruction::new_with_bytes(Pubkey::default(), &data, vec![])])
+    }
+}
+
+/// Bonding curve with seeded pools priced linearly.
+#[derive(Default)]
+pub struct MockBondingCurve {
+    pub pools: Mutex<HashMap<String, MeteoraDbcPool>>,
+    pub price_impact: f64,
+    /// `(pool, side, amount_in, min_out)` of each swap sent.
+    pub swaps: Mutex<Vec<(String, DbcSide, u64, u64)>>,
+    /// Curve progress by token mint.
+    pub progress: Mutex<HashMap<String, DbcProgress>>,
+    /// DAMM v2 pool by token mint, once migrated.
+    pub damm_pools: Mutex<HashMap<String, String>>,
+    pub damms: Mutex<HashMap<String, MeteoraDammPool>>,
+    /// `(pool, input_mint, amount_in, min_out)` of each DAMM v2 swap sent.
+    pub damm_swaps: Mutex<Vec<(String, String, u64, u64)>>,
+    /// Liquidity by DAMM v2 position.
+    pub positions: Mutex<HashMap<String, u128>>,
+    /// Unclaimed LP fees by DAMM v2 position, zeroed when claimed.
+    pub lp_fees: Mutex<Vec<DammPositionFees>>,
+    /// DBC fee schedule; free by default.
+    pub fee_schedule: Option<FeeSchedule>,
+    /// The point fees are evaluated at; each read advances it by one.
+    pub clock: Mutex<u64>,
+    /// Decimals of every curve's quote mint; 9 (wSOL) when unset.
+    pub quote_decimals: Option<u8>,
+}
+
+impl MockBondingCurve {
+    pub fn with_pool(self, pool: MeteoraDbcPool) -> Self {
+        self.pools.lock().unwrap().insert(pool.address.clone(), pool);
+        self
+    }
+
+    pub fn with_progress(self, progress: DbcProgress) -> Self {
+        self.progress.lock().unwrap().insert(progress.token_mint.clone(), progress);
+        self
+    }
+
+    /// Seeds a DAMM v2 pool, found by its token A (the graduated token).
+    pub fn with_damm_pool(self, pool: MeteoraDammPool) -> Self {
+        self.damm_pools.lock().unwrap().insert(pool.token_a.clone(), pool.address.clone());
+        self.damms.lock().unwrap().insert(pool.address.clone(), pool);
+        self
+    }
+
+    pub fn with_lp_fees(self, fees: DammPositionFees) -> Self {
+        self.lp_fees.lock().unwrap().push(fees);
+        self
+    }
+
+    pub fn with_fee_schedule(mut self, schedule: FeeSchedule) -> Self {
+        self.fee_schedule = Some(schedule);
+        self
+    }
+
+    pub fn with_price_impact(mut self, price_impact: f64) -> Self {
+        self.price_impact = price_impact;
+        self
+    }
+
+    pub fn with_quote_decimals(mut self, decimals: u8) -> Self {
+        self.quote_decimals = Some(decimals);
+        self
+    }
+}
+
+#[async_trait]
+impl BondingCurveVenue for MockBondingCurve {
+    async fn get_dbc_pool_info(&self, pool_address: &str) -> Result<MeteoraDbcPool> {
+        self.pools.lock().unwrap().get(pool_address).cloned()
+            .ok_or_else(|| anyhow!("unknown DBC pool {}", pool_address))
+    }
+
+    async fn find_dbc_pool(&self, token_mint: &str) -> Result<MeteoraDbcPool> {
+        self.pools.lock().unwrap().values()
+            .find(|pool| pool.token_mint == token_mint && !matches!(pool.status, DbcStatus::Migrated))
+            .cloned()
+            .ok_or_else(|| anyhow!("No active Meteora DBC pool for {}", token_mint))
+    }
+
+    async fn buy_from_dbc(&self, pool: &MeteoraDbcPool, amount_base: f64) -> Result<Quote> {
+        Ok(Quote {
+            amount_in: (amount_base * 10f64.powi(self.quote_decimals.unwrap_or(9) as i32)) as u64,
+            amount_out: (amount_base / pool.current_price) as u64,
+            fee: 0,
+            price_impact_bps: (self.price_impact * 10_000.0).round() as u32,
+        })
+    }
+
+    async fn dbc_fee_schedule(&self, _pool: &MeteoraDbcPool) -> Result<(FeeSchedule, FeeClock)> {
+        let mut clock = self.clock.lock().unwrap();
+        let now = FeeClock { slot: *clock, unix_timestamp: *clock };
+        *clock += 1;
+        Ok((self.fee_schedule.unwrap_or(FeeSchedule::constant(0)), now))
+    }
+
+    /// Fills at `current_price` with no fee.
+    async fn quote_dbc_swap(&self, pool: &MeteoraDbcPool, side: DbcSide, amount_in: u64) -> Result<(u64, u64)> {
+        let amount_out = match side {
+            DbcSide::Buy => amount_in as f64 / pool.current_price,
+            DbcSide::Sell => amount_in as f64 * pool.current_price,
+        };
+        Ok((amount_out as u64, 0))
+    }
+
+    /// Sends an empty transaction and fills at exactly `min_out`.
+    async fn swap_dbc(
+        &self,
+        pool: &MeteoraDbcPool,
+        side: DbcSide,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        self.swaps.lock().unwrap().push((pool.address.clone(), side, amount_in, min_out));
+        let (input_mint, output_mint) = match side {
+            DbcSide::Buy => (pool.base_token.clone(), pool.token_mint.clone()),
+            DbcSide::Sell => (pool.token_mint.clone(), pool.base_token.clone()),
+        };
+        Ok(SwapFill {
+            signature: solana_client.send_instructions(&[]).await?,
+            input_mint,
+            output_mint,
+            amount_in,
+            amount_out: min_out,
+        })
+    }
+
+    async fn migrate_to_damm(&self, dbc_pool: &MeteoraDbcPool) -> Result<MeteoraDammPool> {
+        let address = self.find_damm_v2_pool(&dbc_pool.token_mint).await?
+            .ok_or_else(|| anyhow!("DBC pool {} has not migrated to DAMM v2 yet", dbc_pool.address))?;
+        self.get_damm_pool(&address).await
+    }
+
+    async fn curve_progress(&self, token_mint: &str) -> Result<DbcProgress> {
+        self.progress.lock().unwrap().get(token_mint).cloned()
+            .ok_or_else(|| anyhow!("No Meteora DBC pool for {}", token_mint))
+    }
+
+    async fn find_damm_v2_pool(&self, token_mint: &str) -> Result<Option<String>> {
+        Ok(self.damm_pools.lock().unwrap().get(token_mint).cloned())
+    }
+
+    async fn get_damm_pool(&self, pool_address: &str) -> Result<MeteoraDammPool> {
+        self.damms.lock().unwrap().get(pool_address).cloned()
+            .ok_or_else(|| anyhow!("unknown DAMM v2 pool {}", pool_address))
+    }
+
+    /// Constant product on the seeded reserves, fee taken from the input.
+    async fn quote_damm_swap(&self, pool: &MeteoraDammPool, input_mint: &str, amount_in: u64) -> Result<(u64, u64)> {
+        let (reserve_in, reserve_out) = if input_mint == pool.token_a {
+            (pool.reserve_a, pool.reserve_b)
+        } else {
+            (pool.reserve_b, pool.reserve_a)
+        };
+        let fee = (amount_in as f64 * pool.fee_rate).ceil() as u64;
+        let net = (amount_in - fee) as u128;
+        Ok(((net * reserve_out as u128 / (reserve_in as u128 + net)) as u64, fee))
+    }
+
+    /// Sends an empty transaction and fills at exactly `min_out`.
+    async fn swap_damm(
+        &self,
+        pool: &MeteoraDammPool,
+        input_mint: &str,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        self.damm_swaps.lock().unwrap().push((pool.address.clone(), input_mint.to_string(), amount_in, min_out));
+        let output_mint = if input_mint == pool.token_a { &pool.token_b } else { &pool.token_a };
+        Ok(SwapFill {
+            signature: solana_client.send_instructions(&[]).await?,
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.clone(),
+            amount_in,
+            amount_out: min_out,
+        })
+    }
+
+    /// Liquidity is the smaller of the two amounts.
+    async fn open_damm_position(
+        &self,
+        _pool: &MeteoraDammPool,
+        max_a: u64,
+        max_b: u64,
+        _slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        let position = Pubkey::new_unique().to_string();
+        let liquidity_delta = max_a.min(max_b) as u128;
+        self.positions.lock().unwrap().insert(position.clone(), liquidity_delta);
+        Ok(DammPositionChange {
+            position,
+            signature: solana_client.send_instructions(&[]).await?,
+            liquidity_delta,
+            token_a_amount: liquidity_delta as u64,
+            token_b_amount: liquidity_delta as u64,
+        })
+    }
+
+    async fn decrease_damm_position(
+        &self,
+        position: &str,
+        liquidity: Option<u128>,
+        _slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        let liquidity_delta = {
+            let mut positions = self.positions.lock().unwrap();
+            let held = positions.get_mut(position).ok_or_else(|| anyhow!("unknown position {}", position))?;
+            let delta = liquidity.unwrap_or(*held);
+            if delta == 0 || delta > *held {
+                return Err(anyhow!("Cannot withdraw {} liquidity from {}: {} unlocked", delta, position, held));
+            }
+            *held -= delta;
+            delta
+        };
+        Ok(DammPositionChange {
+            position: position.to_string(),
+            signature: solana_client.send_instructions(&[]).await?,
+            liquidity_delta,
+            token_a_amount: liquidity_delta as u64,
+            token_b_amount: liquidity_delta as u64,
+        })
+    }
+
+    async fn get_damm_position(&self, position: &str) -> Result<damm_v2::Position> {
+        let held = *self.positions.lock().unwrap().get(position).ok_or_else(|| anyhow!("unknown position {}", position))?;
+        Ok(damm_v2::Position {
+            pool: Pubkey::default(),
+            nft_mint: Pubkey::default(),
+            fee_a_per_token_checkpoint: U256::ZERO,
+            fee_b_per_token_checkpoint: U256::ZERO,
+            fee_a_pending: 0,
+            fee_b_pending: 0,
+            unlocked_liquidity: held,
+            vested_liquidity: 0,
+            permanent_locked_liquidity: 0,
+        })
+    }
+
+    async fn close_damm_position(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        match self.positions.lock().unwrap().get(position) {
+            Some(0) => {}
+            Some(held) => return Err(anyhow!("Position {} still holds {} liquidity", position, held)),
+            None => return Err(anyhow!("unknown position {}", position)),
+        }
+        self.positions.lock().unwrap().remove(position);
+        solana_client.send_instructions(&[]).await
+    }
+
+    async fn unclaimed_damm_fees(&self, _owner: &Pubkey) -> Result<Vec<DammPositionFees>> {
+        Ok(self.lp_fees.lock().unwrap().clone())
+    }
+
+    async fn claim_damm_fees(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        {
+            let mut lp_fees = self.lp_fees.lock().unwrap();
+            let fees = lp_fees.iter_mut()
+                .find(|fees| fees.position == position)
+                .ok_or_else(|| anyhow!("unknown position {}", position))?;
+            fees.fee_a = 0;
+            fees.fee_b = 0;
+        }
+        solana_client.send_instructions(&[]).await
+    }
+}
@@ -0,0 +1,216 @@
+//! Exact numeric types for API fields that must not round-trip through f64.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Largest scale a [`Decimal`] keeps; `i128` holds 38 significant digits.
+const MAX_SCALE: u32 = 38;
+
+/// Base-10 fixed-point number, `mantissa * 10^-scale`. Parsed from the
+/// literal digits so `"0.000001234"` stays exactly that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Nearest `f64`; only for display and heuristics, never for amounts.
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Uses the shortest round-trip representation of `value`.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+        format!("{}", value).parse().ok()
+    }
+
+    /// Converts to integer base units with `decimals` places, rounding down.
+    /// `None` for negative values or on overflow. The inverse of
+    /// [`format_units`]; nothing converts user input through it yet.
+    #[cfg(test)]
+    pub fn to_units(self, decimals: u32) -> Option<u128> {
+        if self.mantissa < 0 {
+            return None;
+        }
+        let mantissa = self.mantissa as u128;
+        if decimals >= self.scale {
+            mantissa.checked_mul(10u128.checked_pow(decimals - self.scale)?)
+        } else {
+            Some(mantissa / 10u128.pow(self.scale - decimals))
+        }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (number, exponent) = match s.find(['e', 'E']) {
+            Some(i) => {
+                let exp: i32 = s[i + 1..].parse().map_err(|_| format!("invalid exponent in {:?}", s))?;
+                (&s[..i], exp)
+            }
+            None => (s, 0),
+        };
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return Err(format!("{:?} is not a number", s));
+        }
+        if !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit()) {
+            return Err(format!("{:?} is not a number", s));
+        }
+
+        let all_digits = format!("{}{}", int_part, frac_part);
+        let all_digits = all_digits.trim_start_matches('0');
+        let mut mantissa: i128 = if all_digits.is_empty() {
+            0
+        } else {
+            all_digits.parse().map_err(|_| format!("{:?} has too many significant digits", s))?
+        };
+        let mut scale = frac_part.len() as i64 - exponent as i64;
+        if scale < 0 {
+            mantissa = 10i128
+                .checked_pow((-scale) as u32)
+                .and_then(|m| mantissa.checked_mul(m))
+                .ok_or_else(|| format!("{:?} is out of range", s))?;
+            scale = 0;
+        }
+        if scale > MAX_SCALE as i64 {
+            return Err(format!("{:?} has more than {} decimal places", s, MAX_SCALE));
+        }
+        if negative {
+            mantissa = -mantissa;
+        }
+        Ok(Self { mantissa, scale: scale as u32 })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (int_part, frac_part) = padded.split_at(padded.len() - scale);
+        write!(f, "{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DecimalVisitor;
+
+        impl Visitor<'_> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal number or numeric string")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Decimal, E> {
+                Ok(Decimal::new(v as i128, 0))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Decimal, E> {
+                Ok(Decimal::new(v as i128, 0))
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Decimal, E> {
+                Decimal::from_f64(v).ok_or_else(|| E::custom(format!("{} is not a finite number", v)))
+            }
+        }
+
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+/// Parses an unsigned integer sent either as a string or as a JSON number.
+/// Numbers above `u64::MAX` only survive as strings; a float is rejected
+/// instead of silently rounded.
+pub fn parse_u128(value: &serde_json::Value) -> Result<u128, String> {
+    match value {
+        serde_json::Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| format!("{:?} is not an unsigned integer", s)),
+        serde_json::Value::Number(n) => n
+            .as_u64()
+            .map(u128::from)
+            .ok_or_else(|| format!("{} is not an unsigned integer (send large values as strings)", n)),
+        other => Err(format!("expected an unsigned integer, got {}", other)),
+    }
+}
+
+/// Formats `amount` base units with `decimals` places, without rounding.
+pub fn format_units(amount: u128, decimals: u8) -> String {
+    Decimal::new(amount as i128, decimals as u32).to_string()
+}
+
+/// Serializes a `u128` as a decimal string so it survives JSON readers that
+/// parse numbers as f64.
+pub fn serialize_u128_str<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_keeps_literal_digits() {
+        let d: Decimal = "0.000001234".parse().unwrap();
+        assert_eq!(d, Decimal::new(1234, 9));
+        assert_eq!(d.to_string(), "0.000001234");
+        assert_eq!("-12.50".parse::<Decimal>().unwrap().to_string(), "-12.50");
+        assert_eq!("1.5e3".parse::<Decimal>().unwrap().to_string(), "1500");
+        assert_eq!("2.5E-3".parse::<Decimal>().unwrap().to_string(), "0.0025");
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("abc".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn decimal_converts_to_base_units() {
+        let d: Decimal = "1.23456789".parse().unwrap();
+        assert_eq!(d.to_units(9), Some(1_234_567_890));
+        assert_eq!(d.to_units(2), Some(123));
+        assert_eq!("-1".parse::<Decimal>().unwrap().to_units(9), None);
+    }
+
+    #[test]
+    fn u128_accepts_strings_beyond_u64() {
+        let big = serde_json::json!("340282366920938463463374607431768211455");
+        assert_eq!(parse_u128(&big).unwrap(), u128::MAX);
+        assert_eq!(parse_u128(&serde_json::json!(42)).unwrap(), 42);
+        assert!(parse_u128(&serde_json::json!(1.5)).is_err());
+        assert!(parse_u128(&serde_json::json!("-3")).is_err());
+        assert_eq!(format_units(1_500_000_000, 9), "1.500000000");
+    }
+}
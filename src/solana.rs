@@ -1,15 +1,20 @@
 use anyhow::{anyhow, Result};
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_client::rpc_request::TokenAccountsFilter;
 use solana_sdk::{
     commitment_config::CommitmentConfig,
+    instruction::Instruction,
     pubkey::Pubkey,
-    signature::{Keypair, read_keypair_file},
-    transaction::Transaction,
-    system_instruction,
+    signature::{Keypair, Signature, Signer, read_keypair_file},
+    transaction::{Transaction, VersionedTransaction},
 };
-use solana_transaction_status::UiTransactionEncoding;
-use spl_token::state::Account as TokenAccount;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding, UiTransactionTokenBalance};
 use std::str::FromStr;
+use std::time::Duration;
+
+/// `getTransaction` can trail `send_and_confirm` by a moment on some nodes.
+const TRANSACTION_FETCH_ATTEMPTS: u32 = 5;
 
 pub struct SolanaClient {
     pub rpc_client: RpcClient,
@@ -44,52 +49,166 @@ impl SolanaClient {
         let mint_pubkey = Pubkey::from_str(token_mint)?;
         let token_accounts = self.rpc_client.get_token_accounts_by_owner(
             &self.wallet_pubkey,
-            spl_token::instruction::TokenAccountFilter::Mint(mint_pubkey),
+            TokenAccountsFilter::Mint(mint_pubkey),
         )?;
 
         if let Some(account) = token_accounts.first() {
-            let account_data = base64::decode(&account.account.data[0])?;
-            let token_account = TokenAccount::unpack(&account_data)?;
-            Ok(token_account.amount)
+            let token_account = Pubkey::from_str(&account.pubkey)?;
+            let balance = self.rpc_client.get_token_account_balance(&token_account)?;
+            Ok(balance.amount.parse()?)
         } else {
             Ok(0)
         }
     }
 
-    pub async fn send_sol(&self, to: &str, amount_sol: f64) -> Result<String> {
-        let to_pubkey = Pubkey::from_str(to)?;
-        let amount_lamports = (amount_sol * 1_000_000_000.0) as u64;
-        
-        let instruction = system_instruction::transfer(
-            &self.wallet_pubkey,
-            &to_pubkey,
-            amount_lamports,
-        );
+    pub async fn get_transaction(&self, signature: &str) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+        let sig = Signature::from_str(signature)?;
+        let transaction = self.rpc_client.get_transaction_with_config(
+            &sig,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        Ok(transaction)
+    }
 
+    /// Reads a fill from the transaction itself rather than two balance
+    /// reads around it, which would also count unrelated transfers and
+    /// trail a lagging node.
+    pub async fn token_balance_change(&self, signature: &Signature, token_mint: &str) -> Result<i128> {
+        let mut attempt = 1;
+        let transaction = loop {
+            match self.get_transaction(&signature.to_string()).await {
+                Ok(transaction) => break transaction,
+                Err(e) if attempt < TRANSACTION_FETCH_ATTEMPTS => {
+                    log::debug!("Transaction {} not readable yet ({}); retrying", signature, e);
+                    attempt += 1;
+                    tokio::time::sleep(Duration::from_millis(400)).await;
+                }
+                Err(e) => return Err(e.context(format!("failed to read transaction {}", signature))),
+            }
+        };
+        let meta = transaction.transaction.meta
+            .ok_or_else(|| anyhow!("Transaction {} has no status meta", signature))?;
+        let pre: Option<Vec<UiTransactionTokenBalance>> = meta.pre_token_balances.into();
+        let post: Option<Vec<UiTransactionTokenBalance>> = meta.post_token_balances.into();
+        owner_balance_change(
+            &pre.unwrap_or_default(),
+            &post.unwrap_or_default(),
+            &self.wallet_pubkey.to_string(),
+            token_mint,
+        )
+    }
+
+    pub async fn send_instructions(&self, instructions: &[Instruction]) -> Result<Signature> {
+        self.send_instructions_with_signers(instructions, &[]).await
+    }
+
+    /// Like `send_instructions`, with `signers` (e.g. a fresh mint) signing
+    /// next to the wallet.
+    pub async fn send_instructions_with_signers(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
         let recent_blockhash = self.rpc_client.get_latest_blockhash()?;
+        let mut all_signers = vec![&self.wallet];
+        all_signers.extend_from_slice(signers);
         let transaction = Transaction::new_signed_with_payer(
-            &[instruction],
+            instructions,
             Some(&self.wallet_pubkey),
-            &[&self.wallet],
+            &all_signers,
             recent_blockhash,
         );
+        let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Adds the wallet's signature to a transaction built elsewhere (e.g. a
+    /// bags.fm launch transaction, base58 bincode) and submits it. Any other
+    /// required signatures must already be present.
+    pub async fn sign_and_send_serialized(&self, encoded: &str) -> Result<Signature> {
+        let bytes = bs58::decode(encoded).into_vec()?;
+        let mut transaction: VersionedTransaction = bincode::deserialize(&bytes)
+            .map_err(|e| anyhow!("Failed to decode transaction: {}", e))?;
+
+        let required = transaction.message.header().num_required_signatures as usize;
+        let index = transaction
+            .message
+            .static_account_keys()
+            .iter()
+            .take(required)
+            .position(|key| *key == self.wallet_pubkey)
+            .ok_or_else(|| anyhow!("Transaction does not require a signature from {}", self.wallet_pubkey))?;
+
+        if transaction.signatures.len() < required {
+            transaction.signatures.resize(required, Signature::default());
+        }
+        transaction.signatures[index] = self.wallet.sign_message(&transaction.message.serialize());
 
         let signature = self.rpc_client.send_and_confirm_transaction(&transaction)?;
-        Ok(signature.to_string())
+        Ok(signature)
     }
+}
 
-    pub async fn get_transaction(&self, signature: &str) -> Result<solana_transaction_status::EncodedConfirmedTransaction> {
-        let sig = bs58::decode(signature).into_vec()?;
-        let transaction = self.rpc_client.get_transaction(
-            &sig.try_into()?,
-            UiTransactionEncoding::Json,
-            solana_transaction_status::UiTransactionConfig {
-                encoding: Some(UiTransactionEncoding::Json),
-                commitment: Some(CommitmentConfig::confirmed()),
-                max_supported_transaction_version: Some(0),
+/// `owner`'s net change in `mint` across a transaction's token balances.
+/// Accounts the transaction opens have no pre balance and ones it closes
+/// no post balance; both count as zero there.
+pub fn owner_balance_change(
+    pre: &[UiTransactionTokenBalance],
+    post: &[UiTransactionTokenBalance],
+    owner: &str,
+    mint: &str,
+) -> Result<i128> {
+    let total = |balances: &[UiTransactionTokenBalance]| -> Result<i128> {
+        let mut total = 0i128;
+        for balance in balances {
+            let balance_owner: Option<&String> = balance.owner.as_ref().into();
+            if balance.mint == mint && balance_owner.is_some_and(|balance_owner| balance_owner == owner) {
+                total += balance.ui_token_amount.amount.parse::<i128>()
+                    .map_err(|e| anyhow!("bad token amount {:?}: {}", balance.ui_token_amount.amount, e))?;
+            }
+        }
+        Ok(total)
+    };
+    Ok(total(post)? - total(pre)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_account_decoder::parse_token::UiTokenAmount;
+    use solana_transaction_status::option_serializer::OptionSerializer;
+
+    fn balance(account_index: u8, mint: &str, owner: &str, amount: u64) -> UiTransactionTokenBalance {
+        UiTransactionTokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 6,
+                amount: amount.to_string(),
+                ui_amount_string: String::new(),
             },
-        )?;
-        Ok(transaction)
+            owner: OptionSerializer::Some(owner.to_string()),
+            program_id: OptionSerializer::Skip,
+        }
     }
-}
 
+    #[test]
+    fn counts_only_the_owners_accounts_for_the_mint() {
+        let pre = [
+            balance(1, "Mint1", "Wallet", 500),
+            balance(2, "Mint1", "Pool", 1_000_000),
+            balance(3, "Mint2", "Wallet", 70),
+        ];
+        // The wallet's Mint1 account grew; a second one was opened by the swap.
+        let post = [
+            balance(1, "Mint1", "Wallet", 800),
+            balance(2, "Mint1", "Pool", 999_700),
+            balance(4, "Mint1", "Wallet", 25),
+        ];
+        assert_eq!(owner_balance_change(&pre, &post, "Wallet", "Mint1").unwrap(), 325);
+        assert_eq!(owner_balance_change(&pre, &post, "Pool", "Mint1").unwrap(), -300);
+        // Mint2's account was closed in the transaction.
+        assert_eq!(owner_balance_change(&pre, &post, "Wallet", "Mint2").unwrap(), -70);
+    }
+}
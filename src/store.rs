@@ -0,0 +1,135 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+/// A record type persisted as one JSON object per line in its own file under
+/// `EXPORT_DIR`. The export command reads these back.
+pub trait LedgerRecord: Serialize + DeserializeOwned {
+    const FILE_NAME: &'static str;
+}
+
+/// Append-only JSONL store for everything the bot does on-chain.
+pub struct Ledger {
+    dir: PathBuf,
+}
+
+impl Ledger {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn from_config(cfg: &Config) -> Self {
+        Self::new(&cfg.export_dir)
+    }
+
+    pub fn append<T: LedgerRecord>(&self, record: &T) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("failed to create ledger dir {}", self.dir.display()))?;
+        let path = self.dir.join(T::FILE_NAME);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open ledger {}", path.display()))?;
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    pub fn read_all<T: LedgerRecord>(&self) -> Result<Vec<T>> {
+        let path = self.dir.join(T::FILE_NAME);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = fs::File::open(&path)
+            .with_context(|| format!("failed to open ledger {}", path.display()))?;
+
+        let mut records = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line)
+                .with_context(|| format!("{}:{}: malformed ledger entry", path.display(), i + 1))?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+}
+
+/// A token we launched through bags.fm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchRecord {
+    pub time_utc: String,
+    pub wallet: String,
+    pub name: String,
+    pub symbol: String,
+    pub mint: String,
+    pub metadata_uri: String,
+    pub signature: String,
+}
+
+impl LedgerRecord for LaunchRecord {
+    const FILE_NAME: &'static str = "launches.jsonl";
+}
+
+/// A creator-fee claim submitted for one of our launches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeClaimRecord {
+    pub time_utc: String,
+    pub wallet: String,
+    pub mint: String,
+    pub amount_lamports: u64,
+    pub signatures: Vec<String>,
+}
+
+impl LedgerRecord for FeeClaimRecord {
+    const FILE_NAME: &'static str = "fee_claims.jsonl";
+}
+
+/// LP fees claimed from one of our DAMM v2 positions, in base units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LpFeeRecord {
+    pub time_utc: String,
+    pub wallet: String,
+    pub pool: String,
+    pub position: String,
+    pub token_a_mint: String,
+    pub token_b_mint: String,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub signature: String,
+}
+
+impl LedgerRecord for LpFeeRecord {
+    const FILE_NAME: &'static str = "lp_fees.jsonl";
+}
+
+/// A swap we executed, with the quote it was signed against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillRecord {
+    pub time_utc: String,
+    pub venue: String,
+    pub pool: String,
+    pub input_mint: String,
+    pub output_mint: String,
+    pub amount_in: u64,
+    pub quoted_out: u64,
+    pub min_out: u64,
+    pub actual_out: u64,
+    pub slippage_bps: u32,
+    /// Positive when the fill came in under the quote.
+    pub realized_slippage_bps: i64,
+    pub price_impact_bps: u32,
+    pub signature: String,
+}
+
+impl LedgerRecord for FillRecord {
+    const FILE_NAME: &'static str = "fills.jsonl";
+}
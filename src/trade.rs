@@ -0,0 +1,187 @@
+//! The quote → `min_out` pipeline every swap goes through.
+//!
+//! A [`Quote`] becomes a [`TradePlan`] only if its price impact is under the
+//! configured ceiling; the plan's `min_out` is what gets written into the
+//! swap instruction. After the fill, [`record_fill`] stores how far the
+//! actual output landed from the quote.
+
+use crate::config::Config;
+use crate::store::{FillRecord, Ledger};
+use anyhow::{bail, Result};
+
+const BPS: u128 = 10_000;
+
+/// Slippage tolerance and price-impact ceiling for one kind of trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlippagePolicy {
+    pub slippage_bps: u32,
+    pub max_price_impact_bps: u32,
+}
+
+impl SlippagePolicy {
+    /// Regular swaps: `SLIPPAGE_BPS`.
+    pub fn from_config(cfg: &Config) -> Self {
+        Self { slippage_bps: cfg.slippage_bps, max_price_impact_bps: cfg.max_price_impact_bps }
+    }
+
+    /// Snipes into fresh launches: `SNIPER_MAX_SLIPPAGE_BPS`.
+    pub fn sniper(cfg: &Config) -> Self {
+        Self { slippage_bps: cfg.sniper_max_slippage_bps, max_price_impact_bps: cfg.max_price_impact_bps }
+    }
+
+    pub fn min_out(&self, amount_out: u64) -> u64 {
+        min_out(amount_out, self.slippage_bps)
+    }
+
+    /// Turns a quote into a signed-off plan, or refuses the trade when the
+    /// price impact is over the ceiling or nothing would come out.
+    pub fn plan(&self, quote: Quote) -> Result<TradePlan> {
+        if quote.price_impact_bps > self.max_price_impact_bps {
+            bail!(
+                "Price impact {:.2}% exceeds the {:.2}% ceiling; not signing",
+                quote.price_impact_bps as f64 / 100.0,
+                self.max_price_impact_bps as f64 / 100.0
+            );
+        }
+        let min_out = self.min_out(quote.amount_out);
+        if min_out == 0 {
+            bail!("Quote of {} for {} leaves no minimum output after slippage", quote.amount_out, quote.amount_in);
+        }
+        Ok(TradePlan { quote, min_out, slippage_bps: self.slippage_bps })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    /// How much worse than the marginal price this fill is, fees excluded.
+    pub price_impact_bps: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradePlan {
+    pub quote: Quote,
+    pub min_out: u64,
+    pub slippage_bps: u32,
+}
+
+/// `amount_out` less `slippage_bps`, rounded down.
+pub fn min_out(amount_out: u64, slippage_bps: u32) -> u64 {
+    (amount_out as u128 * BPS.saturating_sub(slippage_bps as u128) / BPS) as u64
+}
+
+/// `amount_in` plus `slippage_bps`, rounded up: the most a deposit may spend.
+pub fn max_in(amount_in: u64, slippage_bps: u32) -> u64 {
+    let scaled = amount_in as u128 * (BPS + slippage_bps as u128);
+    u64::try_from(scaled.div_ceil(BPS)).unwrap_or(u64::MAX)
+}
+
+/// Price impact of getting `amount_out` for `amount_in` when a marginal
+/// trade of `probe_in` gets `probe_out`.
+pub fn price_impact_bps(amount_in: u64, amount_out: u64, probe_in: u64, probe_out: u64) -> u32 {
+    if amount_in == 0 || probe_in == 0 || probe_out == 0 {
+        return 0;
+    }
+    // 1 - (out/in) / (probe_out/probe_in)
+    let executed = amount_out as u128 * probe_in as u128;
+    let marginal = probe_out as u128 * amount_in as u128;
+    (BPS * marginal.saturating_sub(executed) / marginal) as u32
+}
+
+/// Realized slippage in bps versus the quote; negative when the fill beat it.
+pub fn realized_slippage_bps(quoted_out: u64, actual_out: u64) -> i64 {
+    if quoted_out == 0 {
+        return 0;
+    }
+    ((quoted_out as i128 - actual_out as i128) * BPS as i128 / quoted_out as i128) as i64
+}
+
+/// What a trade was and where it landed, for [`record_fill`].
+#[derive(Debug, Clone)]
+pub struct Fill<'a> {
+    pub venue: &'a str,
+    pub pool: &'a str,
+    pub input_mint: &'a str,
+    pub output_mint: &'a str,
+    pub plan: &'a TradePlan,
+    pub actual_out: u64,
+    pub signature: &'a str,
+}
+
+pub fn record_fill(ledger: &Ledger, fill: &Fill<'_>) -> Result<FillRecord> {
+    let slippage = realized_slippage_bps(fill.plan.quote.amount_out, fill.actual_out);
+    log::info!(
+        "Fill {}: {} in -> {} out (quoted {}, min {}), realized slippage {} bps",
+        fill.signature, fill.plan.quote.amount_in, fill.actual_out, fill.plan.quote.amount_out, fill.plan.min_out, slippage
+    );
+    let record = FillRecord {
+        time_utc: chrono::Utc::now().to_rfc3339(),
+        venue: fill.venue.to_string(),
+        pool: fill.pool.to_string(),
+        input_mint: fill.input_mint.to_string(),
+        output_mint: fill.output_mint.to_string(),
+        amount_in: fill.plan.quote.amount_in,
+        quoted_out: fill.plan.quote.amount_out,
+        min_out: fill.plan.min_out,
+        actual_out: fill.actual_out,
+        slippage_bps: fill.plan.slippage_bps,
+        realized_slippage_bps: slippage,
+        price_impact_bps: fill.plan.quote.price_impact_bps,
+        signature: fill.signature.to_string(),
+    };
+    ledger.append(&record)?;
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(amount_out: u64, price_impact_bps: u32) -> Quote {
+        Quote { amount_in: 1_000, amount_out, fee: 3, price_impact_bps }
+    }
+
+    #[test]
+    fn plan_applies_slippage_and_the_impact_ceiling() {
+        let policy = SlippagePolicy { slippage_bps: 75, max_price_impact_bps: 300 };
+        assert_eq!(policy.plan(quote(1_000_000, 300)).unwrap().min_out, 992_500);
+        assert_eq!(policy.min_out(999), 991);
+
+        let err = policy.plan(quote(1_000_000, 301)).unwrap_err();
+        assert!(err.to_string().contains("exceeds"), "{}", err);
+        assert!(policy.plan(quote(1, 0)).is_err());
+    }
+
+    #[test]
+    fn impact_and_realized_slippage() {
+        // Marginal price 2.0; the fill got 1.9.
+        assert_eq!(price_impact_bps(1_000, 1_900, 10, 20), 500);
+        assert_eq!(price_impact_bps(1_000, 2_100, 10, 20), 0);
+        assert_eq!(realized_slippage_bps(10_000, 9_950), 50);
+        assert_eq!(realized_slippage_bps(10_000, 10_020), -20);
+    }
+
+    #[test]
+    fn fills_are_recorded_in_the_ledger() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = Ledger::new(dir.path());
+        let plan = SlippagePolicy { slippage_bps: 100, max_price_impact_bps: 500 }.plan(quote(10_000, 20)).unwrap();
+        let fill = Fill {
+            venue: "raydium",
+            pool: "Pool1",
+            input_mint: "So11111111111111111111111111111111111111112",
+            output_mint: "Mint1",
+            plan: &plan,
+            actual_out: 9_960,
+            signature: "sig",
+        };
+        record_fill(&ledger, &fill).unwrap();
+
+        let records: Vec<FillRecord> = ledger.read_all().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].min_out, 9_900);
+        assert_eq!(records[0].realized_slippage_bps, 40);
+    }
+}
@@ -0,0 +1,409 @@
+//! Seams between the commands and the outside world. Each live client
+//! implements one of these traits; `crate::mock` provides in-memory versions
+//! so command flows can run without a network.
+
+use crate::aggregator::{AggregatorClient, ProviderQuote};
+use crate::bagsfm::{
+    BagsFmCacheStats, BagsFmClient, BagsFmError, BagsFmLaunch, BagsFmToken, ClaimableFee, FeeClaimRequest,
+    FeeClaimTransactions, LaunchMetadata, LaunchMetadataRequest, LaunchTransaction, LaunchTransactionRequest,
+};
+use crate::dex::{DexClient, DexPool, LiquidityQuote};
+use crate::meteora::fee::{FeeClock, FeeSchedule};
+use crate::meteora::{damm_v2, DammPositionChange, DammPositionFees, DbcProgress, DbcSide, MeteoraClient, MeteoraDammPool, MeteoraDbcPool, SwapFill};
+use crate::solana::SolanaClient;
+use crate::trade::Quote;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use async_trait::async_trait;
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, signature::{Keypair, Signature}};
+
+/// The hot wallet and the chain reads the commands need.
+#[async_trait]
+pub trait SolanaApi: Send + Sync {
+    fn wallet_pubkey(&self) -> Pubkey;
+    async fn get_balance(&self) -> Result<u64>;
+    async fn get_token_balance(&self, token_mint: &str) -> Result<u64>;
+    /// Net change in the wallet's `token_mint` balance made by the confirmed
+    /// transaction `signature` alone, from its pre/post token balances.
+    async fn token_balance_change(&self, signature: &Signature, token_mint: &str) -> Result<i128>;
+    async fn sign_and_send_serialized(&self, encoded: &str) -> Result<Signature>;
+    /// Signs `instructions` as one wallet-paid transaction and submits it.
+    async fn send_instructions(&self, instructions: &[Instruction]) -> Result<Signature>;
+    /// As `send_instructions`, with extra `signers` next to the wallet.
+    async fn send_instructions_with_signers(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature>;
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>>;
+}
+
+/// A token launchpad (bags.fm).
+#[async_trait]
+pub trait LaunchpadApi: Send + Sync {
+    async fn get_token_info(&self, token_address: &str) -> Result<BagsFmToken, BagsFmError>;
+    async fn get_launches_since(&self, since: Option<DateTime<Utc>>, limit: u32) -> Result<Vec<BagsFmLaunch>, BagsFmError>;
+    async fn create_launch_metadata(&self, request: &LaunchMetadataRequest) -> Result<LaunchMetadata, BagsFmError>;
+    async fn create_launch_transaction(&self, request: &LaunchTransactionRequest) -> Result<LaunchTransaction, BagsFmError>;
+    async fn get_claimable_fees(&self, wallet: &str) -> Result<Vec<ClaimableFee>, BagsFmError>;
+    async fn create_fee_claim_transactions(&self, request: &FeeClaimRequest) -> Result<FeeClaimTransactions, BagsFmError>;
+
+    /// Response-cache counters, for implementations that cache.
+    fn cache_stats(&self) -> Option<BagsFmCacheStats> {
+        None
+    }
+}
+
+/// Spot pools across the supported DEX programs (Raydium, Orca, Meteora).
+#[async_trait]
+pub trait AmmVenue: Send + Sync {
+    async fn get_pool_info(&self, pool_address: &str) -> Result<DexPool>;
+    /// Every pool trading `mint`, deepest first.
+    async fn find_pools(&self, mint: &str) -> Result<Vec<DexPool>>;
+    /// The token program that owns `mint`: SPL Token or Token-2022.
+    async fn token_program(&self, mint: &str) -> Result<Pubkey>;
+    async fn calculate_swap_amounts(&self, pool: &DexPool, input_amount: u64, is_input_a: bool) -> Result<(u64, u64)>;
+    async fn create_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction>;
+    async fn add_liquidity(&self, pool: &DexPool, max_a: u64, max_b: u64) -> Result<LiquidityQuote>;
+    async fn remove_liquidity(&self, pool: &DexPool, lp_amount: u128) -> Result<LiquidityQuote>;
+    async fn lp_balance(&self, pool: &DexPool, owner: &Pubkey, position: Option<&str>) -> Result<u128>;
+    async fn create_add_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>>;
+    async fn create_remove_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>>;
+}
+
+/// An external quote source to compare the internal router against (a
+/// Jupiter-compatible aggregator).
+#[async_trait]
+pub trait QuoteProvider: Send + Sync {
+    fn name(&self) -> &str;
+    async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64, slippage_bps: u32) -> Result<ProviderQuote>;
+    /// The provider's transaction for `quote`, base58 bincode, unsigned.
+    async fn swap_transaction(&self, quote: &ProviderQuote, user: &Pubkey) -> Result<String>;
+}
+
+/// A bonding-curve launch venue (Meteora DBC) and its graduation target.
+#[async_trait]
+pub trait BondingCurveVenue: Send + Sync {
+    async fn get_dbc_pool_info(&self, pool_address: &str) -> Result<MeteoraDbcPool>;
+    async fn find_dbc_pool(&self, token_mint: &str) -> Result<MeteoraDbcPool>;
+    async fn buy_from_dbc(&self, pool: &MeteoraDbcPool, amount_base: f64) -> Result<Quote>;
+    async fn dbc_fee_schedule(&self, pool: &MeteoraDbcPool) -> Result<(FeeSchedule, FeeClock)>;
+    async fn quote_dbc_swap(&self, pool: &MeteoraDbcPool, side: DbcSide, amount_in: u64) -> Result<(u64, u64)>;
+    async fn swap_dbc(
+        &self,
+        pool: &MeteoraDbcPool,
+        side: DbcSide,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill>;
+    async fn migrate_to_damm(&self, dbc_pool: &MeteoraDbcPool) -> Result<MeteoraDammPool>;
+    async fn curve_progress(&self, token_mint: &str) -> Result<DbcProgress>;
+    async fn find_damm_v2_pool(&self, token_mint: &str) -> Result<Option<String>>;
+    async fn get_damm_pool(&self, pool_address: &str) -> Result<MeteoraDammPool>;
+    async fn quote_damm_swap(&self, pool: &MeteoraDammPool, input_mint: &str, amount_in: u64) -> Result<(u64, u64)>;
+    async fn swap_damm(
+        &self,
+        pool: &MeteoraDammPool,
+        input_mint: &str,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill>;
+    async fn open_damm_position(
+        &self,
+        pool: &MeteoraDammPool,
+        max_a: u64,
+        max_b: u64,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange>;
+    async fn decrease_damm_position(
+        &self,
+        position: &str,
+        liquidity: Option<u128>,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange>;
+    async fn get_damm_position(&self, position: &str) -> Result<damm_v2::Position>;
+    async fn close_damm_position(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature>;
+    async fn unclaimed_damm_fees(&self, owner: &Pubkey) -> Result<Vec<DammPositionFees>>;
+    async fn claim_damm_fees(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature>;
+}
+
+#[async_trait]
+impl SolanaApi for SolanaClient {
+    fn wallet_pubkey(&self) -> Pubkey {
+        self.wallet_pubkey
+    }
+
+    async fn get_balance(&self) -> Result<u64> {
+        SolanaClient::get_balance(self).await
+    }
+
+    async fn get_token_balance(&self, token_mint: &str) -> Result<u64> {
+        SolanaClient::get_token_balance(self, token_mint).await
+    }
+
+    async fn token_balance_change(&self, signature: &Signature, token_mint: &str) -> Result<i128> {
+        SolanaClient::token_balance_change(self, signature, token_mint).await
+    }
+
+    async fn sign_and_send_serialized(&self, encoded: &str) -> Result<Signature> {
+        SolanaClient::sign_and_send_serialized(self, encoded).await
+    }
+
+    async fn send_instructions(&self, instructions: &[Instruction]) -> Result<Signature> {
+        SolanaClient::send_instructions(self, instructions).await
+    }
+
+    async fn send_instructions_with_signers(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        SolanaClient::send_instructions_with_signers(self, instructions, signers).await
+    }
+
+    async fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let mut signatures = self.rpc_client.get_signatures_for_address(address)?;
+        signatures.truncate(limit);
+        Ok(signatures)
+    }
+}
+
+#[async_trait]
+impl LaunchpadApi for BagsFmClient {
+    async fn get_token_info(&self, token_address: &str) -> Result<BagsFmToken, BagsFmError> {
+        BagsFmClient::get_token_info(self, token_address).await
+    }
+
+    async fn get_launches_since(&self, since: Option<DateTime<Utc>>, limit: u32) -> Result<Vec<BagsFmLaunch>, BagsFmError> {
+        BagsFmClient::get_launches_since(self, since, limit).await
+    }
+
+    async fn create_launch_metadata(&self, request: &LaunchMetadataRequest) -> Result<LaunchMetadata, BagsFmError> {
+        BagsFmClient::create_launch_metadata(self, request).await
+    }
+
+    async fn create_launch_transaction(&self, request: &LaunchTransactionRequest) -> Result<LaunchTransaction, BagsFmError> {
+        BagsFmClient::create_launch_transaction(self, request).await
+    }
+
+    async fn get_claimable_fees(&self, wallet: &str) -> Result<Vec<ClaimableFee>, BagsFmError> {
+        BagsFmClient::get_claimable_fees(self, wallet).await
+    }
+
+    async fn create_fee_claim_transactions(&self, request: &FeeClaimRequest) -> Result<FeeClaimTransactions, BagsFmError> {
+        BagsFmClient::create_fee_claim_transactions(self, request).await
+    }
+
+    fn cache_stats(&self) -> Option<BagsFmCacheStats> {
+        Some(BagsFmClient::cache_stats(self))
+    }
+}
+
+#[async_trait]
+impl AmmVenue for DexClient {
+    async fn get_pool_info(&self, pool_address: &str) -> Result<DexPool> {
+        DexClient::get_pool_info(self, pool_address).await
+    }
+
+    async fn find_pools(&self, mint: &str) -> Result<Vec<DexPool>> {
+        DexClient::find_pools(self, mint).await
+    }
+
+    async fn token_program(&self, mint: &str) -> Result<Pubkey> {
+        DexClient::token_program(self, mint).await
+    }
+
+    async fn calculate_swap_amounts(&self, pool: &DexPool, input_amount: u64, is_input_a: bool) -> Result<(u64, u64)> {
+        DexClient::calculate_swap_amounts(self, pool, input_amount, is_input_a).await
+    }
+
+    async fn create_swap_instruction(
+        &self,
+        pool: &DexPool,
+        user_wallet: &Pubkey,
+        input_mint: &str,
+        output_mint: &str,
+        input_amount: u64,
+        min_output_amount: u64,
+    ) -> Result<Instruction> {
+        DexClient::create_swap_instruction(self, pool, user_wallet, input_mint, output_mint, input_amount, min_output_amount).await
+    }
+
+    async fn add_liquidity(&self, pool: &DexPool, max_a: u64, max_b: u64) -> Result<LiquidityQuote> {
+        DexClient::add_liquidity(self, pool, max_a, max_b).await
+    }
+
+    async fn remove_liquidity(&self, pool: &DexPool, lp_amount: u128) -> Result<LiquidityQuote> {
+        DexClient::remove_liquidity(self, pool, lp_amount).await
+    }
+
+    async fn lp_balance(&self, pool: &DexPool, owner: &Pubkey, position: Option<&str>) -> Result<u128> {
+        DexClient::lp_balance(self, pool, owner, position).await
+    }
+
+    async fn create_add_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        DexClient::create_add_liquidity_instructions(self, pool, owner, quote, slippage_bps, position).await
+    }
+
+    async fn create_remove_liquidity_instructions(
+        &self,
+        pool: &DexPool,
+        owner: &Pubkey,
+        quote: &LiquidityQuote,
+        slippage_bps: u32,
+        position: Option<&str>,
+    ) -> Result<Vec<Instruction>> {
+        DexClient::create_remove_liquidity_instructions(self, pool, owner, quote, slippage_bps, position).await
+    }
+}
+
+#[async_trait]
+impl BondingCurveVenue for MeteoraClient {
+    async fn get_dbc_pool_info(&self, pool_address: &str) -> Result<MeteoraDbcPool> {
+        MeteoraClient::get_dbc_pool_info(self, pool_address).await
+    }
+
+    async fn find_dbc_pool(&self, token_mint: &str) -> Result<MeteoraDbcPool> {
+        MeteoraClient::find_dbc_pool(self, token_mint).await
+    }
+
+    async fn buy_from_dbc(&self, pool: &MeteoraDbcPool, amount_base: f64) -> Result<Quote> {
+        MeteoraClient::buy_from_dbc(self, pool, amount_base).await
+    }
+
+    async fn dbc_fee_schedule(&self, pool: &MeteoraDbcPool) -> Result<(FeeSchedule, FeeClock)> {
+        MeteoraClient::dbc_fee_schedule(self, pool).await
+    }
+
+    async fn quote_dbc_swap(&self, pool: &MeteoraDbcPool, side: DbcSide, amount_in: u64) -> Result<(u64, u64)> {
+        MeteoraClient::quote_dbc_swap(self, pool, side, amount_in).await
+    }
+
+    async fn swap_dbc(
+        &self,
+        pool: &MeteoraDbcPool,
+        side: DbcSide,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        MeteoraClient::swap_dbc(self, pool, side, amount_in, min_out, solana_client).await
+    }
+
+    async fn migrate_to_damm(&self, dbc_pool: &MeteoraDbcPool) -> Result<MeteoraDammPool> {
+        MeteoraClient::migrate_to_damm(self, dbc_pool).await
+    }
+
+    async fn curve_progress(&self, token_mint: &str) -> Result<DbcProgress> {
+        MeteoraClient::curve_progress(self, token_mint).await
+    }
+
+    async fn find_damm_v2_pool(&self, token_mint: &str) -> Result<Option<String>> {
+        MeteoraClient::find_damm_v2_pool(self, token_mint).await
+    }
+
+    async fn get_damm_pool(&self, pool_address: &str) -> Result<MeteoraDammPool> {
+        MeteoraClient::get_damm_pool(self, pool_address).await
+    }
+
+    async fn quote_damm_swap(&self, pool: &MeteoraDammPool, input_mint: &str, amount_in: u64) -> Result<(u64, u64)> {
+        MeteoraClient::quote_damm_swap(self, pool, input_mint, amount_in).await
+    }
+
+    async fn swap_damm(
+        &self,
+        pool: &MeteoraDammPool,
+        input_mint: &str,
+        amount_in: u64,
+        min_out: u64,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<SwapFill> {
+        MeteoraClient::swap_damm(self, pool, input_mint, amount_in, min_out, solana_client).await
+    }
+
+    async fn open_damm_position(
+        &self,
+        pool: &MeteoraDammPool,
+        max_a: u64,
+        max_b: u64,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        MeteoraClient::open_damm_position(self, pool, max_a, max_b, slippage_bps, solana_client).await
+    }
+
+    async fn decrease_damm_position(
+        &self,
+        position: &str,
+        liquidity: Option<u128>,
+        slippage_bps: u32,
+        solana_client: &dyn SolanaApi,
+    ) -> Result<DammPositionChange> {
+        MeteoraClient::decrease_damm_position(self, position, liquidity, slippage_bps, solana_client).await
+    }
+
+    async fn get_damm_position(&self, position: &str) -> Result<damm_v2::Position> {
+        MeteoraClient::get_damm_position(self, position).await
+    }
+
+    async fn close_damm_position(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        MeteoraClient::close_damm_position(self, position, solana_client).await
+    }
+
+    async fn unclaimed_damm_fees(&self, owner: &Pubkey) -> Result<Vec<DammPositionFees>> {
+        MeteoraClient::unclaimed_damm_fees(self, owner).await
+    }
+
+    async fn claim_damm_fees(&self, position: &str, solana_client: &dyn SolanaApi) -> Result<Signature> {
+        MeteoraClient::claim_damm_fees(self, position, solana_client).await
+    }
+}
+
+#[async_trait]
+impl QuoteProvider for AggregatorClient {
+    fn name(&self) -> &str {
+        self.base_url()
+    }
+
+    async fn quote(&self, input_mint: &str, output_mint: &str, amount_in: u64, slippage_bps: u32) -> Result<ProviderQuote> {
+        AggregatorClient::quote(self, input_mint, output_mint, amount_in, slippage_bps).await
+    }
+
+    async fn swap_transaction(&self, quote: &ProviderQuote, user: &Pubkey) -> Result<String> {
+        AggregatorClient::swap_transaction(self, quote, user).await
+    }
+}